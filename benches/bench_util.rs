@@ -2,6 +2,7 @@ use rand_core::{CryptoRng, RngCore};
 use sha2::{Digest, Sha512};
 
 // Fake RNG for benchmarks only. NOT ACTUALLY SECURE! DO NOT USE!
+#[allow(clippy::upper_case_acronyms)]
 pub struct DRNG {
     buf: [u8; 64],
     ptr: usize,
@@ -24,6 +25,12 @@ impl DRNG {
     }
 }
 
+impl Default for DRNG {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RngCore for DRNG {
     fn next_u32(&mut self) -> u32 {
         let mut buf = [0u8; 4];
@@ -50,7 +57,7 @@ impl RngCore for DRNG {
             off += clen;
             if self.ptr == 32 {
                 let mut sh = Sha512::new();
-                sh.update(&self.buf);
+                sh.update(self.buf);
                 self.buf[..].copy_from_slice(&sh.finalize());
                 self.ptr = 0;
             }
@@ -84,6 +84,131 @@ macro_rules! define_fp2_benchmarks {
     };
 }
 
+// Compares the addition chains derived for the fixed exponents of square
+// roots, fourth roots and Legendre symbols against the 4-bit window of
+// pow_pubexp(), which Fp uses instead when the chain is not shorter (see
+// addchain::select()).
+macro_rules! define_chain_benchmarks {
+    ($Fq:ty) => {
+        fn benchmark_chain_exponent(c: &mut Criterion, name: &str, e: &[u64; <$Fq>::N]) {
+            let mut rng = crate::bench_util::DRNG::new();
+            let x = <$Fq>::rand(&mut rng);
+            let mut ops =
+                vec![fp2::addchain::ChainOp::Square(0, 0, 0); fp2::addchain::derived_len(e)];
+            fp2::addchain::derive_into(e, &mut ops);
+
+            let bench_id = format!(
+                "Benchmarking (addition chain) {name} over Fp with {} bits",
+                <$Fq>::BIT_LENGTH
+            );
+            c.bench_function(&bench_id, |b| b.iter(|| black_box(x).pow_chain(&ops)));
+            let bench_id = format!(
+                "Benchmarking (pow_pubexp) {name} over Fp with {} bits",
+                <$Fq>::BIT_LENGTH
+            );
+            c.bench_function(&bench_id, |b| b.iter(|| black_box(x).pow_pubexp(e)));
+        }
+
+        fn benchmark_chain_sqrt(c: &mut Criterion) {
+            let e = fp2::addchain::sqrt_exponent(&<$Fq>::MODULUS);
+            benchmark_chain_exponent(c, "x^((p+1)/4)", &e);
+        }
+
+        fn benchmark_chain_fourth_root(c: &mut Criterion) {
+            let e = fp2::addchain::fourth_root_exponent(&<$Fq>::MODULUS);
+            benchmark_chain_exponent(c, "x^((p+1)/8)", &e);
+        }
+
+        fn benchmark_chain_legendre(c: &mut Criterion) {
+            let e = fp2::addchain::legendre_exponent(&<$Fq>::MODULUS);
+            benchmark_chain_exponent(c, "x^((p-1)/2)", &e);
+        }
+
+        criterion_group! {
+            name = chain_benchmarks;
+            config = Criterion::default().measurement_time(Duration::from_secs(3));
+            targets = benchmark_chain_sqrt, benchmark_chain_fourth_root, benchmark_chain_legendre
+        }
+    };
+}
+
+// Compares the interleaved Montgomery multiplication against Karatsuba
+// followed by Montgomery reduction, to tune utils64::KARATSUBA_THRESHOLD.
+macro_rules! define_karatsuba_benchmarks {
+    ($Fq:ty, $FqK:ty) => {
+        fn benchmark_interleaved_mul(c: &mut Criterion) {
+            let mut rng = crate::bench_util::DRNG::new();
+
+            let x = <$Fq>::rand(&mut rng);
+            let y = <$Fq>::rand(&mut rng);
+
+            let bench_id = format!(
+                "Benchmarking (interleaved) x * y over Fp with {} bits",
+                <$Fq>::BIT_LENGTH
+            );
+            c.bench_function(&bench_id, |b| b.iter(|| black_box(x) * black_box(y)));
+        }
+
+        fn benchmark_karatsuba_mul(c: &mut Criterion) {
+            let mut rng = crate::bench_util::DRNG::new();
+
+            let x = <$FqK>::rand(&mut rng);
+            let y = <$FqK>::rand(&mut rng);
+
+            let bench_id = format!(
+                "Benchmarking (karatsuba) x * y over Fp with {} bits",
+                <$FqK>::BIT_LENGTH
+            );
+            c.bench_function(&bench_id, |b| b.iter(|| black_box(x) * black_box(y)));
+        }
+
+        criterion_group! {
+            name = karatsuba_benchmarks;
+            config = Criterion::default().measurement_time(Duration::from_secs(3));
+            targets = benchmark_interleaved_mul, benchmark_karatsuba_mul
+        }
+    };
+}
+
+// Moduli of the form c*2^k - 1 used for the Karatsuba benchmarks: all
+// words are 0xFFFFFFFFFFFFFFFF except for the top one.
+const fn karatsuba_bench_modulus<const N: usize>(top: u64) -> [u64; N] {
+    let mut m = [u64::MAX; N];
+    m[N - 1] = top;
+    m
+}
+
+macro_rules! define_karatsuba_bench_module {
+    ($name:ident, $n:literal, $top:literal) => {
+        mod $name {
+            use criterion::{Criterion, black_box, criterion_group};
+            use std::time::Duration;
+
+            const MODULUS: [u64; $n] = crate::karatsuba_bench_modulus($top);
+
+            fp2::define_fp_core!(
+                typename = Fp,
+                modulus = MODULUS,
+                karatsuba_threshold = usize::MAX,
+            );
+            fp2::define_fp_core!(typename = FpK, modulus = MODULUS, karatsuba_threshold = 1,);
+
+            define_karatsuba_benchmarks!(Fp, FpK);
+        }
+    };
+}
+
+// p = 303*2^1015 - 1
+define_karatsuba_bench_module!(bench_karatsuba_1024, 16, 0x977FFFFFFFFFFFFF);
+// p = 273*2^1527 - 1
+define_karatsuba_bench_module!(bench_karatsuba_1536, 24, 0x887FFFFFFFFFFFFF);
+// p = 605*2^2038 - 1
+define_karatsuba_bench_module!(bench_karatsuba_2048, 32, 0x973FFFFFFFFFFFFF);
+// p = 75*2^3065 - 1
+define_karatsuba_bench_module!(bench_karatsuba_3072, 48, 0x95FFFFFFFFFFFFFF);
+// p = 3^2*7*2^4084 - 1
+define_karatsuba_bench_module!(bench_karatsuba_4090, 64, 0x03EFFFFFFFFFFFFF);
+
 mod bench_251 {
     use criterion::{Criterion, black_box, criterion_group, criterion_main};
     use std::time::Duration;
@@ -99,8 +224,9 @@ mod bench_251 {
 
     define_fp_benchmarks!(Fp);
     define_fp2_benchmarks!(Fp2);
+    define_chain_benchmarks!(Fp);
 
-    criterion_main!(fp_benchmarks, fp2_benchmarks);
+    criterion_main!(fp_benchmarks, fp2_benchmarks, chain_benchmarks);
 }
 
 mod bench_508 {
@@ -122,8 +248,9 @@ mod bench_508 {
 
     define_fp_benchmarks!(Fp);
     define_fp2_benchmarks!(Fp2);
+    define_chain_benchmarks!(Fp);
 
-    criterion_main!(fp_benchmarks, fp2_benchmarks);
+    criterion_main!(fp_benchmarks, fp2_benchmarks, chain_benchmarks);
 }
 
 mod bench_896 {
@@ -151,8 +278,9 @@ mod bench_896 {
 
     define_fp_benchmarks!(Fp);
     define_fp2_benchmarks!(Fp2);
+    define_chain_benchmarks!(Fp);
 
-    criterion_main!(fp_benchmarks, fp2_benchmarks);
+    criterion_main!(fp_benchmarks, fp2_benchmarks, chain_benchmarks);
 }
 
 mod bench_1008 {
@@ -182,8 +310,9 @@ mod bench_1008 {
 
     define_fp_benchmarks!(Fp);
     define_fp2_benchmarks!(Fp2);
+    define_chain_benchmarks!(Fp);
 
-    criterion_main!(fp_benchmarks, fp2_benchmarks);
+    criterion_main!(fp_benchmarks, fp2_benchmarks, chain_benchmarks);
 }
 
 mod bench_1554 {
@@ -222,23 +351,35 @@ mod bench_1554 {
 
     define_fp_benchmarks!(Fp);
     define_fp2_benchmarks!(Fp2);
+    define_chain_benchmarks!(Fp);
 
-    criterion_main!(fp_benchmarks, fp2_benchmarks);
+    criterion_main!(fp_benchmarks, fp2_benchmarks, chain_benchmarks);
 }
 
 fn main() {
     bench_251::fp_benchmarks();
     bench_251::fp2_benchmarks();
+    bench_251::chain_benchmarks();
 
     bench_508::fp_benchmarks();
     bench_508::fp2_benchmarks();
+    bench_508::chain_benchmarks();
 
     bench_896::fp_benchmarks();
     bench_896::fp2_benchmarks();
+    bench_896::chain_benchmarks();
 
     bench_1008::fp_benchmarks();
     bench_1008::fp2_benchmarks();
+    bench_1008::chain_benchmarks();
 
     bench_1554::fp_benchmarks();
     bench_1554::fp2_benchmarks();
+    bench_1554::chain_benchmarks();
+
+    bench_karatsuba_1024::karatsuba_benchmarks();
+    bench_karatsuba_1536::karatsuba_benchmarks();
+    bench_karatsuba_2048::karatsuba_benchmarks();
+    bench_karatsuba_3072::karatsuba_benchmarks();
+    bench_karatsuba_4090::karatsuba_benchmarks();
 }
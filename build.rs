@@ -0,0 +1,18 @@
+// The only job of this build script is to compile the C driver of
+// tests/test_c_api.rs, with the `c-api-test` feature. The driver is linked
+// explicitly by that test, and nothing is linked into the library.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "c-api-test")]
+    {
+        println!("cargo:rerun-if-changed=tests/c");
+        cc::Build::new()
+            .file("tests/c/test_c_api.c")
+            .include("tests/c")
+            .cargo_metadata(false)
+            .compile("fp2_c_api_test");
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        println!("cargo:rustc-link-search=native={out_dir}");
+    }
+}
@@ -0,0 +1,477 @@
+//! Addition chains for the fixed exponents of a finite field Fp.
+//!
+//! Square roots, fourth roots, progenitors (see `Fp::progenitor()`) and, on
+//! the 32-bit backend, inversions and Legendre symbols are exponentiations by
+//! constants which only depend on the modulus: (p+1)/4, (p+1)/8, (p-3)/4, p-2
+//! and (p-1)/2. Rather than exploring the bits
+//! of these exponents at runtime, `define_fp_core` derives a chain of
+//! multiplications and squarings for each of them at compile time, which
+//! `Fp::set_pow_chain()` then evaluates. Chains may also be given explicitly,
+//! e.g. as found by a dedicated search tool for a given prime; they are
+//! checked against the modulus at compile time.
+//!
+//! A chain is a sequence of operations over `CHAIN_TEMPS` temporaries, which
+//! all start with the value x to exponentiate. Once all operations have been
+//! applied, the first temporary contains the result. The derived chains use a
+//! sliding window over the odd powers x, x^3, x^5... with the window width
+//! which minimizes the number of multiplications, and only precompute the
+//! powers which are used. A chain, derived or given, is only used when it
+//! needs fewer multiplications and squarings than the 4-bit window of
+//! `Fp::set_pow_pubexp()` (see `select()`), which is the fallback otherwise.
+
+use crate::fp::{FieldParams, Fp};
+
+/// Number of temporaries available to an addition chain.
+pub const CHAIN_TEMPS: usize = 32;
+
+/// An operation of an addition chain, on temporaries `t[0]` to
+/// `t[CHAIN_TEMPS - 1]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainOp {
+    /// `Mul(d, a, b)` sets `t[d]` to `t[a] * t[b]`.
+    Mul(u8, u8, u8),
+    /// `Square(d, a, n)` sets `t[d]` to `t[a]` squared `n` times (a copy if
+    /// `n` is zero).
+    Square(u8, u8, u32),
+}
+
+/// Addition chains for the fixed exponents of a field (see the module
+/// documentation), as given to `FieldParams::CHAINS`.
+#[derive(Clone, Copy, Debug)]
+pub struct Chains {
+    /// Chain for (p+1)/4, used by square roots.
+    pub sqrt: &'static [ChainOp],
+    /// Chain for (p+1)/8 (rounded down), used by fourth roots when
+    /// p = 7 mod 8.
+    pub fourth_root: &'static [ChainOp],
+    /// Chain for (p-3)/4, used by progenitors.
+    pub progenitor: &'static [ChainOp],
+    /// Chain for (p-1)/2, used by Legendre symbols (`W32` backend only).
+    pub legendre: &'static [ChainOp],
+    /// Chain for p-2, used by inversions (`W32` backend only).
+    pub inverse: &'static [ChainOp],
+}
+
+// Return (p + d) >> s, for a small d.
+const fn shifted<const N: usize>(modulus: &[u64; N], d: i64, s: u32) -> [u64; N] {
+    let mut r = *modulus;
+    let mut c = d.unsigned_abs();
+    let mut i = 0;
+    while i < N && c != 0 {
+        let (x, o) = if d > 0 {
+            r[i].overflowing_add(c)
+        } else {
+            r[i].overflowing_sub(c)
+        };
+        r[i] = x;
+        c = o as u64;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < N {
+        r[i] >>= s;
+        if s > 0 && i + 1 < N {
+            r[i] |= r[i + 1] << (64 - s);
+        }
+        i += 1;
+    }
+    r
+}
+
+/// Return (p+1)/4, for the modulus p.
+pub const fn sqrt_exponent<const N: usize>(modulus: &[u64; N]) -> [u64; N] {
+    shifted(modulus, 1, 2)
+}
+
+/// Return (p+1)/8 (rounded down), for the modulus p.
+pub const fn fourth_root_exponent<const N: usize>(modulus: &[u64; N]) -> [u64; N] {
+    shifted(modulus, 1, 3)
+}
+
+/// Return (p-3)/4, for the modulus p.
+pub const fn progenitor_exponent<const N: usize>(modulus: &[u64; N]) -> [u64; N] {
+    shifted(modulus, -3, 2)
+}
+
+/// Return (p-1)/2, for the modulus p.
+pub const fn legendre_exponent<const N: usize>(modulus: &[u64; N]) -> [u64; N] {
+    shifted(modulus, -1, 1)
+}
+
+/// Return p-2, for the modulus p.
+pub const fn inverse_exponent<const N: usize>(modulus: &[u64; N]) -> [u64; N] {
+    shifted(modulus, -2, 0)
+}
+
+// Bit length of e.
+const fn bitlen(e: &[u64]) -> usize {
+    let mut n = e.len();
+    while n > 0 && e[n - 1] == 0 {
+        n -= 1;
+    }
+    if n == 0 {
+        return 0;
+    }
+    64 * n - (e[n - 1].leading_zeros() as usize)
+}
+
+const fn bit(e: &[u64], i: usize) -> u64 {
+    (e[i >> 6] >> (i & 63)) & 1
+}
+
+// Scan e from the top with a sliding window of w bits, and either write the
+// operations to ops (if maxc is the largest window value), or return the
+// largest window value (if maxc is zero). The number of operations is
+// returned in both cases; operations beyond the length of ops are not
+// written.
+const fn emit(e: &[u64], w: usize, maxc: usize, ops: &mut [ChainOp]) -> (usize, usize) {
+    // Temporaries: t[0] is the accumulator, t[1 + k] is x^(2*k + 1) for
+    // k <= maxc/2, and x^2 is right after them, so that the chain uses as
+    // few temporaries as possible. All start with x.
+    let x2 = (2 + (maxc >> 1)) as u8;
+    let write = maxc != 0;
+    let mut n = 0;
+    if maxc > 1 {
+        push(ops, &mut n, write, ChainOp::Square(x2, x2, 1));
+        let mut k = 1;
+        while k <= (maxc >> 1) {
+            push(ops, &mut n, write, ChainOp::Mul(1 + k as u8, k as u8, x2));
+            k += 1;
+        }
+    }
+
+    let mut m = 0;
+    let mut first = true;
+    let mut pending = 0;
+    let mut i = bitlen(e);
+    assert!(i > 0, "exponent is zero");
+    while i > 0 {
+        if bit(e, i - 1) == 0 {
+            pending += 1;
+            i -= 1;
+            continue;
+        }
+        let mut j = i.saturating_sub(w);
+        while bit(e, j) == 0 {
+            j += 1;
+        }
+        let mut c = 0;
+        let mut k = i;
+        while k > j {
+            c = (c << 1) | (bit(e, k - 1) as usize);
+            k -= 1;
+        }
+        if c > m {
+            m = c;
+        }
+        let t = 1 + (c >> 1) as u8;
+        if first {
+            if t != 1 {
+                push(ops, &mut n, write, ChainOp::Square(0, t, 0));
+            }
+            first = false;
+        } else {
+            push(
+                ops,
+                &mut n,
+                write,
+                ChainOp::Square(0, 0, (pending + i - j) as u32),
+            );
+            push(ops, &mut n, write, ChainOp::Mul(0, 0, t));
+        }
+        pending = 0;
+        i = j;
+    }
+    if pending > 0 {
+        push(ops, &mut n, write, ChainOp::Square(0, 0, pending as u32));
+    }
+    (n, m)
+}
+
+// Write op at index n of ops (if write is true and it fits), and increment n.
+const fn push(ops: &mut [ChainOp], n: &mut usize, write: bool, op: ChainOp) {
+    if write && *n < ops.len() {
+        ops[*n] = op;
+    }
+    *n += 1;
+}
+
+// Return the window width for e, and the largest window value.
+const fn best_window(e: &[u64]) -> (usize, usize) {
+    let mut best = (0, 0);
+    let mut best_cost = usize::MAX;
+    let mut w = 1;
+    while w < 6 {
+        // Multiplications: the precomputed odd powers, and then one per
+        // window but the first.
+        let (_, maxc) = emit(e, w, 0, &mut []);
+        let nw = count_windows(e, w);
+        let cost = (maxc >> 1) + nw - 1;
+        if cost < best_cost {
+            best = (w, maxc);
+            best_cost = cost;
+        }
+        w += 1;
+    }
+    best
+}
+
+// Number of windows of width w in e.
+const fn count_windows(e: &[u64], w: usize) -> usize {
+    let mut nw = 0;
+    let mut i = bitlen(e);
+    while i > 0 {
+        if bit(e, i - 1) == 0 {
+            i -= 1;
+            continue;
+        }
+        i = i.saturating_sub(w);
+        while bit(e, i) == 0 {
+            i += 1;
+        }
+        nw += 1;
+    }
+    nw
+}
+
+/// Return the number of operations of the chain derived for the non-zero
+/// exponent e (unsigned little-endian convention over 64-bit words).
+pub const fn derived_len(e: &[u64]) -> usize {
+    let (w, maxc) = best_window(e);
+    emit(e, w, maxc, &mut []).0
+}
+
+/// Write the chain derived for the non-zero exponent e (unsigned
+/// little-endian convention over 64-bit words) into ops, which must have
+/// length at least `derived_len(e)`, and return its number of operations.
+pub const fn derive_into(e: &[u64], ops: &mut [ChainOp]) -> usize {
+    let (w, maxc) = best_window(e);
+    let n = emit(e, w, maxc, ops).0;
+    assert!(n <= ops.len(), "output buffer is too short");
+    n
+}
+
+/// Return the chain derived for the non-zero exponent e (unsigned
+/// little-endian convention over 64-bit words), with `L = derived_len(e)`.
+pub const fn derive<const L: usize>(e: &[u64]) -> [ChainOp; L] {
+    let mut ops = [ChainOp::Square(0, 0, 0); L];
+    let n = derive_into(e, &mut ops);
+    assert!(n == L, "wrong chain length");
+    ops
+}
+
+/// Return the number of multiplications and squarings of a chain.
+pub const fn chain_cost(ops: &[ChainOp]) -> usize {
+    let mut n = 0;
+    let mut i = 0;
+    while i < ops.len() {
+        n += match ops[i] {
+            ChainOp::Mul(..) => 1,
+            ChainOp::Square(_, _, k) => k as usize,
+        };
+        i += 1;
+    }
+    n
+}
+
+/// Return the number of multiplications and squarings of
+/// `Fp::set_pow_pubexp()` for the exponent e: 14 for its 4-bit window, then
+/// four squarings per 4-bit chunk after the top non-zero one, and one
+/// multiplication per non-zero chunk.
+pub const fn pow_pubexp_cost(e: &[u64]) -> usize {
+    let mut n = 14;
+    let mut started = false;
+    let mut i = 16 * e.len();
+    while i > 0 {
+        i -= 1;
+        let c = (e[i >> 4] >> ((i & 15) << 2)) & 0x0F;
+        if started {
+            n += 4 + (c != 0) as usize;
+        } else {
+            started = c != 0;
+        }
+    }
+    n
+}
+
+/// Return `Some(ops)` if the chain ops needs fewer multiplications and
+/// squarings than `Fp::set_pow_pubexp()` for the exponent e, or `None` if the
+/// latter should be used instead.
+pub const fn select(ops: &'static [ChainOp], e: &[u64]) -> Option<&'static [ChainOp]> {
+    if chain_cost(ops) < pow_pubexp_cost(e) {
+        Some(ops)
+    } else {
+        None
+    }
+}
+
+/// Return the exponent computed by a chain, over `N` words, or `None` if it
+/// does not fit, or if the chain uses temporaries out of range.
+pub const fn chain_exponent<const N: usize>(ops: &[ChainOp]) -> Option<[u64; N]> {
+    let mut one = [0u64; N];
+    one[0] = 1;
+    let mut t = [one; CHAIN_TEMPS];
+    let mut i = 0;
+    while i < ops.len() {
+        match ops[i] {
+            ChainOp::Mul(d, a, b) => {
+                let (d, a, b) = (d as usize, a as usize, b as usize);
+                if d >= CHAIN_TEMPS || a >= CHAIN_TEMPS || b >= CHAIN_TEMPS {
+                    return None;
+                }
+                let mut r = [0u64; N];
+                let mut c = false;
+                let mut j = 0;
+                while j < N {
+                    let (x, c1) = t[a][j].overflowing_add(t[b][j]);
+                    let (x, c2) = x.overflowing_add(c as u64);
+                    r[j] = x;
+                    c = c1 | c2;
+                    j += 1;
+                }
+                if c {
+                    return None;
+                }
+                t[d] = r;
+            }
+            ChainOp::Square(d, a, n) => {
+                let (d, a, n) = (d as usize, a as usize, n as usize);
+                if d >= CHAIN_TEMPS || a >= CHAIN_TEMPS {
+                    return None;
+                }
+                let r = t[a];
+                if n > 0 && bitlen(&r) + n > 64 * N {
+                    return None;
+                }
+                let mut s = [0u64; N];
+                let (q, b) = (n >> 6, n & 63);
+                let mut j = N;
+                while j > q {
+                    j -= 1;
+                    s[j] = r[j - q] << b;
+                    if b > 0 && j > q {
+                        s[j] |= r[j - q - 1] >> (64 - b);
+                    }
+                }
+                t[d] = s;
+            }
+        }
+        i += 1;
+    }
+    Some(t[0])
+}
+
+/// Return the number of temporaries used by a chain, i.e. one more than the
+/// largest index it reads or writes (at least one, for the result).
+pub const fn temps_used(ops: &[ChainOp]) -> usize {
+    let mut m = 0;
+    let mut i = 0;
+    while i < ops.len() {
+        let (d, a, b) = match ops[i] {
+            ChainOp::Mul(d, a, b) => (d, a, b),
+            ChainOp::Square(d, a, _) => (d, a, a),
+        };
+        let k = if d > a { d } else { a };
+        let k = if k > b { k } else { b };
+        if k as usize > m {
+            m = k as usize;
+        }
+        i += 1;
+    }
+    m + 1
+}
+
+impl<P: FieldParams<N>, const N: usize> Fp<P, N> {
+    /// Raise this value to the exponent computed by the addition chain
+    /// `ops` (see `ChainOp`). The chain is public. Temporaries out of the
+    /// `[0, CHAIN_TEMPS)` range make this function panic.
+    pub fn set_pow_chain(&mut self, ops: &[ChainOp]) {
+        // Only set up as many temporaries as the chain uses, rounded up to
+        // a power of two.
+        match temps_used(ops) {
+            0..=4 => self.set_pow_chain_temps::<4>(ops),
+            5..=8 => self.set_pow_chain_temps::<8>(ops),
+            9..=16 => self.set_pow_chain_temps::<16>(ops),
+            _ => self.set_pow_chain_temps::<CHAIN_TEMPS>(ops),
+        }
+    }
+
+    // set_pow_chain() with T temporaries, all starting with this value.
+    fn set_pow_chain_temps<const T: usize>(&mut self, ops: &[ChainOp]) {
+        let mut t = [*self; T];
+        for op in ops {
+            match *op {
+                ChainOp::Mul(d, a, b) => {
+                    let (d, a, b) = (d as usize, a as usize, b as usize);
+                    let y = t[b];
+                    if d != a {
+                        t[d] = t[a];
+                    }
+                    t[d].set_mul(&y);
+                }
+                ChainOp::Square(d, a, n) => {
+                    let (d, a) = (d as usize, a as usize);
+                    if d != a {
+                        t[d] = t[a];
+                    }
+                    t[d].set_n_square(n);
+                }
+            }
+        }
+        *self = t[0];
+    }
+
+    /// Return this value raised to the exponent computed by the addition
+    /// chain `ops` (see `set_pow_chain()`).
+    pub fn pow_chain(self, ops: &[ChainOp]) -> Self {
+        let mut r = self;
+        r.set_pow_chain(ops);
+        r
+    }
+
+    /// Check that the addition chains of the parameters, if any, compute
+    /// the exponents of the modulus (panics otherwise). `define_fp_core`
+    /// calls this at compile time.
+    pub const fn check_chains() {
+        let Some(c) = P::CHAINS else {
+            return;
+        };
+        let m = &P::MODULUS;
+        assert!(
+            Self::chain_matches(c.sqrt, &sqrt_exponent(m)),
+            "addition chain sqrt does not match the modulus"
+        );
+        assert!(
+            Self::chain_matches(c.fourth_root, &fourth_root_exponent(m)),
+            "addition chain fourth_root does not match the modulus"
+        );
+        assert!(
+            Self::chain_matches(c.progenitor, &progenitor_exponent(m)),
+            "addition chain progenitor does not match the modulus"
+        );
+        assert!(
+            Self::chain_matches(c.legendre, &legendre_exponent(m)),
+            "addition chain legendre does not match the modulus"
+        );
+        assert!(
+            Self::chain_matches(c.inverse, &inverse_exponent(m)),
+            "addition chain inverse does not match the modulus"
+        );
+    }
+
+    const fn chain_matches(ops: &[ChainOp], e: &[u64; N]) -> bool {
+        match chain_exponent::<N>(ops) {
+            Some(x) => {
+                let mut i = 0;
+                while i < N {
+                    if x[i] != e[i] {
+                        return false;
+                    }
+                    i += 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Generate the macro invocations for a given prime.
+//!
+//! The prime may be given in decimal, in hexadecimal (with a `0x` prefix) or
+//! as an expression using `+`, `-`, `*`, `^` (or `**`) and parentheses, for
+//! example `65*2^376-1`. The prime is checked to be a prime p = 3 mod 4 larger
+//! than 2^64, and the tool prints ready-to-paste `define_fp_core!` and
+//! `define_fp2_from_modulus!` invocations, as well as a test module.
+//!
+//! Numbers are read with `utils64::parse_words`, and the checks and constants
+//! come from the library itself (`fp_dyn::FpCtx` and `fp::Precomputed`), so
+//! that they match what the macros compute at compile time.
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run --features gen --bin fp2-gen -- [--name NAME] [--backend w32|w64] [--precomputed] PRIME
+//! ```
+
+use fp2::fp::Precomputed;
+use fp2::fp_dyn::{Fp2Dyn, FpCtx};
+use fp2::utils64::{self, MODULUS_STR_MAX_WORDS};
+use num_bigint::{BigInt, BigUint};
+use std::fmt::Write as _;
+use std::process::exit;
+
+const USAGE: &str = "usage: fp2-gen [--name NAME] [--backend w32|w64] [--precomputed] PRIME";
+
+// Small recursive descent parser for integer expressions:
+//   expr   = term (('+' | '-') term)*
+//   term   = factor ('*' factor)*
+//   factor = ('-' factor) | atom (('^' | '**') factor)?
+//   atom   = number | '(' expr ')'
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, tok: &str) -> bool {
+        if self.s[self.pos..].starts_with(tok) {
+            self.pos += tok.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<BigInt, String> {
+        let mut x = self.term()?;
+        loop {
+            if self.eat("+") {
+                x += self.term()?;
+            } else if self.eat("-") {
+                x -= self.term()?;
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<BigInt, String> {
+        let mut x = self.factor()?;
+        // '*' but not '**'
+        while self.peek() == Some(b'*') && !self.s[self.pos..].starts_with("**") {
+            self.pos += 1;
+            x *= self.factor()?;
+        }
+        Ok(x)
+    }
+
+    fn factor(&mut self) -> Result<BigInt, String> {
+        if self.eat("-") {
+            return Ok(-self.factor()?);
+        }
+        let x = self.atom()?;
+        if self.eat("^") || self.eat("**") {
+            let e = self.factor()?;
+            let e = u32::try_from(e).map_err(|_| "invalid exponent".to_string())?;
+            return Ok(x.pow(e));
+        }
+        Ok(x)
+    }
+
+    fn atom(&mut self) -> Result<BigInt, String> {
+        if self.eat("(") {
+            let x = self.expr()?;
+            if !self.eat(")") {
+                return Err(format!("expected ')' at position {}", self.pos));
+            }
+            return Ok(x);
+        }
+        let start = self.pos;
+        let hex = self.eat("0x") || self.eat("0X");
+        let digits = self.pos;
+        while let Some(c) = self.peek() {
+            if !(c == b'_' || (hex && c.is_ascii_hexdigit()) || c.is_ascii_digit()) {
+                break;
+            }
+            self.pos += 1;
+        }
+        // parse_words() panics on malformed or too large numbers.
+        let len = self.s[digits..self.pos]
+            .bytes()
+            .filter(|&c| c != b'_')
+            .count();
+        if len == 0 {
+            return Err(format!("expected a number at position {}", start));
+        }
+        let max_len = if hex { 16 } else { 19 } * MODULUS_STR_MAX_WORDS;
+        if len > max_len {
+            return Err(format!("number too large at position {}", start));
+        }
+        let words = utils64::parse_words::<MODULUS_STR_MAX_WORDS>(&self.s[start..self.pos]);
+        Ok(BigUint::from_slice(&to_u32_digits(&words)).into())
+    }
+}
+
+// Split little-endian 64-bit words into 32-bit digits, for num-bigint.
+fn to_u32_digits(words: &[u64]) -> Vec<u32> {
+    words
+        .iter()
+        .flat_map(|&w| [w as u32, (w >> 32) as u32])
+        .collect()
+}
+
+fn parse_prime(s: &str) -> Result<BigUint, String> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parser = Parser { s: &s, pos: 0 };
+    let x = parser.expr()?;
+    if parser.pos != s.len() {
+        return Err(format!("unexpected character at position {}", parser.pos));
+    }
+    x.to_biguint()
+        .ok_or_else(|| "the modulus must be positive".to_string())
+}
+
+// The constants printed for a modulus: the smallest nqr such that nqr + i is
+// not a square in GF(p^2), and the precomputed constants r, r2, tdec,
+// tfixdiv and sqrt_exp of define_fp_core, in this order.
+struct Constants {
+    nqr: u64,
+    precomputed: [Vec<u64>; 5],
+}
+
+// Check the modulus and compute its constants with the library functions,
+// for a modulus of exactly N words. Returns None if p is not prime.
+fn field_constants<const N: usize>(p: &[u64]) -> Option<Constants> {
+    let modulus: [u64; N] = p.try_into().unwrap();
+    let ctx = FpCtx::new(&modulus)?;
+    let nqr = (1u64..)
+        .find(|&k| Fp2Dyn::from_u64_pair(&ctx, k, 1).is_square() == 0)
+        .unwrap();
+    let pc = Precomputed::new(&modulus);
+    Some(Constants {
+        nqr,
+        precomputed: [pc.r, pc.r2, pc.tdec, pc.tfixdiv, pc.sqrt_exp].map(|x| x.to_vec()),
+    })
+}
+
+// Call field_constants() with N set to the number of words of the modulus,
+// which is at least 2.
+macro_rules! field_constants_dispatch {
+    ($p:expr, $($n:literal)*) => {
+        match $p.len() {
+            $($n => field_constants::<$n>($p),)*
+            _ => unreachable!(),
+        }
+    };
+}
+
+// Format words as a comma separated list of hexadecimal constants.
+fn fmt_words(words: &[u64]) -> String {
+    words
+        .iter()
+        .map(|w| format!("0x{:016X}", w))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Return the macro invocations and test module for the given prime, or an
+// error message.
+fn generate(
+    prime: &str,
+    name: Option<String>,
+    backend: Option<String>,
+    precomputed: bool,
+) -> Result<String, String> {
+    let p = parse_prime(prime).map_err(|e| format!("could not parse the modulus: {}", e))?;
+    let bitlen = p.bits() as usize;
+    if bitlen <= 64 {
+        return Err("the modulus must be larger than 2^64".to_string());
+    }
+    if bitlen > 64 * MODULUS_STR_MAX_WORDS {
+        return Err(format!(
+            "the modulus must be at most {} bits",
+            64 * MODULUS_STR_MAX_WORDS
+        ));
+    }
+    if !p.bit(0) || !p.bit(1) {
+        return Err("the modulus must be equal to 3 mod 4".to_string());
+    }
+    let words = p.to_u64_digits();
+    let c = field_constants_dispatch!(
+        &words[..],
+        2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+        33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61
+        62 63 64 65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86 87 88 89 90
+        91 92 93 94 95 96 97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114
+        115 116 117 118 119 120 121 122 123 124 125 126 127 128
+    )
+    .ok_or_else(|| "the modulus is not prime".to_string())?;
+
+    let nwords = words.len();
+    let name = name.unwrap_or_else(|| format!("Fp{}", bitlen));
+    let module = format!("{}_tests", name.to_lowercase());
+    let nqr = c.nqr;
+
+    let mut params = String::new();
+    if let Some(backend) = &backend {
+        writeln!(params, "    backend = {},", backend).unwrap();
+    }
+    if precomputed {
+        // Precomputed constants, optional parameters of define_fp_core which
+        // otherwise are slow to compute at compile time for very large moduli
+        for (k, v) in ["r", "r2", "tdec", "tfixdiv", "sqrt_exp"]
+            .iter()
+            .zip(&c.precomputed)
+        {
+            writeln!(params, "    {} = [{}],", k, fmt_words(v)).unwrap();
+        }
+    }
+    let params_indented: String = params.lines().map(|l| format!("    {}\n", l)).collect();
+
+    let mut from_modulus_params = String::new();
+    if let Some(backend) = &backend {
+        writeln!(from_modulus_params, "    backend = {},", backend).unwrap();
+    }
+
+    Ok(format!(
+        "\
+// p = {prime} ({bitlen} bits)
+const MODULUS: [u64; {nwords}] = [{modulus}];
+
+// {name}: a finite field element GF(p) with p = 3 mod 4.
+// Contents are opaque, all functions are constant-time.
+fp2::define_fp_core!(
+    typename = {name},
+    modulus = MODULUS,
+{params});
+
+// Alternatively, {name}Ext: a finite field element GF(p^2) with modulus x^2 + 1,
+// together with its base field {name}.
+// Contents are opaque, all functions are constant-time.
+fp2::define_fp2_from_modulus!(
+    typename = {name}Ext,
+    base_typename = {name},
+    modulus = MODULUS,
+{from_modulus_params});
+
+mod {module} {{
+    // Field modulus
+    const MODULUS: [u64; {nwords}] = [{modulus}];
+
+    fp2::define_fp_core!(
+        typename = {name},
+        modulus = MODULUS,
+{params_indented}    );
+    fp2::define_fp2_from_type!(typename = {name}Ext, base_field = {name},);
+
+    fp2::define_fp_tests!({name});
+    fp2::define_fp2_tests!({name}Ext, MODULUS, {nqr});
+}}",
+        modulus = fmt_words(&words),
+    ))
+}
+
+fn main() {
+    let mut name = None;
+    let mut backend = None;
+    let mut precomputed = false;
+    let mut prime = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => name = args.next(),
+            "--backend" => match args.next().as_deref() {
+                Some(b @ ("w32" | "w64")) => backend = Some(b.to_string()),
+                _ => {
+                    eprintln!("{}", USAGE);
+                    exit(1);
+                }
+            },
+            "--precomputed" => precomputed = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if prime.is_none() => prime = Some(arg),
+            _ => {
+                eprintln!("{}", USAGE);
+                exit(1);
+            }
+        }
+    }
+    let Some(prime) = prime else {
+        eprintln!("{}", USAGE);
+        exit(1);
+    };
+
+    match generate(&prime, name, backend, precomputed) {
+        Ok(s) => println!("{}", s),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // p = 65*2^376 - 1
+    const MODULUS: [u64; 6] = [
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x40FFFFFFFFFFFFFF,
+    ];
+
+    fp2::define_fp2_from_modulus!(
+        typename = Fp383Ext,
+        base_typename = Fp383,
+        modulus = MODULUS,
+    );
+
+    #[test]
+    fn check_against_macro_constants() {
+        let out = generate("65*2^376-1", None, None, true).unwrap();
+        let modulus = format!(
+            "const MODULUS: [u64; 6] = [{}];",
+            fmt_words(&Fp383::MODULUS)
+        );
+        assert!(out.contains(&modulus), "{out}");
+        assert!(out.contains("typename = Fp383,"));
+
+        let pc = Fp383::precompute();
+        for (k, v) in [
+            ("r", pc.r),
+            ("r2", pc.r2),
+            ("tdec", pc.tdec),
+            ("tfixdiv", pc.tfixdiv),
+            ("sqrt_exp", pc.sqrt_exp),
+        ] {
+            let line = format!("\n    {} = [{}],\n", k, fmt_words(&v));
+            assert!(out.contains(&line), "constant {k}");
+        }
+
+        let nqr = Fp383Ext::NON_SQUARE.x0().encode();
+        let nqr = u64::from_le_bytes(nqr[..8].try_into().unwrap());
+        let tests = format!("fp2::define_fp2_tests!(Fp383Ext, MODULUS, {});", nqr);
+        assert!(out.contains(&tests));
+
+        // The same prime in hexadecimal, without precomputed constants.
+        let hex = format!("0x40{}", "F".repeat(94));
+        let out2 = generate(&hex, Some("Fp383".to_string()), None, false).unwrap();
+        assert!(out2.contains(&modulus));
+        assert!(!out2.contains("tfixdiv"));
+    }
+
+    #[test]
+    fn check_rejected_moduli() {
+        for (p, e) in [
+            ("2^61-1", "larger than 2^64"),
+            ("2^127+1", "3 mod 4"),
+            ("2^128-1", "not prime"),
+            ("2^127-", "could not parse"),
+            ("12a", "could not parse"),
+        ] {
+            let r = generate(p, None, None, false).unwrap_err();
+            assert!(r.contains(e), "{p}: {r}");
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! A macro to export the arithmetic of a finite field GF(p^2) through a C ABI,
+//! e.g. to cross-check it against C implementations.
+//!
+//! The exported functions operate on byte buffers holding the canonical
+//! encodings of the elements (see `Fq::encode`), and a C header declaring them
+//! is generated as a string constant. Each function returns `0xFFFFFFFF` on
+//! success, or `0x00000000` on failure (e.g. when an input is not a canonical
+//! encoding, or when a square root does not exist), in which case its output is
+//! set to zero.
+
+use crate::traits::Fq;
+
+/// A macro to export the arithmetic of a GF(p^2) type, such as one defined
+/// with `define_fp2_from_modulus`, as `extern "C"` functions named
+/// `<prefix>_<operation>`. All functions are constant-time except for
+/// `<prefix>_pow` and `<prefix>_solve_dlp_2e`, whose exponent lengths are
+/// considered public.
+///
+/// This also defines the string constant `<PREFIX>_H`, the C header declaring
+/// the exported functions, which should be written to `<prefix>.h`:
+///
+/// - `size_t <prefix>_encoded_length(void)`: the length of encoded elements.
+/// - `size_t <prefix>_limbs(void)`: the number `N` of 64-bit words of p.
+/// - `<prefix>_add`, `<prefix>_sub`, `<prefix>_mul`: `out = a op b`.
+/// - `<prefix>_sqr`, `<prefix>_inv`, `<prefix>_sqrt`: `out = f(a)`, where the
+///   inverse of zero is zero and square roots fail on non-squares.
+/// - `<prefix>_pow(out, a, e, elen)`: `out = a^e`, with `e` encoded over
+///   `elen` bytes in unsigned little-endian convention.
+/// - `<prefix>_encode(out, limbs)`: encode the element whose components x0
+///   and x1 are given over `2*N` words, as `x0*R mod p` followed by
+///   `x1*R mod p` (see `Fp2::to_montgomery_limbs()`).
+/// - `<prefix>_decode(limbs, a)`: the converse of `<prefix>_encode`.
+/// - `<prefix>_solve_dlp_2e(v, g, x, e)`: find `v` such that `x = g^v`, with
+///   `g` of order 2^e exactly, written over `(e + 7) / 8` bytes.
+///
+/// Macro expectations:
+/// - A GF(p^2) type over a base field of this crate.
+/// - An identifier used as prefix for the exported symbols.
+#[macro_export]
+macro_rules! define_fp2_c_api {
+    (
+        typename = $Fp2:ty,
+        prefix = $prefix:ident,
+    ) => {
+        $crate::paste::paste! {
+            #[doc = concat!("C header for the functions exported with the prefix `",
+                stringify!($prefix), "`.")]
+            pub const [<$prefix:upper _H>]: &str = concat!(
+                "/* C API of a finite field GF(p^2), generated by fp2::define_fp2_c_api.\n",
+                " * Elements are passed as their canonical encodings over\n",
+                " * ", stringify!($prefix), "_encoded_length() bytes. Functions returning\n",
+                " * uint32_t return 0xFFFFFFFF on success, or 0 on failure, in which\n",
+                " * case the output is set to zero. */\n",
+                "#ifndef ", stringify!([<$prefix:upper _H>]), "\n",
+                "#define ", stringify!([<$prefix:upper _H>]), "\n",
+                "\n",
+                "#include <stddef.h>\n",
+                "#include <stdint.h>\n",
+                "\n",
+                "#ifdef __cplusplus\n",
+                "extern \"C\" {\n",
+                "#endif\n",
+                "\n",
+                "size_t ", stringify!($prefix), "_encoded_length(void);\n",
+                "size_t ", stringify!($prefix), "_limbs(void);\n",
+                "uint32_t ", stringify!($prefix),
+                "_add(uint8_t *out, const uint8_t *a, const uint8_t *b);\n",
+                "uint32_t ", stringify!($prefix),
+                "_sub(uint8_t *out, const uint8_t *a, const uint8_t *b);\n",
+                "uint32_t ", stringify!($prefix),
+                "_mul(uint8_t *out, const uint8_t *a, const uint8_t *b);\n",
+                "uint32_t ", stringify!($prefix), "_sqr(uint8_t *out, const uint8_t *a);\n",
+                "uint32_t ", stringify!($prefix), "_inv(uint8_t *out, const uint8_t *a);\n",
+                "uint32_t ", stringify!($prefix), "_sqrt(uint8_t *out, const uint8_t *a);\n",
+                "uint32_t ", stringify!($prefix),
+                "_pow(uint8_t *out, const uint8_t *a, const uint8_t *e, size_t elen);\n",
+                "uint32_t ", stringify!($prefix),
+                "_encode(uint8_t *out, const uint64_t *limbs);\n",
+                "uint32_t ", stringify!($prefix),
+                "_decode(uint64_t *limbs, const uint8_t *a);\n",
+                "uint32_t ", stringify!($prefix),
+                "_solve_dlp_2e(uint8_t *v, const uint8_t *g, const uint8_t *x, size_t e);\n",
+                "\n",
+                "#ifdef __cplusplus\n",
+                "}\n",
+                "#endif\n",
+                "\n",
+                "#endif\n",
+            );
+
+            /// Return the length of encoded elements.
+            #[unsafe(no_mangle)]
+            pub extern "C" fn [<$prefix _encoded_length>]() -> usize {
+                <$Fp2>::ENCODED_LENGTH
+            }
+
+            /// Return the number of 64-bit words of the characteristic.
+            #[unsafe(no_mangle)]
+            pub extern "C" fn [<$prefix _limbs>]() -> usize {
+                <$Fp2 as $crate::traits::Fq>::N
+            }
+
+            /// Set `out` to `a + b`.
+            ///
+            /// # Safety
+            ///
+            /// All pointers MUST be valid for encoded elements.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _add>](
+                out: *mut u8,
+                a: *const u8,
+                b: *const u8,
+            ) -> u32 {
+                unsafe { $crate::c_api::binary::<$Fp2>(out, a, b, |x, y| x + y) }
+            }
+
+            /// Set `out` to `a - b`.
+            ///
+            /// # Safety
+            ///
+            /// All pointers MUST be valid for encoded elements.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _sub>](
+                out: *mut u8,
+                a: *const u8,
+                b: *const u8,
+            ) -> u32 {
+                unsafe { $crate::c_api::binary::<$Fp2>(out, a, b, |x, y| x - y) }
+            }
+
+            /// Set `out` to `a * b`.
+            ///
+            /// # Safety
+            ///
+            /// All pointers MUST be valid for encoded elements.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _mul>](
+                out: *mut u8,
+                a: *const u8,
+                b: *const u8,
+            ) -> u32 {
+                unsafe { $crate::c_api::binary::<$Fp2>(out, a, b, |x, y| x * y) }
+            }
+
+            /// Set `out` to `a^2`.
+            ///
+            /// # Safety
+            ///
+            /// All pointers MUST be valid for encoded elements.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _sqr>](out: *mut u8, a: *const u8) -> u32 {
+                unsafe { $crate::c_api::unary::<$Fp2>(out, a, |x| (x.square(), u32::MAX)) }
+            }
+
+            /// Set `out` to `1/a` (zero if `a` is zero).
+            ///
+            /// # Safety
+            ///
+            /// All pointers MUST be valid for encoded elements.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _inv>](out: *mut u8, a: *const u8) -> u32 {
+                unsafe { $crate::c_api::unary::<$Fp2>(out, a, |x| (x.invert(), u32::MAX)) }
+            }
+
+            /// Set `out` to a square root of `a`, failing if there is none.
+            ///
+            /// # Safety
+            ///
+            /// All pointers MUST be valid for encoded elements.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _sqrt>](out: *mut u8, a: *const u8) -> u32 {
+                unsafe { $crate::c_api::unary::<$Fp2>(out, a, |x| x.sqrt()) }
+            }
+
+            /// Set `out` to `a^e`, with `e` over `elen` bytes.
+            ///
+            /// # Safety
+            ///
+            /// `out` and `a` MUST be valid for encoded elements, and `e` for
+            /// `elen` bytes.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _pow>](
+                out: *mut u8,
+                a: *const u8,
+                e: *const u8,
+                elen: usize,
+            ) -> u32 {
+                let e = unsafe { ::core::slice::from_raw_parts(e, elen) };
+                unsafe {
+                    $crate::c_api::unary::<$Fp2>(out, a, |x| (x.pow(e, 8 * elen), u32::MAX))
+                }
+            }
+
+            /// Encode the element given by the Montgomery representations of
+            /// its components.
+            ///
+            /// # Safety
+            ///
+            /// `out` MUST be valid for an encoded element, and `limbs` for
+            /// `2*N` words.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _encode>](out: *mut u8, limbs: *const u64) -> u32 {
+                const N: usize = <$Fp2 as $crate::traits::Fq>::N;
+                let w = unsafe { ::core::slice::from_raw_parts(limbs, 2 * N) };
+                let mut x0 = [0u64; N];
+                let mut x1 = [0u64; N];
+                x0.copy_from_slice(&w[..N]);
+                x1.copy_from_slice(&w[N..]);
+                let (x, cc) = <$Fp2>::from_montgomery_limbs(&x0, &x1);
+                unsafe { $crate::c_api::write(out, x) };
+                cc
+            }
+
+            /// Decode an element into the Montgomery representations of its
+            /// components.
+            ///
+            /// # Safety
+            ///
+            /// `a` MUST be valid for an encoded element, and `limbs` for
+            /// `2*N` words.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _decode>](limbs: *mut u64, a: *const u8) -> u32 {
+                const N: usize = <$Fp2 as $crate::traits::Fq>::N;
+                let (x, cc) = unsafe { $crate::c_api::read::<$Fp2>(a) };
+                let (x0, x1) = x.to_montgomery_limbs();
+                let w = unsafe { ::core::slice::from_raw_parts_mut(limbs, 2 * N) };
+                w[..N].copy_from_slice(&x0);
+                w[N..].copy_from_slice(&x1);
+                cc
+            }
+
+            /// Find `v` such that `x = g^v`, with `g` of order 2^e exactly.
+            ///
+            /// # Safety
+            ///
+            /// `g` and `x` MUST be valid for encoded elements, and `v` for
+            /// `(e + 7) / 8` bytes.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn [<$prefix _solve_dlp_2e>](
+                v: *mut u8,
+                g: *const u8,
+                x: *const u8,
+                e: usize,
+            ) -> u32 {
+                const T: usize = $crate::fp2_gen::dlp_table_capacity(<$Fp2>::CHAR_BIT_LENGTH);
+                const L: usize = <$Fp2>::ENCODED_LENGTH;
+                let out = unsafe { ::core::slice::from_raw_parts_mut(v, (e + 7) >> 3) };
+                out.fill(0);
+                // The order of g divides p + 1 and p - 1.
+                if e == 0 || e > <$Fp2>::CHAR_BIT_LENGTH {
+                    return 0;
+                }
+                let (g, c0) = unsafe { $crate::c_api::read::<$Fp2>(g) };
+                let (x, c1) = unsafe { $crate::c_api::read::<$Fp2>(x) };
+                let (r, c2) = g.solve_dlp_2e_fixed::<T, L>(&x, e, None);
+                let cc = c0 & c1 & c2;
+                for (d, s) in out.iter_mut().zip(r.iter()) {
+                    *d = s & (cc as u8);
+                }
+                cc
+            }
+        }
+    };
+} // End of macro: define_fp2_c_api
+
+/// Read an element from its encoding at `a`.
+///
+/// # Safety
+///
+/// `a` MUST be valid for `F::ENCODED_LENGTH` bytes.
+#[doc(hidden)]
+pub unsafe fn read<F: Fq>(a: *const u8) -> (F, u32) {
+    F::decode(unsafe { core::slice::from_raw_parts(a, F::ENCODED_LENGTH) })
+}
+
+/// Write the encoding of `x` at `out`.
+///
+/// # Safety
+///
+/// `out` MUST be valid for `F::ENCODED_LENGTH` bytes.
+#[doc(hidden)]
+pub unsafe fn write<F: Fq>(out: *mut u8, x: F) {
+    let d = unsafe { core::slice::from_raw_parts_mut(out, F::ENCODED_LENGTH) };
+    d.copy_from_slice(x.encode().as_ref());
+}
+
+/// Set `out` to `f(a)`, or to zero if either `a` is not a canonical
+/// encoding or `f` fails.
+///
+/// # Safety
+///
+/// `out` and `a` MUST be valid for `F::ENCODED_LENGTH` bytes.
+#[doc(hidden)]
+pub unsafe fn unary<F: Fq>(out: *mut u8, a: *const u8, f: impl FnOnce(F) -> (F, u32)) -> u32 {
+    let (x, c0) = unsafe { read::<F>(a) };
+    let (r, c1) = f(x);
+    let cc = c0 & c1;
+    unsafe { write(out, F::select(&F::ZERO, &r, cc)) };
+    cc
+}
+
+/// Set `out` to `f(a, b)`, or to zero if either input is not a canonical
+/// encoding.
+///
+/// # Safety
+///
+/// `out`, `a` and `b` MUST be valid for `F::ENCODED_LENGTH` bytes.
+#[doc(hidden)]
+pub unsafe fn binary<F: Fq>(
+    out: *mut u8,
+    a: *const u8,
+    b: *const u8,
+    f: impl FnOnce(F, F) -> F,
+) -> u32 {
+    let (x, c0) = unsafe { read::<F>(a) };
+    let (y, c1) = unsafe { read::<F>(b) };
+    let cc = c0 & c1;
+    unsafe { write(out, F::select(&F::ZERO, &f(x, y), cc)) };
+    cc
+}
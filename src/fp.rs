@@ -0,0 +1,3364 @@
+//! A generic implementation of efficient and constant-time arithmetic for
+//! finite fields Fp with p = 3 mod 4 using Montgomery multiplication.
+//!
+//! # Parameters
+//!
+//! The field is described by a type implementing `FieldParams`, which gives
+//! the modulus over `N` words in base 2^64 and, optionally, the limb backend
+//! and precomputed constants. All other constants are derived from the
+//! modulus at compile time. The type `Fp<P, N>` is then a field element, and
+//! implements the traits `Fq`, `FqExp`, `FqRoots` and `FqRnd`:
+//!
+//! ```
+//! use fp2::fp::{FieldParams, Fp};
+//!
+//! pub struct Fp127Params;
+//!
+//! impl FieldParams<2> for Fp127Params {
+//!     const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+//!     type Encoding = [u8; 16];
+//!     type Fp2Encoding = [u8; 32];
+//! }
+//!
+//! pub type Fp127 = Fp<Fp127Params, 2>;
+//!
+//! let x = Fp127::from(3u64).invert();
+//! assert!((x * Fp127::THREE).equals(&Fp127::ONE) == u32::MAX);
+//! ```
+//!
+//! The macro `define_fp_core` is a thin wrapper which defines the parameters
+//! type and the alias as above.
+//!
+//! # Authorship and History
+//!
+//! The majority of this code has been adapted from code written by Thomas Pornin
+//! from collaboration in previous projects and several methods which appear in other
+//! macros in the cryptographic research library crrl <https://github.com/pornin/crrl>
+//!
+//! This code has also been used in a handful of isogeny-based cryptography research
+//! projects before being rewritten for this crate, including:
+//! - <https://github.com/ThetaIsogenies/two-isogenies>
+//! - <https://github.com/GiacomoPope/cubical-pairings>
+//! - <https://github.com/GiacomoPope/ThetaCGL>
+
+use core::cmp::Ordering;
+use core::fmt::{self, Write as _};
+use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::addchain::{ChainOp, Chains};
+use crate::traits::SignConvention;
+use crate::utils64;
+
+/// Limb arithmetic used for the field elements. Both backends use the same
+/// Montgomery representation, and thus give identical encodings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// 64-bit limbs with 128-bit products.
+    W64,
+    /// 32-bit limbs with 64-bit products, for targets without a native
+    /// 64x64-bit multiplication. Inversions and Legendre symbols are computed
+    /// by exponentiation.
+    W32,
+}
+
+impl Backend {
+    /// `W32` on targets with 32-bit pointers, and `W64` otherwise.
+    pub const DEFAULT: Backend = if cfg!(target_pointer_width = "32") {
+        Backend::W32
+    } else {
+        Backend::W64
+    };
+}
+
+/// Constants derived from the modulus which are slow to compute at compile
+/// time for very large moduli, all given over `N` words. `r`, `r2` and `tdec`
+/// are 2^(64*N), 2^(128*N) and 2^(64*(2*N-1)) modulo p (as plain integers),
+/// `tfixdiv` is the constant used at the end of divisions and `sqrt_exp` is
+/// (p+1)/4. The `fp2-gen` tool outputs these constants.
+#[derive(Clone, Copy, Debug)]
+pub struct Precomputed<const N: usize> {
+    pub r: [u64; N],
+    pub r2: [u64; N],
+    pub tdec: [u64; N],
+    pub tfixdiv: [u64; N],
+    pub sqrt_exp: [u64; N],
+}
+
+impl<const N: usize> Precomputed<N> {
+    /// Compute the constants for the modulus p, given over `N` words
+    /// (little-endian order) with a non-zero top word. This is the value of
+    /// `Fp::precompute()` for the fields over p; it also works at runtime,
+    /// e.g. in the `fp2-gen` tool.
+    pub const fn new(modulus: &[u64; N]) -> Self {
+        let bl = 64 * N - (modulus[N - 1].leading_zeros() as usize);
+        let p0i = utils64::ninv64(modulus[0]);
+        let r2 = utils64::const_pow2_mod(modulus, 128 * N);
+
+        // 2^(33*NUM1 + 64 - NUM2) * R^2 mod p, in Montgomery representation
+        // (see Fp::set_div()).
+        let num1 = (2 * bl - 34) / 31;
+        let num2 = 2 * bl - 31 * num1 - 2;
+        let t = utils64::const_pow2_mod(modulus, num1 * 33 + 64 - num2);
+        let t = utils64::const_mmul(&t, &r2, modulus, p0i);
+
+        Precomputed {
+            r: utils64::const_pow2_mod(modulus, 64 * N),
+            r2,
+            tdec: utils64::const_pow2_mod(modulus, (2 * N - 1) * 64),
+            tfixdiv: utils64::const_mmul(&t, &r2, modulus, p0i),
+            sqrt_exp: crate::addchain::sqrt_exponent(modulus),
+        }
+    }
+}
+
+/// Montgomery arithmetic over `N` 64-bit words supplied by the parameters,
+/// such as the functions generated by fiat-crypto (see `define_fp_from_fiat`).
+/// All values are fully reduced and in Montgomery representation with
+/// R = 2^(64*N), as for the other backends, except for the output of
+/// `from_montgomery`, which is the plain integer x for the value x*R mod p.
+#[derive(Clone, Copy, Debug)]
+pub struct FiatFunctions<const N: usize> {
+    pub mul: fn(&mut [u64; N], &[u64; N], &[u64; N]),
+    pub square: fn(&mut [u64; N], &[u64; N]),
+    pub add: fn(&mut [u64; N], &[u64; N], &[u64; N]),
+    pub sub: fn(&mut [u64; N], &[u64; N], &[u64; N]),
+    pub from_montgomery: fn(&mut [u64; N], &[u64; N]),
+}
+
+/// Byte arrays, used for the encodings of field elements.
+pub trait ByteArray: Copy + AsRef<[u8]> + AsMut<[u8]> + fmt::Debug {
+    /// Length of the array.
+    const LEN: usize;
+
+    /// Array with all bytes set to zero.
+    const ZERO: Self;
+}
+
+impl<const L: usize> ByteArray for [u8; L] {
+    const LEN: usize = L;
+    const ZERO: Self = [0u8; L];
+}
+
+/// Parameters of the finite field GF(p), with p given over `N` words in base
+/// 2^64.
+///
+/// The modulus MUST be a prime p = 3 mod 4 spanning at least two words, with
+/// a non-zero top word; this is checked at compile time (see `CHECK_MODULUS`).
+/// As array lengths cannot depend on generic constants on stable Rust, the
+/// encoding types are given explicitly: they MUST be byte arrays of length
+/// `(BIT_LENGTH + 7) / 8` and twice that, which is checked at compile time as
+/// well.
+pub trait FieldParams<const N: usize> {
+    /// The field characteristic p, in little-endian order.
+    const MODULUS: [u64; N];
+
+    /// The encoding of elements of GF(p), i.e. `[u8; ENCODED_LENGTH]`.
+    type Encoding: ByteArray;
+
+    /// The encoding of elements of GF(p^2), i.e. `[u8; 2 * ENCODED_LENGTH]`.
+    type Fp2Encoding: ByteArray;
+
+    /// The limb backend, see `Backend::DEFAULT`.
+    const BACKEND: Backend = Backend::DEFAULT;
+
+    /// Number of words from which multiplications use Karatsuba followed by
+    /// a separate Montgomery reduction (`W64` backend only); `usize::MAX`
+    /// disables it.
+    const KARATSUBA_THRESHOLD: usize = utils64::KARATSUBA_THRESHOLD;
+
+    /// Precomputed constants, which are otherwise computed at compile time
+    /// when first used. These are either hard-coded values, e.g. as output
+    /// by the `fp2-gen` tool, or `Some(Fp::<Self, N>::precompute())`; see
+    /// `Fp::precompute()` and `Fp::check_precomputed()`.
+    const PRECOMPUTED: Option<Precomputed<N>> = None;
+
+    /// Externally supplied Montgomery arithmetic, e.g. formally verified.
+    /// When given, it replaces the limb backend for additions, subtractions,
+    /// negations, multiplications and squarings, and for the conversion out
+    /// of the Montgomery representation when encoding. Everything else is
+    /// computed on top of these, except for inversions and Legendre symbols
+    /// which still use `BACKEND`.
+    const FIAT: Option<FiatFunctions<N>> = None;
+
+    /// Addition chains for the fixed exponents of the field, used instead of
+    /// generic exponentiations when given and shorter (see `addchain`).
+    /// `define_fp_core` derives them at compile time.
+    const CHAINS: Option<Chains> = None;
+
+    /// Whether to check at compile time that the modulus is a prime
+    /// p = 3 mod 4. Disabling the check is only meant for deliberate
+    /// experiments.
+    const CHECK_MODULUS: bool = true;
+
+    /// The result of the compile-time primality test of the modulus (see
+    /// `utils64::check_prime()`), when `CHECK_MODULUS` is set; `None` for
+    /// moduli above `utils64::PRIMALITY_CHECK_MAX_BITS` bits, which are not
+    /// tested.
+    /// `define_fp_core` evaluates it in its implementation, over one
+    /// constant per group of bases, so that the `long_running_const_eval`
+    /// lint is silenced there and large moduli do not trigger it.
+    const MODULUS_IS_PRIME: Option<bool> = if Self::CHECK_MODULUS {
+        utils64::check_prime(&Self::MODULUS, utils64::PRIMALITY_BASES.as_flattened())
+    } else {
+        None
+    };
+}
+
+/// A finite field element. Contents are opaque.
+/// All functions are constant-time.
+///
+/// A field element x is encoded into bytes by using the unsigned
+/// little-endian convention over the unique representant of x in the
+/// [0..(p-1)] range. There is no sign bit.
+pub struct Fp<P, const N: usize>(pub(crate) [u64; N], PhantomData<fn() -> P>);
+
+// Clone and Copy are implemented manually, so that they do not require the
+// same traits on the parameters type.
+impl<P, const N: usize> Clone for Fp<P, N> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P, const N: usize> Copy for Fp<P, N> {}
+
+/// Encoding length (in bytes) of the elements of the field with the given
+/// modulus, i.e. `(BIT_LENGTH + 7) / 8`. This is meant for the `Encoding`
+/// types of `FieldParams`.
+pub const fn encoded_length(modulus: &[u64]) -> usize {
+    let mut n = modulus.len();
+    while n > 0 && modulus[n - 1] == 0 {
+        n -= 1;
+    }
+    if n == 0 {
+        return 0;
+    }
+    let bl = (n - 1) * 64 + 64 - modulus[n - 1].leading_zeros() as usize;
+    (bl + 7) >> 3
+}
+
+// Compare two arrays of words in a const context.
+const fn const_eq(a: &[u64], b: &[u64]) -> bool {
+    let mut eq = a.len() == b.len();
+    let mut i = 0;
+    while eq && i < a.len() {
+        eq = a[i] == b[i];
+        i += 1;
+    }
+    eq
+}
+
+// For very large moduli (such as 4000+ bits) some of the const functions can
+// take a long time. The slowest constants (R, R2, TDEC, TFIXDIV and SQRT_EXP)
+// are therefore read from the parameters when given, see precompute().
+impl<P: FieldParams<N>, const N: usize> Fp<P, N> {
+    // IMPLEMENTATION NOTES
+    // --------------------
+    //
+    // Modulus is p. Each element is represented over N limbs, in base
+    // 2^64.
+    //
+    // Let R = 2^(64*N) mod p. A field element x is represented by
+    // the integer x*R mod p, in the [0..(p-1)] range. The limbs are in
+    // little-endian order (limb 0 is the least significant).
+    //
+    // Multiplications use Montgomery multiplication: given x and y,
+    // the value (x*y)/R mod p is computed. Since our values are in
+    // Montgomery representation, what is computed is really
+    // (x*R)*(y*R)/R = x*y*R mod p, which is the correct product. The
+    // decoding and encoding functions apply the required conversions; the
+    // use of Montgomery representation is not visible to other code using
+    // this type.
+
+    // Number of words and bit length of the field characteristic
+    pub const N: usize = N;
+    pub const BIT_LENGTH: usize = Self::mod_bitlen();
+    pub const MODULUS: [u64; N] = {
+        Self::check_params();
+        P::MODULUS
+    };
+
+    // Multiplier for decode_reduce().
+    const CLEN: usize = 8 * (Self::N - 1);
+    const TDEC: Self = Self::new(Self::CONSTANTS.tdec);
+
+    // Limb backend, and whether the 32-bit backend is used (see fp_gen32.rs)
+    pub const BACKEND: Backend = P::BACKEND;
+    const W32: bool = matches!(P::BACKEND, Backend::W32);
+    // Whether externally supplied functions are used (see fp_fiat.rs)
+    const FIAT: bool = P::FIAT.is_some();
+
+    // Constants used for internal arithmetic
+    const P0I: u64 = Self::ninv64(Self::MODULUS[0]);
+    // The given precomputed constants, or their values computed once.
+    const CONSTANTS: Precomputed<N> = match P::PRECOMPUTED {
+        Some(pc) => pc,
+        None => Self::precompute(),
+    };
+    const R: Self = Self::new(Self::CONSTANTS.r);
+    const R2: Self = Self::new(Self::CONSTANTS.r2);
+    const P1: u64 = Self::top_u32();
+    const P1DIV_M: u64 =
+        1 + ((((((1u64 << 32) - Self::P1) as u128) << 64) / (Self::P1 as u128)) as u64);
+    const NUM1: usize = (2 * Self::BIT_LENGTH - 34) / 31;
+    const NUM2: usize = 2 * Self::BIT_LENGTH - 31 * Self::NUM1 - 2;
+    const TFIXDIV: Self = Self::new(Self::CONSTANTS.tfixdiv);
+    // With externally supplied functions, sums of products are computed
+    // with them, and are not faster than the schoolbook method.
+    pub const SUM_OF_PRODUCTS_ADDITIONAL_SUB: bool = Self::FIAT || Self::sum_of_products_check();
+
+    // For large N, products are computed with Karatsuba multiplication
+    // and then reduced, rather than with interleaved Montgomery.
+    pub const KARATSUBA_THRESHOLD: usize = P::KARATSUBA_THRESHOLD;
+    pub const USE_KARATSUBA: bool =
+        !Self::W32 && !Self::FIAT && Self::N >= Self::KARATSUBA_THRESHOLD;
+    // The scratch space is allocated as 5 arrays of N words, which is
+    // enough for all N >= KARATSUBA_LEAF_LIMBS.
+    const KARATSUBA_SCRATCH_LEN: usize = {
+        let len = crate::utils64::karatsuba_scratch_len(Self::N);
+        assert!(len <= 5 * Self::N);
+        len
+    };
+
+    // Predefined constants used externally
+    pub const ZERO: Self = Self::new([0u64; N]);
+    pub const ONE: Self = Self::R;
+    pub const TWO: Self = Self::const_small(2);
+    pub const THREE: Self = Self::const_small(3);
+    pub const FOUR: Self = Self::const_small(4);
+    pub const MINUS_ONE: Self = Self::const_neg(Self::R);
+
+    /// Encoding length of a field element (in bytes). All elements
+    /// always encode into exactly that many bytes. Encoding is
+    /// canonical: a given field element has a unique valid encoding,
+    /// and the decoding process verifies that this specific encoding
+    /// was used.
+    pub const ENCODED_LENGTH: usize = (Self::BIT_LENGTH + 7) >> 3;
+
+    /// Build an element from its Montgomery representation x*R mod p,
+    /// with R = 2^(64*N), over `N` words in little-endian order. The words
+    /// are not checked, and MUST be less than p; see
+    /// `from_montgomery_limbs()` for a checked version.
+    pub const fn new(input: [u64; N]) -> Self {
+        Self(input, PhantomData)
+    }
+
+    /// Return 0xFFFFFFFF if this value is zero, or 0x00000000 otherwise.
+    #[inline]
+    pub fn is_zero(self) -> u32 {
+        let mut x = self.0[0];
+        for i in 1..Self::N {
+            x |= self.0[i];
+        }
+        (!crate::utils64::sgnw(x | x.wrapping_neg())) as u32
+    }
+
+    /// Return 0xFFFFFFFF if this value is equal to rhs, or 0x00000000
+    /// otherwise.
+    #[inline(always)]
+    pub fn equals(self, rhs: &Self) -> u32 {
+        let mut r = 0u64;
+        for i in 0..Self::N {
+            r |= self.0[i] ^ rhs.0[i];
+        }
+        (((r | r.wrapping_neg()) >> 63) as u32).wrapping_sub(1)
+    }
+
+    /// Add `rhs` to this value.
+    #[inline]
+    pub fn set_add(&mut self, rhs: &Self) {
+        if let Some(f) = P::FIAT {
+            self.set_add_fiat(&f, rhs);
+            return;
+        }
+
+        // raw addition.
+        let mut cc1 = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(self.0[i], rhs.0[i], cc1);
+            self.0[i] = d;
+            cc1 = ee;
+        }
+
+        // subtract modulus.
+        let mut cc2 = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(self.0[i], Self::MODULUS[i], cc2);
+            self.0[i] = d;
+            cc2 = ee;
+        }
+
+        // add back modulus if the result was negative, i.e. cc1 - cc2 < 0.
+        let mm = (cc1 as u64).wrapping_sub(cc2 as u64);
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(self.0[i], mm & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    /// Subtract `rhs` from this value.
+    #[inline]
+    pub fn set_sub(&mut self, rhs: &Self) {
+        if let Some(f) = P::FIAT {
+            self.set_sub_fiat(&f, rhs);
+            return;
+        }
+
+        // raw subtraction
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(self.0[i], rhs.0[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+
+        // add back modulus if the result was negative
+        let mm = (cc as u64).wrapping_neg();
+        cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(self.0[i], mm & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    /// Negate this value.
+    #[inline]
+    pub fn set_neg(&mut self) {
+        if let Some(f) = P::FIAT {
+            self.set_neg_fiat(&f);
+            return;
+        }
+
+        // subtract from zero
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(0, self.0[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+
+        // add back the modulus if needed (i.e. if input was non-zero)
+        let mm = (cc as u64).wrapping_neg();
+        cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(self.0[i], mm & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    // Perform Montgomery reduction (division by R) on this value.
+    // Internal note: if self has proper contents (value less than p), then
+    // this necessarily yields a properly reduced value. If self is not
+    // properly reduced, then the output is in [0..p] inclusive.
+    #[inline]
+    fn set_montyred(&mut self) {
+        if let Some(f) = P::FIAT {
+            self.set_montyred_fiat(&f);
+            return;
+        }
+        if Self::W32 {
+            self.set_montyred_w32();
+            return;
+        }
+        for _ in 0..Self::N {
+            let f = self.0[0].wrapping_mul(Self::P0I);
+            let (_, mut cc) = crate::utils64::umull_add(f, Self::MODULUS[0], self.0[0]);
+            for i in 1..Self::N {
+                let (d, hi) = crate::utils64::umull_add2(f, Self::MODULUS[i], self.0[i], cc);
+                self.0[i - 1] = d;
+                cc = hi;
+            }
+            self.0[Self::N - 1] = cc;
+        }
+    }
+
+    /// Multiply this value by `rhs`, optimised for when N is "small"
+    #[inline]
+    fn set_mul_small_word_len(&mut self, rhs: &Self) {
+        let mut t = Self::ZERO;
+
+        let mut cch: u8 = 0;
+        for i in 0..Self::N {
+            let (lo, mut cc1) = crate::utils64::umull_add(rhs.0[i], self.0[0], t.0[0]);
+            t.0[0] = lo;
+            for j in 1..Self::N {
+                let (d, hi1) = crate::utils64::umull_add2(rhs.0[i], self.0[j], t.0[j], cc1);
+                cc1 = hi1;
+                t.0[j] = d;
+            }
+
+            let q = t.0[0].wrapping_mul(Self::P0I);
+
+            let (_, mut cc2) = crate::utils64::umull_add(q, Self::MODULUS[0], t.0[0]);
+            for j in 1..Self::N {
+                let (d, hi2) = crate::utils64::umull_add2(q, Self::MODULUS[j], t.0[j], cc2);
+                cc2 = hi2;
+                t.0[j - 1] = d;
+            }
+            let (d, ee) = crate::utils64::addcarry_u64(cc1, cc2, cch);
+            t.0[Self::N - 1] = d;
+            cch = ee;
+        }
+
+        // final reduction: subtract modulus if necessary
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(t.0[i], Self::MODULUS[i], cc);
+            t.0[i] = d;
+            cc = ee;
+        }
+        let mask = (cch as u64).wrapping_sub(cc as u64);
+        cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(t.0[i], mask & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    /// Multiply this value by `rhs`, optimised for when N is "large"
+    #[inline]
+    fn set_mul_large_word_len(&mut self, rhs: &Self) {
+        let mut t = Self::ZERO;
+
+        // combined muls + reduction
+        let mut cch = 0;
+        for i in 0..Self::N {
+            let f = rhs.0[i];
+            let (lo, mut cc1) = crate::utils64::umull_add(f, self.0[0], t.0[0]);
+            let g = lo.wrapping_mul(Self::P0I);
+            let (_, mut cc2) = crate::utils64::umull_add(g, Self::MODULUS[0], lo);
+            for j in 1..Self::N {
+                let (d, hi1) = crate::utils64::umull_add2(f, self.0[j], t.0[j], cc1);
+                cc1 = hi1;
+                let (d, hi2) = crate::utils64::umull_add2(g, Self::MODULUS[j], d, cc2);
+                cc2 = hi2;
+                t.0[j - 1] = d;
+            }
+            let (d, ee) = crate::utils64::addcarry_u64(cc1, cc2, cch);
+            t.0[Self::N - 1] = d;
+            cch = ee;
+        }
+
+        // final reduction: subtract modulus if necessary
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(t.0[i], Self::MODULUS[i], cc);
+            t.0[i] = d;
+            cc = ee;
+        }
+        let mm = (cch as u64).wrapping_sub(cc as u64);
+        cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(t.0[i], mm & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    // Set this value to the Montgomery reduction of the 2*N-word
+    // integer t, i.e. t/R mod p. The input MUST be less than p*R,
+    // which is the case for the product of two field elements.
+    #[inline]
+    fn set_montyred_wide(&mut self, t: &[[u64; N]; 2]) {
+        // We use the following facts:
+        //  - upper half is necessarily less than p
+        //  - set_montyred() accepts a full-limbs input and outputs a
+        //    value of at most p
+        //  - set_add() tolerates an input operand equal to p provided
+        //    that the sum is less than 2*p
+        self.0 = t[0];
+        self.set_montyred();
+        self.set_add(&Self::new(t[1]));
+    }
+
+    /// Multiply this value by `rhs`, using Karatsuba multiplication
+    /// over integers followed by Montgomery reduction. Intended for
+    /// when N is "very large" (thousands of bits).
+    #[inline]
+    fn set_mul_karatsuba(&mut self, rhs: &Self) {
+        let mut t = [[0u64; N]; 2];
+        let mut s = [[0u64; N]; 5];
+        crate::utils64::mul_karatsuba(
+            t.as_flattened_mut(),
+            &self.0,
+            &rhs.0,
+            &mut s.as_flattened_mut()[..Self::KARATSUBA_SCRATCH_LEN],
+        );
+        self.set_montyred_wide(&t);
+    }
+
+    /// Multiply this value by `rhs`.
+    #[inline]
+    pub fn set_mul(&mut self, rhs: &Self) {
+        // TODO: what's the best bound here?
+        if let Some(f) = P::FIAT {
+            self.set_mul_fiat(&f, rhs);
+        } else if Self::W32 {
+            self.set_mul_w32(rhs);
+        } else if Self::USE_KARATSUBA {
+            self.set_mul_karatsuba(rhs);
+        } else if Self::N < 15 {
+            self.set_mul_small_word_len(rhs);
+        } else {
+            self.set_mul_large_word_len(rhs);
+        }
+    }
+
+    /// Replace this value with its square.
+    // Some of the loops below are empty for small N.
+    #[allow(clippy::reversed_empty_ranges, clippy::needless_range_loop)]
+    #[inline]
+    pub fn set_square(&mut self) {
+        if let Some(f) = P::FIAT {
+            self.set_square_fiat(&f);
+            return;
+        }
+        if Self::W32 {
+            let x = *self;
+            self.set_mul_w32(&x);
+            return;
+        }
+
+        // For very large moduli, the Karatsuba product beats the
+        // dedicated squaring below.
+        if Self::USE_KARATSUBA {
+            let x = *self;
+            self.set_mul_karatsuba(&x);
+            return;
+        }
+
+        // FIXME: this turns out to be slower than set_mul() on x86_64
+        // when N >= 23. This is probably due to the more complicated
+        // loop bounds. Full unrolling helps, but can only be done at
+        // the crate level:
+        //   RUSTFLAGS="-C llvm-args=-unroll-threshold=1200"
+        // This impacts all the code in the crate, and is thus
+        // probably not a very good idea.
+
+        // Compute the square over integers.
+        let mut tt = [[0u64; N]; 2];
+        let t = tt.as_flattened_mut();
+
+        // sum_{i<j} a_i*a_j*2^(64*(i+j)) < 2^(64*(2*N-1))
+        // -> t[2*N-1] remains at zero
+        let f = self.0[0];
+        let (d, mut cc) = crate::utils64::umull(f, self.0[1]);
+        t[1] = d;
+        for j in 2..Self::N {
+            let (d, hi) = crate::utils64::umull_add(f, self.0[j], cc);
+            t[j] = d;
+            cc = hi;
+        }
+        t[Self::N] = cc;
+        for i in 1..(Self::N - 1) {
+            let f = self.0[i];
+            let (d, mut cc) = crate::utils64::umull_add(f, self.0[i + 1], t[(i << 1) + 1]);
+            t[(i << 1) + 1] = d;
+            for j in (i + 2)..Self::N {
+                let (d, hi) = crate::utils64::umull_add2(f, self.0[j], t[i + j], cc);
+                t[i + j] = d;
+                cc = hi;
+            }
+            t[i + Self::N] = cc;
+        }
+
+        // Double the partial sum.
+        // -> t contains sum_{i!=j} a_i*a_j*2^(64*(i+j))
+        let mut cc = 0;
+        for i in 1..((Self::N << 1) - 1) {
+            let w = t[i];
+            let ee = w >> 63;
+            t[i] = (w << 1) | cc;
+            cc = ee;
+        }
+        t[(Self::N << 1) - 1] = cc;
+
+        // Add the squares a_i*a_i*w^(64*2*i).
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (lo, hi) = crate::utils64::umull(self.0[i], self.0[i]);
+            let (d0, ee) = crate::utils64::addcarry_u64(lo, t[i << 1], cc);
+            let (d1, ee) = crate::utils64::addcarry_u64(hi, t[(i << 1) + 1], ee);
+            t[i << 1] = d0;
+            t[(i << 1) + 1] = d1;
+            cc = ee;
+        }
+
+        // Apply Montgomery reduction.
+        self.set_montyred_wide(&tt);
+    }
+
+    /// Halve this value.
+    #[inline]
+    pub fn set_half(&mut self) {
+        let m = (self.0[0] & 1).wrapping_neg();
+        let (mut dd, mut cc) = crate::utils64::addcarry_u64(self.0[0], m & Self::MODULUS[0], 0);
+        dd >>= 1;
+        for i in 1..Self::N {
+            let (x, ee) = crate::utils64::addcarry_u64(self.0[i], m & Self::MODULUS[i], cc);
+            cc = ee;
+            self.0[i - 1] = dd | (x << 63);
+            dd = x >> 1;
+        }
+        self.0[Self::N - 1] = dd | ((cc as u64) << 63);
+    }
+
+    /// Double this value.
+    #[inline]
+    pub fn set_mul2(&mut self) {
+        // Double (as an integer) and subtract the modulus.
+        let mut cc = 0;
+        let mut tb = 0;
+        for i in 0..Self::N {
+            let w = self.0[i];
+            let t = (w << 1) | tb;
+            tb = w >> 63;
+            let (d, ee) = crate::utils64::subborrow_u64(t, Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+
+        // add back modulus if the result was negative
+        let mm = tb.wrapping_sub(cc as u64);
+        cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(self.0[i], mm & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    /// Multiply this value by a small signed integer k.
+    #[inline]
+    pub fn set_mul_small(&mut self, k: i32) {
+        // Get the absolute value of the multiplier (but remember the sign).
+        let sk = (k >> 31) as u32;
+        let ak = ((k as u32) ^ sk).wrapping_sub(sk);
+
+        // Without a cheap 64-bit quotient estimate, the 32-bit backend
+        // simply converts |k| into Montgomery representation and multiplies,
+        // as do externally supplied functions.
+        if Self::W32 || Self::FIAT {
+            self.set_mul(&Self::from(ak));
+            self.set_cond_neg(sk);
+            return;
+        }
+
+        // Do the product over integers.
+        let (d, mut hi) = crate::utils64::umull(self.0[0], ak as u64);
+        self.0[0] = d;
+        for i in 1..Self::N {
+            let (d, ee) = crate::utils64::umull_add(self.0[i], ak as u64, hi);
+            self.0[i] = d;
+            hi = ee;
+        }
+
+        // We write:
+        //    p = p1*2^m + p0   (modulus)
+        //    x = x1*2^m + x0   (unreduced product)
+        // with:
+        //    2^31 <= p1 < 2^32
+        //    0 <= p0 < 2^m
+        //    0 <= x0 < 2^m
+        // Since the current value x is the product of the input (less
+        // than p) by a multiplier of at most 2^31, we know that:
+        //    0 <= x < p*2^31 < 2^(63+m)
+        //    0 <= x1 < 2^63.
+        // We compute:
+        //    b = floor(x1/p1)
+        // Analysis shows that floor(x/p) = b, b-1 or b+1.
+        //
+        // We thus obtain b, then increment it (unless b == p1), then
+        // subtract b*p from x; we then add back p repeatedly until a
+        // non-negative result is obtained. At most two conditional
+        // additions are needed to achieve that result.
+        //
+        // Division by p1 can be done with the Granlund-Montgomery method:
+        //    https://dl.acm.org/doi/10.1145/773473.178249
+        // (LLVM usually applies that method, but may fail to do so if for
+        // instance optimizing for code size on some platforms, thus it is
+        // best to apply the method explicitly so that constant-time code
+        // is more reliably achieved.)
+
+        // Extract top word of x.
+        let bl = Self::BIT_LENGTH & 63;
+        let x1 = if bl == 0 {
+            (self.0[Self::N - 1] >> 32) | (hi << 32)
+        } else if bl < 32 {
+            (self.0[Self::N - 1] << (32 - bl)) | (self.0[Self::N - 2] >> (32 + bl))
+        } else if bl == 32 {
+            self.0[Self::N - 1]
+        } else {
+            (hi << (96 - bl)) | (self.0[Self::N - 1] >> (bl - 32))
+        };
+
+        // Compute b = floor(x1/p1).
+        let (_, t) = crate::utils64::umull(x1, Self::P1DIV_M);
+        let b = (x1.wrapping_sub(t) >> 1).wrapping_add(t) >> 31;
+
+        // Add 1 to b, unless b == p1 (we cannot have b > p1).
+        let b = b + (Self::P1.wrapping_sub(b) >> 63);
+
+        // Subtract b*p from x.
+        let mut cc1 = 0;
+        let mut cc2 = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::umull_add(b, Self::MODULUS[i], cc1);
+            cc1 = ee;
+            let (d, ee) = crate::utils64::subborrow_u64(self.0[i], d, cc2);
+            self.0[i] = d;
+            cc2 = ee;
+        }
+        let (mut hi, _) = crate::utils64::subborrow_u64(hi, cc1, cc2);
+
+        // Add p (at most twice) as long as the value is negative.
+        for _ in 0..2 {
+            let m = crate::utils64::sgnw(hi);
+            let mut cc = 0;
+            for i in 0..Self::N {
+                let (d, ee) = crate::utils64::addcarry_u64(self.0[i], m & Self::MODULUS[i], cc);
+                self.0[i] = d;
+                cc = ee;
+            }
+            hi = hi.wrapping_add(cc as u64);
+        }
+
+        // We computed self*|k|; we must adjust for the sign of k.
+        self.set_cond_neg(sk);
+    }
+
+    /// Set this value to either a or b, depending on whether the control
+    /// word ctl is 0x00000000 or 0xFFFFFFFF, respectively.
+    /// The value of ctl MUST be either 0x00000000 or 0xFFFFFFFF.
+    #[inline]
+    pub fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+        let c = (ctl as u64) | ((ctl as u64) << 32);
+        for i in 0..Self::N {
+            let wa = a.0[i];
+            let wb = b.0[i];
+            self.0[i] = wa ^ (c & (wa ^ wb));
+        }
+    }
+
+    /// Set this value to rhs if ctl is 0xFFFFFFFF; leave it unchanged if
+    /// ctl is 0x00000000.
+    /// The value of ctl MUST be either 0x00000000 or 0xFFFFFFFF.
+    #[inline]
+    pub fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+        let c = (ctl as u64) | ((ctl as u64) << 32);
+        for i in 0..Self::N {
+            let wa = self.0[i];
+            let wb = rhs.0[i];
+            self.0[i] = wa ^ (c & (wa ^ wb));
+        }
+    }
+
+    /// Exchange the values of a and b is ctl is 0xFFFFFFFF; leave both
+    /// values unchanged if ctl is 0x00000000.
+    /// The value of ctl MUST be either 0x00000000 or 0xFFFFFFFF.
+    #[inline]
+    pub fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+        let c = (ctl as u64) | ((ctl as u64) << 32);
+        for i in 0..Self::N {
+            let wa = a.0[i];
+            let wb = b.0[i];
+            let wc = c & (wa ^ wb);
+            a.0[i] = wa ^ wc;
+            b.0[i] = wb ^ wc;
+        }
+    }
+
+    // Set this value to (u*f+v*g)/2^64. Coefficients f
+    // and g are provided as u64, but they are signed integers in the
+    // [-2^62..+2^62] range.
+    #[inline]
+    fn set_montylin(&mut self, u: &Self, v: &Self, f: u64, g: u64) {
+        // Make sure f and g are non-negative.
+        let sf = crate::utils64::sgnw(f);
+        let f = (f ^ sf).wrapping_sub(sf);
+        let tu = Self::select(u, &-u, sf as u32);
+        let sg = crate::utils64::sgnw(g);
+        let g = (g ^ sg).wrapping_sub(sg);
+        let tv = Self::select(v, &-v, sg as u32);
+
+        let (d, mut cc) = crate::utils64::umull_x2(tu.0[0], f, tv.0[0], g);
+        self.0[0] = d;
+        for i in 1..Self::N {
+            let (d, hi) = crate::utils64::umull_x2_add(tu.0[i], f, tv.0[i], g, cc);
+            self.0[i] = d;
+            cc = hi;
+        }
+        let up = cc;
+
+        // Montgomery reduction (one round)
+        let k = self.0[0].wrapping_mul(Self::P0I);
+        let (_, mut cc) = crate::utils64::umull_add(k, Self::MODULUS[0], self.0[0]);
+        for i in 1..Self::N {
+            let (d, hi) = crate::utils64::umull_add2(k, Self::MODULUS[i], self.0[i], cc);
+            self.0[i - 1] = d;
+            cc = hi;
+        }
+        let (d, cc1) = crate::utils64::addcarry_u64(up, cc, 0);
+        self.0[Self::N - 1] = d;
+
+        // |f| <= 2^62 and |g| <= 2^62, therefore |u*f + v*g| < p*2^63
+        // We added less than p*2^64, and divided by 2^64, so the result
+        // is less than 2*p and a single conditional subtraction is enough.
+        let mut cc2 = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(self.0[i], Self::MODULUS[i], cc2);
+            self.0[i] = d;
+            cc2 = ee;
+        }
+        let mm = (cc1 as u64).wrapping_sub(cc2 as u64);
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::addcarry_u64(self.0[i], mm & Self::MODULUS[i], cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+    }
+
+    #[inline(always)]
+    fn montylin(a: &Self, b: &Self, f: u64, g: u64) -> Self {
+        let mut r = Self::ZERO;
+        r.set_montylin(a, b, f, g);
+        r
+    }
+
+    // Set this value to abs((a*f + b*g)/2^31). Values a and b are
+    // interpreted as plain integers (not modular). Coefficients f and
+    // g are provided as u64 but they really are signed integers in the
+    // [-2^31..+2^31] range (inclusive). The low 31 bits of a*f + b*g
+    // are dropped (i.e. the division is assumed to be exact). The result
+    // is assumed to fit in N limbs (extra high bits, if any, are
+    // dropped). The absolute value of (a*f + b*g)/2^31 is computed.
+    // Returned value is -1 (as a u64) if a*f + b*g was negative, 0
+    // otherwise.
+    #[inline]
+    fn set_lindiv31abs(&mut self, a: &Self, b: &Self, f: u64, g: u64) -> u64 {
+        // Replace f and g with abs(f) and abs(g), but remember the
+        // original signs.
+        let sf = crate::utils64::sgnw(f);
+        let f = (f ^ sf).wrapping_sub(sf);
+        let sg = crate::utils64::sgnw(g);
+        let g = (g ^ sg).wrapping_sub(sg);
+
+        // Compute a*f + b*g (upper word in 'up')
+        let mut cc1 = 0;
+        let mut cc2 = 0;
+        let mut cc3 = 0;
+        for i in 0..Self::N {
+            let (d1, ee1) = crate::utils64::subborrow_u64(a.0[i] ^ sf, sf, cc1);
+            cc1 = ee1;
+            let (d2, ee2) = crate::utils64::subborrow_u64(b.0[i] ^ sg, sg, cc2);
+            cc2 = ee2;
+            let (d3, hi3) = crate::utils64::umull_x2_add(d1, f, d2, g, cc3);
+            self.0[i] = d3;
+            cc3 = hi3;
+        }
+        let up = cc3
+            .wrapping_sub((cc1 as u64).wrapping_neg() & f)
+            .wrapping_sub((cc2 as u64).wrapping_neg() & g);
+
+        // Right-shift the result by 31 bits.
+        for i in 0..(Self::N - 1) {
+            self.0[i] = (self.0[i] >> 31) | (self.0[i + 1] << 33);
+        }
+        self.0[Self::N - 1] = (self.0[Self::N - 1] >> 31) | (up << 33);
+
+        // Negate the result if (a*f + b*g) was negative.
+        let w = crate::utils64::sgnw(up);
+        let mut cc = 0;
+        for i in 0..Self::N {
+            let (d, ee) = crate::utils64::subborrow_u64(self.0[i] ^ w, w, cc);
+            self.0[i] = d;
+            cc = ee;
+        }
+
+        w
+    }
+
+    #[inline(always)]
+    fn lindiv31abs(a: &Self, b: &Self, f: u64, g: u64) -> (Self, u64) {
+        let mut r = Self::ZERO;
+        let ng = r.set_lindiv31abs(a, b, f, g);
+        (r, ng)
+    }
+
+    /// Divide this value by `y`. If `y` is zero, then this sets this value
+    /// to zero.
+    pub fn set_div(&mut self, y: &Self) {
+        if Self::W32 {
+            self.set_div_w32(y);
+            return;
+        }
+
+        // a <- y
+        // b <- p (modulus)
+        // u <- x (self)
+        // v <- 0
+        //
+        // Invariants:
+        //    a*x = y*u mod p
+        //    b*x = y*v mod p
+        //    b is always odd
+        //
+        // At each step:
+        //    if a is even, then:
+        //        a <- a/2, u <- u/2 mod p
+        //    else:
+        //        if a < b:
+        //            (a, u, b, v) <- (b, v, a, u)
+        //        a <- (a - b)/2
+        //        u <- (u - v)/2 mod p
+        //
+        // We optimize this algorithm following:
+        //    https://eprint.iacr.org/2020/972
+
+        let mut a = *y;
+        let mut b = Self::new(Self::MODULUS);
+        let mut u = *self;
+        let mut v = Self::ZERO;
+
+        // Generic loop; each iteration reduces the sum of the sizes
+        // of a and b by at least 31, and that sum starts at 2*BITLEN
+        // (at most). We need to run it until the sum of the two lengths
+        // is at most 64.
+        for _ in 0..Self::NUM1 {
+            // Get approximations of a and b over 64 bits:
+            //  - If len(a) <= 64 and len(b) <= 64, then we just
+            //    use their values (low limbs).
+            //  - Otherwise, with n = max(len(a), len(b)), we use:
+            //       (a mod 2^31) + 2^31*floor(a / 2^(n - 33))
+            //       (b mod 2^31) + 2^31*floor(b / 2^(n - 33))
+            let mut c_hi = 0xFFFFFFFFFFFFFFFFu64;
+            let mut c_lo = 0xFFFFFFFFFFFFFFFFu64;
+            let mut a_hi = 0u64;
+            let mut a_lo = 0u64;
+            let mut b_hi = 0u64;
+            let mut b_lo = 0u64;
+            for j in (0..Self::N).rev() {
+                let aw = a.0[j];
+                let bw = b.0[j];
+                a_hi ^= (a_hi ^ aw) & c_hi;
+                a_lo ^= (a_lo ^ aw) & c_lo;
+                b_hi ^= (b_hi ^ bw) & c_hi;
+                b_lo ^= (b_lo ^ bw) & c_lo;
+                c_lo = c_hi;
+                let mw = aw | bw;
+                c_hi &= ((mw | mw.wrapping_neg()) >> 63).wrapping_sub(1);
+            }
+
+            // If c_lo = 0, then we grabbed two words for a and b.
+            // If c_lo != 0, then c_hi = 0 (they cannot be both non-zero
+            // since that would mean that a = b = 0, but b is odd). In that
+            // case, we grabbed one word (in a_hi and b_hi) and both values
+            // fit in 64 bits.
+            let s = crate::utils64::lzcnt(a_hi | b_hi);
+            let mut xa = (a_hi << s) | ((a_lo >> 1) >> (63 - s));
+            let mut xb = (b_hi << s) | ((b_lo >> 1) >> (63 - s));
+            xa = (xa & 0xFFFFFFFF80000000) | (a.0[0] & 0x000000007FFFFFFF);
+            xb = (xb & 0xFFFFFFFF80000000) | (b.0[0] & 0x000000007FFFFFFF);
+
+            // If c_lo != 0, then we should ignore the computed xa and xb,
+            // and instead use the low limbs directly.
+            xa ^= c_lo & (xa ^ a.0[0]);
+            xb ^= c_lo & (xb ^ b.0[0]);
+
+            // Compute the 31 inner iterations.
+            let mut fg0 = 1u64;
+            let mut fg1 = 1u64 << 32;
+            for _ in 0..31 {
+                let a_odd = (xa & 1).wrapping_neg();
+                let (_, cc) = crate::utils64::subborrow_u64(xa, xb, 0);
+                let swap = a_odd & (cc as u64).wrapping_neg();
+                let t1 = swap & (xa ^ xb);
+                xa ^= t1;
+                xb ^= t1;
+                let t2 = swap & (fg0 ^ fg1);
+                fg0 ^= t2;
+                fg1 ^= t2;
+                xa = xa.wrapping_sub(a_odd & xb);
+                fg0 = fg0.wrapping_sub(a_odd & fg1);
+                xa >>= 1;
+                fg1 <<= 1;
+            }
+            fg0 = fg0.wrapping_add(0x7FFFFFFF7FFFFFFF);
+            fg1 = fg1.wrapping_add(0x7FFFFFFF7FFFFFFF);
+            let f0 = (fg0 & 0xFFFFFFFF).wrapping_sub(0x7FFFFFFF);
+            let g0 = (fg0 >> 32).wrapping_sub(0x7FFFFFFF);
+            let f1 = (fg1 & 0xFFFFFFFF).wrapping_sub(0x7FFFFFFF);
+            let g1 = (fg1 >> 32).wrapping_sub(0x7FFFFFFF);
+
+            // Propagate updates to a, b, u and v.
+            let (na, nega) = Self::lindiv31abs(&a, &b, f0, g0);
+            let (nb, negb) = Self::lindiv31abs(&a, &b, f1, g1);
+            let f0 = (f0 ^ nega).wrapping_sub(nega);
+            let g0 = (g0 ^ nega).wrapping_sub(nega);
+            let f1 = (f1 ^ negb).wrapping_sub(negb);
+            let g1 = (g1 ^ negb).wrapping_sub(negb);
+            let nu = Self::montylin(&u, &v, f0, g0);
+            let nv = Self::montylin(&u, &v, f1, g1);
+            a = na;
+            b = nb;
+            u = nu;
+            v = nv;
+        }
+
+        // If y is non-zero, then the final GCD is 1, and
+        // len(a) + len(b) <= NUM2 + 2 at this point (initially,
+        // len(a) + len(b) <= 2*BITLEN, and each outer iteration reduces
+        // the total by at least 31). Thus, the two values fit in one word
+        // and we can finish the computation that way. We only need NUM2
+        // iterations to reach the point where b = 1.
+        let mut xa = a.0[0];
+        let mut xb = b.0[0];
+        let mut f0 = 1u64;
+        let mut g0 = 0u64;
+        let mut f1 = 0u64;
+        let mut g1 = 1u64;
+        for _ in 0..Self::NUM2 {
+            let a_odd = (xa & 1).wrapping_neg();
+            let (_, cc) = crate::utils64::subborrow_u64(xa, xb, 0);
+            let swap = a_odd & (cc as u64).wrapping_neg();
+            let t1 = swap & (xa ^ xb);
+            xa ^= t1;
+            xb ^= t1;
+            let t2 = swap & (f0 ^ f1);
+            f0 ^= t2;
+            f1 ^= t2;
+            let t3 = swap & (g0 ^ g1);
+            g0 ^= t3;
+            g1 ^= t3;
+            xa = xa.wrapping_sub(a_odd & xb);
+            f0 = f0.wrapping_sub(a_odd & f1);
+            g0 = g0.wrapping_sub(a_odd & g1);
+            xa >>= 1;
+            f1 <<= 1;
+            g1 <<= 1;
+        }
+
+        self.set_montylin(&u, &v, f1, g1);
+
+        // If y != 0 then b = 1 at this point. If y == 0, then we
+        // force the result to zero.
+        let w = !y.is_zero();
+        let w = ((w as u64) << 32) | (w as u64);
+        for i in 0..Self::N {
+            self.0[i] &= w;
+        }
+
+        // At this point, each outer iteration injected 31 extra doublings,
+        // plus NUM2 for the last loop, for a total of NUM1*31 + NUM2.
+        // Each montylin() call divided by 2^64, so in total we really
+        // divided the value by 2^(64*(NUM1+1) - 31*NUM1 - NUM2).
+        //
+        // Moreover, both divisor and dividend were in Montgomery
+        // representation, so the result is not in Montgomery representation
+        // (the two R factors canceled each other). We want the result
+        // in Montgomery representation, i.e. multiplied by 2^(64*N).
+        // Therefore, we must multiply by 2^(33*NUM1 + 64 - NUM2 + 64*N),
+        // which we need in
+        self.set_mul(&Self::TFIXDIV);
+    }
+
+    /// Legendre symbol on this value. Return value is:
+    ///   0   if this value is zero
+    ///  +1   if this value is a non-zero quadratic residue
+    ///  -1   if this value is not a quadratic residue
+    pub fn legendre(self) -> i32 {
+        if Self::W32 {
+            return self.legendre_w32();
+        }
+
+        // This is the same optimized binary GCD as in division, except
+        // that we do not need to keep track of u and v. We can also
+        // work directly on the Montgomery representation because R = 2^1184
+        // is a square.
+        let mut a = self;
+        let mut b = Self::new(Self::MODULUS);
+        let mut ls = 0u64;
+
+        // Generic loop; each iteration reduces the sum of the sizes
+        // of a and b by at least 31, and that sum starts at 2*BITLEN
+        // (at most). We need to run it until the sum of the two lengths
+        // is at most 64.
+        for _ in 0..Self::NUM1 {
+            // Get approximations of a and b over 64 bits:
+            //  - If len(a) <= 64 and len(b) <= 64, then we just
+            //    use their values (low limbs).
+            //  - Otherwise, with n = max(len(a), len(b)), we use:
+            //       (a mod 2^31) + 2^31*floor(a / 2^(n - 33))
+            //       (b mod 2^31) + 2^31*floor(b / 2^(n - 33))
+            let mut c_hi = 0xFFFFFFFFFFFFFFFFu64;
+            let mut c_lo = 0xFFFFFFFFFFFFFFFFu64;
+            let mut a_hi = 0u64;
+            let mut a_lo = 0u64;
+            let mut b_hi = 0u64;
+            let mut b_lo = 0u64;
+            for j in (0..Self::N).rev() {
+                let aw = a.0[j];
+                let bw = b.0[j];
+                a_hi ^= (a_hi ^ aw) & c_hi;
+                a_lo ^= (a_lo ^ aw) & c_lo;
+                b_hi ^= (b_hi ^ bw) & c_hi;
+                b_lo ^= (b_lo ^ bw) & c_lo;
+                c_lo = c_hi;
+                let mw = aw | bw;
+                c_hi &= ((mw | mw.wrapping_neg()) >> 63).wrapping_sub(1);
+            }
+
+            // If c_lo = 0, then we grabbed two words for a and b.
+            // If c_lo != 0, then c_hi = 0 (they cannot be both non-zero
+            // since that would mean that a = b = 0, but b is odd). In that
+            // case, we grabbed one word (in a_hi and b_hi) and both values
+            // fit in 64 bits.
+            let s = crate::utils64::lzcnt(a_hi | b_hi);
+            let mut xa = (a_hi << s) | ((a_lo >> 1) >> (63 - s));
+            let mut xb = (b_hi << s) | ((b_lo >> 1) >> (63 - s));
+            xa = (xa & 0xFFFFFFFF80000000) | (a.0[0] & 0x000000007FFFFFFF);
+            xb = (xb & 0xFFFFFFFF80000000) | (b.0[0] & 0x000000007FFFFFFF);
+
+            // If c_lo != 0, then we should ignore the computed xa and xb,
+            // and instead use the low limbs directly.
+            xa ^= c_lo & (xa ^ a.0[0]);
+            xb ^= c_lo & (xb ^ b.0[0]);
+
+            // First 29 inner iterations.
+            let mut fg0 = 1u64;
+            let mut fg1 = 1u64 << 32;
+            for _ in 0..29 {
+                let a_odd = (xa & 1).wrapping_neg();
+                let (_, cc) = crate::utils64::subborrow_u64(xa, xb, 0);
+                let swap = a_odd & (cc as u64).wrapping_neg();
+                ls ^= swap & ((xa & xb) >> 1);
+                let t1 = swap & (xa ^ xb);
+                xa ^= t1;
+                xb ^= t1;
+                let t2 = swap & (fg0 ^ fg1);
+                fg0 ^= t2;
+                fg1 ^= t2;
+                xa = xa.wrapping_sub(a_odd & xb);
+                fg0 = fg0.wrapping_sub(a_odd & fg1);
+                xa >>= 1;
+                fg1 <<= 1;
+                ls ^= xb.wrapping_add(2) >> 2;
+            }
+
+            // Compute the updated a and b (low words only) to get enough
+            // bits for the next two iterations.
+            let fg0z = fg0.wrapping_add(0x7FFFFFFF7FFFFFFF);
+            let fg1z = fg1.wrapping_add(0x7FFFFFFF7FFFFFFF);
+            let f0 = (fg0z & 0xFFFFFFFF).wrapping_sub(0x7FFFFFFF);
+            let g0 = (fg0z >> 32).wrapping_sub(0x7FFFFFFF);
+            let f1 = (fg1z & 0xFFFFFFFF).wrapping_sub(0x7FFFFFFF);
+            let g1 = (fg1z >> 32).wrapping_sub(0x7FFFFFFF);
+            let mut a0 = a.0[0]
+                .wrapping_mul(f0)
+                .wrapping_add(b.0[0].wrapping_mul(g0))
+                >> 29;
+            let mut b0 = a.0[0]
+                .wrapping_mul(f1)
+                .wrapping_add(b.0[0].wrapping_mul(g1))
+                >> 29;
+            for _ in 0..2 {
+                let a_odd = (xa & 1).wrapping_neg();
+                let (_, cc) = crate::utils64::subborrow_u64(xa, xb, 0);
+                let swap = a_odd & (cc as u64).wrapping_neg();
+                ls ^= swap & ((a0 & b0) >> 1);
+                let t1 = swap & (xa ^ xb);
+                xa ^= t1;
+                xb ^= t1;
+                let t2 = swap & (fg0 ^ fg1);
+                fg0 ^= t2;
+                fg1 ^= t2;
+                let t3 = swap & (a0 ^ b0);
+                a0 ^= t3;
+                b0 ^= t3;
+                xa = xa.wrapping_sub(a_odd & xb);
+                fg0 = fg0.wrapping_sub(a_odd & fg1);
+                a0 = a0.wrapping_sub(a_odd & b0);
+                xa >>= 1;
+                fg1 <<= 1;
+                a0 >>= 1;
+                ls ^= b0.wrapping_add(2) >> 2;
+            }
+
+            // Propagate updates to a and b.
+            fg0 = fg0.wrapping_add(0x7FFFFFFF7FFFFFFF);
+            fg1 = fg1.wrapping_add(0x7FFFFFFF7FFFFFFF);
+            let f0 = (fg0 & 0xFFFFFFFF).wrapping_sub(0x7FFFFFFF);
+            let g0 = (fg0 >> 32).wrapping_sub(0x7FFFFFFF);
+            let f1 = (fg1 & 0xFFFFFFFF).wrapping_sub(0x7FFFFFFF);
+            let g1 = (fg1 >> 32).wrapping_sub(0x7FFFFFFF);
+
+            // Propagate updates to a, b, u and v.
+            let (na, nega) = Self::lindiv31abs(&a, &b, f0, g0);
+            let (nb, _) = Self::lindiv31abs(&a, &b, f1, g1);
+            ls ^= nega & (nb.0[0] >> 1);
+            a = na;
+            b = nb;
+        }
+
+        // If y is non-zero, then the final GCD is 1, and
+        // len(a) + len(b) <= NUM2 + 2 at this point (initially,
+        // len(a) + len(b) <= 2*BITLEN, and each outer iteration reduces
+        // the total by at least 31). Thus, the two values fit in one word
+        // and we can finish the computation that way. We only need NUM2
+        // iterations to reach the point where b = 1.
+        let mut xa = a.0[0];
+        let mut xb = b.0[0];
+        for _ in 0..Self::NUM2 {
+            let a_odd = (xa & 1).wrapping_neg();
+            let (_, cc) = crate::utils64::subborrow_u64(xa, xb, 0);
+            let swap = a_odd & (cc as u64).wrapping_neg();
+            ls ^= swap & ((xa & xb) >> 1);
+            let t1 = swap & (xa ^ xb);
+            xa ^= t1;
+            xb ^= t1;
+            xa = xa.wrapping_sub(a_odd & xb);
+            xa >>= 1;
+            ls ^= xb.wrapping_add(2) >> 2;
+        }
+
+        // At this point, if the source value was not zero, then the low
+        // bit of ls contains the QR status (0 = square, 1 = non-square),
+        // which we need to convert to the expected value (+1 or -1).
+        // If y == 0, then we return 0, per the API.
+        let r = 1u32.wrapping_sub(((ls as u32) & 1) << 1);
+        (r & !self.is_zero()) as i32
+    }
+
+    /// Encode this value into bytes. Encoding uses little-endian, has
+    /// a fixed size (for a given field), and is canonical.
+    #[inline(always)]
+    pub fn encode(self) -> P::Encoding {
+        let mut r = self;
+        r.set_montyred();
+        let w = r.0.map(u64::to_le_bytes);
+        let mut d = P::Encoding::ZERO;
+        d.as_mut()
+            .copy_from_slice(&w.as_flattened()[..Self::ENCODED_LENGTH]);
+        d
+    }
+
+    #[inline]
+    fn set_decode_nocheck(&mut self, buf: &[u8]) {
+        for i in 0..(Self::N - 1) {
+            self.0[i] =
+                u64::from_le_bytes(*<&[u8; 8]>::try_from(&buf[(8 * i)..(8 * i + 8)]).unwrap());
+        }
+        let mut w = 0u64;
+        for j in 0..(Self::ENCODED_LENGTH - (Self::N - 1) * 8) {
+            w |= (buf[(Self::N - 1) * 8 + j] as u64) << (8 * j);
+        }
+        self.0[Self::N - 1] = w;
+    }
+
+    /// Decode the provided bytes into a field element. Returned values
+    /// are the element and 0xFFFFFFFF on success, or the zero element and
+    /// 0x00000000 on failure. A failure is reported if the source slice
+    /// does not have exactly the canonical encoding length of a field
+    /// element (Self::ENCODED_LENGTH), or if the source encodes
+    /// an integer which is not in the [0..(p-1)] range.
+    #[inline(always)]
+    pub fn decode(buf: &[u8]) -> (Self, u32) {
+        if buf.len() != Self::ENCODED_LENGTH {
+            return (Self::ZERO, 0);
+        }
+
+        // decode raw value
+        let mut r = Self::ZERO;
+        r.set_decode_nocheck(buf);
+
+        // check that the source is canonical; clear if invalid
+        let m = r.set_clear_unreduced();
+
+        // convert to Montgomery representation
+        r.set_mul(&Self::R2);
+        (r, m)
+    }
+
+    // Set this value to zero if its words are not less than p, and return
+    // 0xFFFFFFFF if they were, or 0x00000000 otherwise.
+    #[inline]
+    fn set_clear_unreduced(&mut self) -> u32 {
+        let (_, mut cc) = crate::utils64::subborrow_u64(self.0[0], Self::MODULUS[0], 0);
+        for i in 1..Self::N {
+            let (_, ee) = crate::utils64::subborrow_u64(self.0[i], Self::MODULUS[i], cc);
+            cc = ee;
+        }
+        let m = (cc as u64).wrapping_neg();
+        for i in 0..Self::N {
+            self.0[i] &= m;
+        }
+        m as u32
+    }
+
+    /// Get the "hash" of the value (low 64 bits of the Montgomery
+    /// representation).
+    pub fn hashcode(self) -> u64 {
+        self.0[0]
+    }
+
+    /// Return a wrapper displaying this value as a decimal integer in the
+    /// [0..(p-1)] range, e.g. with `format!("{}", x.decimal())`.
+    pub fn decimal(self) -> Decimal<Self> {
+        Decimal(self)
+    }
+
+    /// Compare this value with `rhs` as integers in the [0..(p-1)] range.
+    /// This is the order used by the `Ord` implementation.
+    ///
+    /// This function is NOT constant-time, and shall be used only on public
+    /// values (e.g. to sort public j-invariants).
+    pub fn cmp_vartime(self, rhs: &Self) -> Ordering {
+        let a = self.to_canonical_limbs();
+        let b = rhs.to_canonical_limbs();
+        a.iter().rev().cmp(b.iter().rev())
+    }
+
+    /// Return the Montgomery representation x*R mod p of this value x,
+    /// with R = 2^(64*N), over `N` words in little-endian order. This is
+    /// the internal representation, as used by most C implementations
+    /// with 64-bit words (e.g. the SIKE and SQIsign reference code).
+    pub fn to_montgomery_limbs(self) -> [u64; N] {
+        self.0
+    }
+
+    /// Build an element from its Montgomery representation x*R mod p (see
+    /// `to_montgomery_limbs()`). Returned values are the element and
+    /// `0xFFFFFFFF` on success, or the zero element and `0x00000000` if the
+    /// words are not less than p.
+    pub fn from_montgomery_limbs(limbs: &[u64; N]) -> (Self, u32) {
+        let mut r = Self::new(*limbs);
+        let m = r.set_clear_unreduced();
+        (r, m)
+    }
+
+    /// Return the unique representant of this value in the [0..(p-1)]
+    /// range, over `N` words in little-endian order.
+    pub fn to_canonical_limbs(self) -> [u64; N] {
+        let mut r = self;
+        r.set_montyred();
+        r.0
+    }
+
+    /// Build an element from an integer in the [0..(p-1)] range, over `N`
+    /// words in little-endian order. Returned values are the element and
+    /// `0xFFFFFFFF` on success, or the zero element and `0x00000000` if the
+    /// integer is not less than p.
+    pub fn from_canonical_limbs(limbs: &[u64; N]) -> (Self, u32) {
+        let mut r = Self::new(*limbs);
+        let m = r.set_clear_unreduced();
+        r.set_mul(&Self::R2);
+        (r, m)
+    }
+
+    /// Implements Algorithm 2 from Patrick Longa's
+    /// [ePrint 2022-367](https://eprint.iacr.org/2022/367) §3.
+    /// Computes a1 * b1 + a2 * b2 using an optimised method intended
+    /// for use in Fp2 multiplications
+    #[inline(always)]
+    pub fn sum_of_products(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        if Self::FIAT {
+            return *a1 * *b1 + *a2 * *b2;
+        }
+        if Self::W32 {
+            return Self::sum_of_products_w32(a1, b1, a2, b2);
+        }
+
+        // Line 1: u <- 0
+        let mut u = Self::ZERO;
+
+        let mut cc1: u64;
+        let mut cc2: u64;
+        let mut cc3: u64;
+
+        let mut cch: u8 = 0;
+        let mut cch1: u8;
+        let mut cch2: u8;
+
+        // Line 2: for j = 0 to N - 1
+        for j in 0..Self::N {
+            // Line 3: u <- u + a0,j * b0 + a1,j * b1
+
+            // We can always compute a * b + c + d = lo + 2^64 * hi
+            // This is what we do in the inner loop to compute
+            //     u <- u + a0,j * b0
+            (u.0[0], cc1) = crate::utils64::umull_add(a1.0[j], b1.0[0], u.0[0]);
+            for k in 1..Self::N {
+                (u.0[k], cc1) = crate::utils64::umull_add2(a1.0[j], b1.0[k], u.0[k], cc1);
+            }
+            //     u <- u + a1,j * b1
+            (u.0[0], cc2) = crate::utils64::umull_add(a2.0[j], b2.0[0], u.0[0]);
+            for k in 1..Self::N {
+                (u.0[k], cc2) = crate::utils64::umull_add2(a2.0[j], b2.0[k], u.0[k], cc2);
+            }
+
+            // Line 4: q <- u * p' mod 2^64
+            let q = u.0[0].wrapping_mul(Self::P0I);
+
+            // Line 5: u <- (u + q * p') / 2^64
+            (_, cc3) = crate::utils64::umull_add(q, Self::MODULUS[0], u.0[0]);
+            for k in 1..Self::N {
+                (u.0[k - 1], cc3) = crate::utils64::umull_add2(q, Self::MODULUS[k], u.0[k], cc3);
+            }
+
+            // We now have to handle all the carries, which means adding cc1, cc2 and cc3 as well
+            // as the carry cch from the last iteration
+            (u.0[Self::N - 1], cch1) = crate::utils64::addcarry_u64(cc1, cc2, cch);
+            (u.0[Self::N - 1], cch2) = crate::utils64::addcarry_u64(u.0[Self::N - 1], cc3, 0);
+            cch = cch1 + cch2;
+        }
+
+        // From the paper we have something in the range [0, 2p) if 2*(p - 1)^2 < p*R
+        // Which means we need to subtract p and then conditionally add p back
+        let mut borrow: u8 = 0;
+        for i in 0..Self::N {
+            (u.0[i], borrow) = crate::utils64::subborrow_u64(u.0[i], Self::MODULUS[i], borrow);
+        }
+        let mask = (cch as u64).wrapping_sub(borrow as u64);
+        let mut cc = 0;
+        for i in 0..Self::N {
+            (u.0[i], cc) = crate::utils64::addcarry_u64(u.0[i], mask & Self::MODULUS[i], cc);
+        }
+
+        // We are only guarenteed that the above result is in [0, 2p) when we have the
+        // condition: 2*(p - 1)^2 < p*R which is true most of the time, but not when
+        // p is close to R. When the user's modulus is too close to 2^(64 * N) then we
+        // may need an additional conditional subtraction to get a result within the canonical
+        // range.
+        if Self::SUM_OF_PRODUCTS_ADDITIONAL_SUB {
+            borrow = 0;
+            for i in 0..Self::N {
+                (u.0[i], borrow) = crate::utils64::subborrow_u64(u.0[i], Self::MODULUS[i], borrow);
+            }
+            let mask = (borrow as u64).wrapping_neg();
+            cc = 0;
+            for i in 0..Self::N {
+                (u.0[i], cc) = crate::utils64::addcarry_u64(u.0[i], mask & Self::MODULUS[i], cc);
+            }
+        }
+
+        u
+    }
+
+    /// Implements Algorithm 2 from Patrick Longa's
+    /// [ePrint 2022-367](https://eprint.iacr.org/2022/367) §3.
+    /// Computes a1 * b1 - a2 * b2 using an optimised method intended
+    /// for use in Fp2 multiplications
+    #[inline(always)]
+    pub fn difference_of_products(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        // Compute p - b2
+        let mut borrow = 0;
+        let mut b2_minus = *b2;
+        for i in 0..Self::N {
+            (b2_minus.0[i], borrow) =
+                crate::utils64::subborrow_u64(Self::MODULUS[i], b2_minus.0[i], borrow);
+        }
+        // Regular sum of products (could the above be optimised into the main loop?)
+        Self::sum_of_products(a1, b1, a2, &b2_minus)
+    }
+
+    /*
+     * Support functions which compute constants at compile time to
+     * generate macro constants, this allows a user to create the field
+     * with only the modulus as input.
+     *
+     * These have been adapted from https://github.com/pornin/crrl/src/backend/w64/gfgen.rs
+     */
+    // Return -1/x mod 2^64. It is assumed that x is odd.
+    const fn ninv64(x: u64) -> u64 {
+        let y = 2u64.wrapping_sub(x);
+        let y = y.wrapping_mul(2u64.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u64.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u64.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u64.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u64.wrapping_sub(y.wrapping_mul(x)));
+        y.wrapping_neg()
+    }
+
+    // Custom add-with-carry, for use in const (compile-time) contexts.
+    const fn adc(x: u64, y: u64, cc: u64) -> (u64, u64) {
+        let z = (x as u128) + (y as u128) + (cc as u128);
+        (z as u64, (z >> 64) as u64)
+    }
+
+    // Custom sub-with-borrow, for use in const (compile-time) contexts.
+    const fn sbb(x: u64, y: u64, cc: u64) -> (u64, u64) {
+        let z = (x as u128).wrapping_sub(y as u128).wrapping_sub(cc as u128);
+        (z as u64, ((z >> 64) as u64).wrapping_neg())
+    }
+
+    // Add the modulus, return borrow (compile-time).
+    const fn addm(a: Self) -> (Self, u64) {
+        let mut r = a;
+        let mut cc = 0u64;
+        let mut i = 0;
+        while i < Self::N {
+            let (d, c) = Self::adc(r.0[i], Self::MODULUS[i], cc);
+            r.0[i] = d;
+            cc = c;
+            i += 1;
+        }
+        (r, cc)
+    }
+
+    // Subtract the modulus, return borrow (compile-time).
+    const fn subm(a: Self) -> (Self, u64) {
+        let mut r = a;
+        let mut cc = 0u64;
+        let mut i = 0;
+        while i < Self::N {
+            let (d, c) = Self::sbb(r.0[i], Self::MODULUS[i], cc);
+            r.0[i] = d;
+            cc = c;
+            i += 1;
+        }
+        (r, cc)
+    }
+
+    // For the result of sum of products to work, we need 2*p - 4 to fit into
+    // N words, otherwise we need to do an additional subtraction. This function
+    // computes 2*p - 4 and if an overflow is detected, then a boolean is set
+    // at compile time to ensure an addtional conditional subtraction is performed.
+    const fn sub4_inner(a: Self, cc: u64, i: usize) -> (Self, u64) {
+        if i == a.0.len() {
+            (a, cc)
+        } else {
+            let n = if i == 0 { 4 } else { 0 };
+            let (d, cc) = Self::sbb(a.0[i], n, cc);
+            let mut aa = a;
+            aa.0[i] = d;
+            Self::sub4_inner(aa, cc, i + 1)
+        }
+    }
+
+    const fn sum_of_products_check() -> bool {
+        // Subtract 4 from the modulus, we ignore the borrow here
+        // as it would only be an issue for p = 2, 3 and we assume
+        // p is large.
+        let (r1, _) = Self::sub4_inner(Self::new(Self::MODULUS), 0, 0);
+
+        // Add the modulus to compute 2*p - 4
+        let (_, b1) = Self::addm(r1);
+
+        // If the addition of the modulus has a carry, then return true
+        b1 == 1
+    }
+
+    // Add the modulus if mm == -1; return a unchanged with mm == 0
+    // (compile-time).
+    const fn addm_cond(a: Self, mm: u64) -> Self {
+        Self::addm_cond_carry(a, mm).0
+    }
+
+    // Add the modulus if mm == -1, also returning the carry (compile-time).
+    const fn addm_cond_carry(a: Self, mm: u64) -> (Self, u64) {
+        let mut r = a;
+        let mut cc = 0u64;
+        let mut i = 0;
+        while i < Self::N {
+            let (d, c) = Self::adc(r.0[i], Self::MODULUS[i] & mm, cc);
+            r.0[i] = d;
+            cc = c;
+            i += 1;
+        }
+        (r, cc)
+    }
+
+    // Check at compile time that the modulus spans at least two words with a
+    // non-zero top word and, unless disabled, that it is a prime p = 3 mod 4
+    // (primality only up to utils64::PRIMALITY_CHECK_MAX_BITS bits).
+    // The encoding types must also have the expected lengths.
+    const fn check_params() {
+        assert!(N >= 2, "the modulus must be larger than 2^64");
+        assert!(
+            P::MODULUS[N - 1] != 0,
+            "the top word of the modulus must be non-zero"
+        );
+        if P::CHECK_MODULUS {
+            // This also ensures that p is odd, as required for the Montgomery
+            // arithmetic used above.
+            assert!(
+                P::MODULUS[0] & 3 == 3,
+                "the modulus must be equal to 3 mod 4"
+            );
+            // Moduli above utils64::PRIMALITY_CHECK_MAX_BITS are too slow
+            // to test (None), and only get the structural checks.
+            if let Some(b) = P::MODULUS_IS_PRIME {
+                assert!(b, "the modulus is not prime");
+            }
+        }
+        assert!(
+            P::Encoding::LEN == Self::ENCODED_LENGTH,
+            "the encoding type must be [u8; ENCODED_LENGTH]"
+        );
+        assert!(
+            P::Fp2Encoding::LEN == 2 * Self::ENCODED_LENGTH,
+            "the GF(p^2) encoding type must be [u8; 2 * ENCODED_LENGTH]"
+        );
+    }
+
+    // Compute the modulus exact bit length (compile-time).
+    const fn mod_bitlen() -> usize {
+        const fn bitlen(x: u64, max: usize) -> usize {
+            if max == 1 {
+                x as usize
+            } else {
+                let hm = max >> 1;
+                let y = x >> hm;
+                if y == 0 {
+                    bitlen(x, hm)
+                } else {
+                    bitlen(y, max - hm) + hm
+                }
+            }
+        }
+        (Self::N - 1) * 64 + bitlen(P::MODULUS[Self::N - 1], 64)
+    }
+
+    // Get the top 32 bits of the actual modulus value (if the modulus
+    // is less than 32 bits in length, then this returns the modulus).
+    const fn top_u32() -> u64 {
+        if Self::BIT_LENGTH < 32 {
+            Self::MODULUS[0]
+        } else {
+            let hi = Self::MODULUS[Self::N - 1];
+            let bl = Self::BIT_LENGTH & 63;
+            if bl == 0 {
+                hi >> 32
+            } else if bl < 32 {
+                let lo = Self::MODULUS[Self::N - 2];
+                (hi << (32 - bl)) | (lo >> (bl + 32))
+            } else {
+                hi >> (bl - 32)
+            }
+        }
+    }
+
+    // Const implementation of modular negation. This MUST NOT be
+    // applied on zero.
+    const fn const_neg(a: Self) -> Self {
+        let mut r = Self::new([0u64; N]);
+        let mut cc = 0u64;
+        let mut j = 0;
+        while j < Self::N {
+            let (x, c) = Self::sbb(Self::MODULUS[j], a.0[j], cc);
+            r.0[j] = x;
+            cc = c;
+            j += 1;
+        }
+        r
+    }
+
+    // Const implementation of Montgomery multiplication. It uses
+    // recursion in order to be compatible with the constraints of
+    // const code; at runtime, it would be slower than the normal
+    // implementation, but still constant-time (in case it gets
+    // mistakenly used).
+    const fn const_mmul(a: Self, b: Self) -> Self {
+        // 128-bit fused multiply-accumulate: x*y + acc + carry → (lo, hi).
+        const fn umaal(x: u64, y: u64, acc: u64, carry: u64) -> (u64, u64) {
+            let z = (x as u128) * (y as u128) + (acc as u128) + (carry as u128);
+            (z as u64, (z >> 64) as u64)
+        }
+
+        let mut d = Self::new([0u64; N]);
+        let mut dh: u64 = 0;
+
+        // Outer loop — one Montgomery reduction step per limb of b.
+        let mut j = 0;
+        while j < Self::N {
+            let bj = b.0[j];
+            // Montgomery factor for this step.
+            let fm = a.0[0]
+                .wrapping_mul(bj)
+                .wrapping_add(d.0[0])
+                .wrapping_mul(Self::P0I);
+
+            let mut cc1 = 0u64; // carry from a[i]*bj column
+            let mut cc2 = 0u64; // carry from MODULUS[i]*fm column
+
+            // Inner loop — accumulate a[i]*bj and reduce with fm*MODULUS[i].
+            // The word at i=0 is consumed by the reduction and not stored;
+            // for i>0 each result shifts into d[i-1].
+            let mut i = 0;
+            while i < Self::N {
+                let (z, c1) = umaal(a.0[i], bj, d.0[i], cc1);
+                let (z, c2) = umaal(Self::MODULUS[i], fm, z, cc2);
+                if i > 0 {
+                    d.0[i - 1] = z;
+                }
+                cc1 = c1;
+                cc2 = c2;
+                i += 1;
+            }
+
+            // Fold the two high-word carries into d[N-1] and dh.
+            let (z, zh1) = Self::adc(dh, cc1, 0);
+            let (z, zh2) = Self::adc(z, cc2, 0);
+            d.0[Self::N - 1] = z;
+            dh = zh1 + zh2;
+
+            j += 1;
+        }
+
+        // Final conditional subtraction (identical logic to original).
+        let (d2, cc) = Self::subm(d);
+        Self::addm_cond(d2, (cc & !dh).wrapping_neg())
+    }
+
+    const fn const_small(x: u64) -> Self {
+        let mut d = [0u64; N];
+        d[0] = x;
+        Self::const_mmul(Self::new(d), Self::R2)
+    }
+
+    /// Compute the constants of `Precomputed` from the modulus.
+    ///
+    /// This is slow for very large moduli: evaluating it in a constant of
+    /// the parameters type, e.g. as
+    /// `const PRECOMPUTED: Option<Precomputed<N>> = Some(Fp::<Self, N>::precompute());`,
+    /// ensures that it is computed only once, and allows silencing the
+    /// `long_running_const_eval` lint on that implementation.
+    pub const fn precompute() -> Precomputed<N> {
+        Precomputed::new(&P::MODULUS)
+    }
+
+    /// Check that the precomputed constants of the parameters, if any,
+    /// match the modulus (panics otherwise). The 32-bit backend does not use
+    /// `tfixdiv`, which is then not checked. `define_fp_core` calls this at
+    /// compile time in debug builds when the constants are given.
+    pub const fn check_precomputed() {
+        let Some(pc) = P::PRECOMPUTED else {
+            return;
+        };
+        let cc = Self::precompute();
+        assert!(
+            const_eq(&pc.r, &cc.r),
+            "precomputed constant r does not match the modulus"
+        );
+        assert!(
+            const_eq(&pc.r2, &cc.r2),
+            "precomputed constant r2 does not match the modulus"
+        );
+        assert!(
+            const_eq(&pc.tdec, &cc.tdec),
+            "precomputed constant tdec does not match the modulus"
+        );
+        assert!(
+            Self::W32 || const_eq(&pc.tfixdiv, &cc.tfixdiv),
+            "precomputed constant tfixdiv does not match the modulus"
+        );
+        assert!(
+            const_eq(&pc.sqrt_exp, &cc.sqrt_exp),
+            "precomputed constant sqrt_exp does not match the modulus"
+        );
+    }
+
+    /// Decode an element from bytes, no check is made that the input
+    /// value is reduced except that the buffer is of the excpected
+    /// length of `Self::ENCODED_LENGTH`.
+    pub const fn const_decode_no_check(buf: &[u8]) -> Self {
+        let mut r = Self::ZERO;
+        if buf.len() != Self::ENCODED_LENGTH {
+            return r;
+        }
+
+        // Fill the first N-1 elements
+        let mut i = 0;
+        while i < Self::N - 1 {
+            r.0[i] = u64::from_le_bytes([
+                buf[i * 8],
+                buf[i * 8 + 1],
+                buf[i * 8 + 2],
+                buf[i * 8 + 3],
+                buf[i * 8 + 4],
+                buf[i * 8 + 5],
+                buf[i * 8 + 6],
+                buf[i * 8 + 7],
+            ]);
+            i += 1;
+        }
+
+        // Fill the last element
+        let mut w = 0u64;
+        let mut j = 0;
+        while j < Self::ENCODED_LENGTH - (Self::N - 1) * 8 {
+            w |= (buf[(Self::N - 1) * 8 + j] as u64) << (8 * j);
+            j += 1;
+        }
+        r.0[Self::N - 1] = w;
+
+        Self::const_mmul(r, Self::R2)
+    }
+
+    /// Return the element `x`, for use in const contexts (`x` is always
+    /// less than the modulus, which is larger than 2^64).
+    pub const fn const_from_u64(x: u64) -> Self {
+        Self::const_small(x)
+    }
+
+    /// Return `self + rhs`, for use in const contexts.
+    pub const fn const_add(self, rhs: Self) -> Self {
+        let mut r = self;
+        let mut cc = 0u64;
+        let mut i = 0;
+        while i < Self::N {
+            let (d, c) = Self::adc(r.0[i], rhs.0[i], cc);
+            r.0[i] = d;
+            cc = c;
+            i += 1;
+        }
+
+        // Subtract the modulus, and add it back if the sum was lower.
+        let (r2, bb) = Self::subm(r);
+        Self::addm_cond(r2, (bb & (cc ^ 1)).wrapping_neg())
+    }
+
+    /// Return `self - rhs`, for use in const contexts.
+    pub const fn const_sub(self, rhs: Self) -> Self {
+        let mut r = self;
+        let mut cc = 0u64;
+        let mut i = 0;
+        while i < Self::N {
+            let (d, c) = Self::sbb(r.0[i], rhs.0[i], cc);
+            r.0[i] = d;
+            cc = c;
+            i += 1;
+        }
+        Self::addm_cond(r, cc.wrapping_neg())
+    }
+
+    /// Return `self / 2`, for use in const contexts.
+    pub const fn const_half(self) -> Self {
+        let (mut r, cc) = Self::addm_cond_carry(self, (self.0[0] & 1).wrapping_neg());
+        let mut i = 0;
+        while i < Self::N - 1 {
+            r.0[i] = (r.0[i] >> 1) | (r.0[i + 1] << 63);
+            i += 1;
+        }
+        r.0[Self::N - 1] = (r.0[Self::N - 1] >> 1) | (cc << 63);
+        r
+    }
+
+    /// Return `self * rhs`, for use in const contexts.
+    pub const fn const_mul(self, rhs: Self) -> Self {
+        Self::const_mmul(self, rhs)
+    }
+
+    /// Return `self` raised to the power `e` (in little-endian 64-bit
+    /// limbs), for use in const contexts. This is a plain square-and-multiply
+    /// and is slow for very large moduli.
+    pub const fn const_pow(self, e: &[u64]) -> Self {
+        let mut r = Self::ONE;
+        let mut i = e.len() * 64;
+        while i > 0 {
+            i -= 1;
+            r = Self::const_mmul(r, r);
+            if (e[i >> 6] >> (i & 63)) & 1 != 0 {
+                r = Self::const_mmul(r, self);
+            }
+        }
+        r
+    }
+
+    /// Return `true` if `self` is equal to `rhs`, for use in const contexts.
+    pub const fn const_equals(self, rhs: &Self) -> bool {
+        const_eq(&self.0, &rhs.0)
+    }
+}
+
+/*
+ * Implementations of from methods from simple integer types
+ */
+
+impl<P: FieldParams<N>, const N: usize> From<u64> for Fp<P, N> {
+    fn from(x: u64) -> Fp<P, N> {
+        let mut r = Self::ZERO;
+        r.0[0] = x;
+        r.set_mul(&Self::R2);
+        r
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> Fp<P, N> {
+    const SQRT_EXP: [u64; N] = Self::CONSTANTS.sqrt_exp;
+    const FOURTH_ROOT_EXP: [u64; N] = Self::const_fourth_root_exp();
+    const PROGENITOR_EXP: [u64; N] = crate::addchain::progenitor_exponent(&P::MODULUS);
+
+    // The chains of the parameters, for the exponents above, which are
+    // shorter than set_pow_pubexp() (None otherwise).
+    const SQRT_CHAIN: Option<&'static [ChainOp]> = match P::CHAINS {
+        Some(c) => crate::addchain::select(c.sqrt, &Self::SQRT_EXP),
+        None => None,
+    };
+    const FOURTH_ROOT_CHAIN: Option<&'static [ChainOp]> = match P::CHAINS {
+        Some(c) => crate::addchain::select(c.fourth_root, &Self::FOURTH_ROOT_EXP),
+        None => None,
+    };
+    const PROGENITOR_CHAIN: Option<&'static [ChainOp]> = match P::CHAINS {
+        Some(c) => crate::addchain::select(c.progenitor, &Self::PROGENITOR_EXP),
+        None => None,
+    };
+
+    /// Compute the square of this value.
+    #[inline(always)]
+    pub fn square(self) -> Self {
+        let mut r = self;
+        r.set_square();
+        r
+    }
+
+    /// Square this value n times in place
+    #[inline(always)]
+    pub fn set_n_square(&mut self, n: u32) {
+        for _ in 0..n {
+            self.set_square();
+        }
+    }
+
+    /// Square this value n times
+    #[inline(always)]
+    pub fn n_square(self, n: u32) -> Self {
+        let mut r = self;
+        r.set_n_square(n);
+        r
+    }
+
+    /// Compute the half of this value.
+    #[inline(always)]
+    pub fn half(self) -> Self {
+        let mut r = self;
+        r.set_half();
+        r
+    }
+
+    /// Compute the sum of this value with itself.
+    #[inline(always)]
+    pub fn mul2(self) -> Self {
+        let mut r = self;
+        r.set_mul2();
+        r
+    }
+
+    /// Triple this value.
+    #[inline]
+    pub fn set_mul3(&mut self) {
+        let r = self.mul2();
+        *self += &r;
+    }
+
+    /// Compute the triple of this value.
+    #[inline(always)]
+    pub fn mul3(self) -> Self {
+        let mut r = self;
+        r.set_mul3();
+        r
+    }
+
+    /// Quadruple this value.
+    #[inline]
+    pub fn set_mul4(&mut self) {
+        self.set_mul2();
+        self.set_mul2();
+    }
+
+    /// Compute the quadruple of this value.
+    #[inline(always)]
+    pub fn mul4(self) -> Self {
+        let mut r = self;
+        r.set_mul4();
+        r
+    }
+
+    /// Multiply this value by 8
+    #[inline]
+    pub fn set_mul8(&mut self) {
+        self.set_mul2();
+        self.set_mul2();
+        self.set_mul2();
+    }
+
+    /// Compute 8 times this value.
+    #[inline(always)]
+    pub fn mul8(self) -> Self {
+        let mut r = self;
+        r.set_mul8();
+        r
+    }
+
+    /// Compute the product of this value by a small (unsigned) integer k.
+    #[inline(always)]
+    pub fn mul_small(self, k: i32) -> Self {
+        let mut r = self;
+        r.set_mul_small(k);
+        r
+    }
+
+    /// Return a or b, if ctl is 0x00000000 or 0xFFFFFFFF, respectively.
+    /// ctl MUST be either 0x00000000 or 0xFFFFFFFF.
+    /// The value of ctl MUST be either 0x00000000 or 0xFFFFFFFF.
+    #[inline]
+    pub fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+        let mut r = Self::ZERO;
+        r.set_select(a, b, ctl);
+        r
+    }
+
+    /// Negate this value if ctl is 0xFFFFFFFF; leave it unchanged if
+    /// ctl is 0x00000000.
+    /// The value of ctl MUST be either 0x00000000 or 0xFFFFFFFF.
+    #[inline]
+    pub fn set_cond_neg(&mut self, ctl: u32) {
+        let v = -(self as &Self);
+        self.set_cond(&v, ctl);
+    }
+
+    pub fn set_invert(&mut self) {
+        let r = *self;
+        *self = Self::ONE;
+        self.set_div(&r);
+    }
+
+    pub fn invert(self) -> Self {
+        let mut r = Self::ONE;
+        r.set_div(&self);
+        r
+    }
+
+    /// Set this value to its square root. Returned value is 0xFFFFFFFF if
+    /// the operation succeeded (value was indeed a quadratic residue), or
+    /// 0x00000000 otherwise. On success, the chosen root is the one whose
+    /// least significant bit (as an integer in [0..p-1]) is zero. On
+    /// failure, this value is set to 0.
+    pub fn set_sqrt(&mut self) -> u32 {
+        // Compute x^((p+1)/4)
+        let x = *self;
+        match Self::SQRT_CHAIN {
+            Some(ops) => self.set_pow_chain(ops),
+            None => self.set_pow_pubexp(&Self::SQRT_EXP),
+        }
+
+        // Check whether the square of the result equals the input and zeroize
+        // on failure
+        let r = self.square().equals(&x);
+        self.set_cond(&Self::ZERO, !r);
+
+        // Normalise the output so that the LSB is zero
+        let ctl = self.is_odd();
+        self.set_cond_neg(ctl);
+
+        r
+    }
+
+    /// Compute the square root of this value. If this value is indeed a
+    /// quadratic residue, then this returns (x, 0xFFFFFFFF), with x being
+    /// the (unique) square root of this value whose least significant bit
+    /// is zero (when normalized to an integer in [0..p-1]). If this value
+    /// is not a quadratic residue, then this returns (zero, 0x00000000).
+    pub fn sqrt(self) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_sqrt();
+        (x, r)
+    }
+
+    /// Set this value to its square root. Returned value is 0xFFFFFFFF if
+    /// the operation succeeded (value was indeed a quadratic residue), or
+    /// 0x00000000 otherwise. On success, the chosen root is the one whose
+    /// least significant bit (as an integer in [0..p-1]) is zero. On
+    /// failure, this value is set to 0.
+    ///
+    /// When p = 7 mod 8 we can compute x^((p+1)/8), but for all other cases
+    /// we fall back to the 2x slower method of computing x^((p+1)/4) twice.
+    pub fn set_fourth_root(&mut self) -> u32 {
+        let x = *self;
+
+        if Self::MODULUS[0] & 7 == 7 {
+            // Compute x^((p+1)/8)
+            match Self::FOURTH_ROOT_CHAIN {
+                Some(ops) => self.set_pow_chain(ops),
+                None => self.set_pow_pubexp(&Self::FOURTH_ROOT_EXP),
+            }
+        } else {
+            // Fall back to the much slower, general case of two sqrt.
+            for _ in 0..2 {
+                match Self::SQRT_CHAIN {
+                    Some(ops) => self.set_pow_chain(ops),
+                    None => self.set_pow_pubexp(&Self::SQRT_EXP),
+                }
+            }
+        }
+
+        // Check whether the square of the result equals the input and zeroize
+        // on failure
+        let r = self.n_square(2).equals(&x);
+        self.set_cond(&Self::ZERO, !r);
+
+        // Normalise the output so that the LSB is zero
+        let ctl = self.is_odd();
+        self.set_cond_neg(ctl);
+
+        r
+    }
+
+    /// Set this value to its progenitor x^((p-3)/4). For a non-zero x with
+    /// progenitor z, x*z^2 is the Legendre symbol of x (as 1 or -1) and x*z
+    /// is a square root of either x or -x, so that square roots, inverse
+    /// square roots and quadratic residuosity all follow from a single
+    /// exponentiation.
+    pub fn set_progenitor(&mut self) {
+        match Self::PROGENITOR_CHAIN {
+            Some(ops) => self.set_pow_chain(ops),
+            None => self.set_pow_pubexp(&Self::PROGENITOR_EXP),
+        }
+    }
+
+    /// Return the progenitor x^((p-3)/4) of this value, see
+    /// `set_progenitor()`.
+    pub fn progenitor(self) -> Self {
+        let mut r = self;
+        r.set_progenitor();
+        r
+    }
+
+    /// Compute a square root of `u/v`, as `sqrt_ratio` in RFC 9380 with
+    /// Z = -1 (a non-square, since p = 3 mod 4). If `v` is non-zero and
+    /// `u/v` is a square, then this returns `(y, 0xFFFFFFFF)` with
+    /// `y^2 = u/v`; otherwise, this returns `(y, 0x00000000)` with
+    /// `y^2 = -u/v` (and `y = 0` if `v = 0`). In both cases, `y` is the root
+    /// whose least significant bit is zero. This uses a single
+    /// exponentiation and no inversion.
+    ///
+    /// For another non-square Z, the fallback value is obtained by
+    /// multiplying `y` by a square root of -Z.
+    pub fn sqrt_ratio(u: &Self, v: &Self) -> (Self, u32) {
+        // y = u*v*(u*v^3)^((p-3)/4). If u/v is a square, then y^2 = u/v,
+        // otherwise y^2 = -u/v (see set_progenitor()).
+        let uv = *u * *v;
+        let mut y = uv * (uv * v.square()).progenitor();
+        let r = (y.square() * *v).equals(u) & !v.is_zero();
+
+        // Normalise the output so that the LSB is zero
+        let ctl = y.is_odd();
+        y.set_cond_neg(ctl);
+
+        (y, r)
+    }
+
+    /// Set this value to its inverse square root. Returned value is
+    /// 0xFFFFFFFF if the operation succeeded (value was a non-zero
+    /// quadratic residue), or 0x00000000 otherwise. On success, the chosen
+    /// root is the one whose least significant bit (as an integer in
+    /// [0..p-1]) is zero. On failure, this value is set to 0.
+    pub fn set_invsqrt(&mut self) -> u32 {
+        // For a square x, the progenitor z satisfies x*z^2 = 1.
+        let x = *self;
+        self.set_progenitor();
+        let r = (x * self.square()).equals(&Self::ONE);
+        self.set_cond(&Self::ZERO, !r);
+
+        // Normalise the output so that the LSB is zero
+        let ctl = self.is_odd();
+        self.set_cond_neg(ctl);
+
+        r
+    }
+
+    /// Compute the inverse square root of this value. If this value is a
+    /// non-zero quadratic residue, then this returns (y, 0xFFFFFFFF), with
+    /// y being the square root of 1/x whose least significant bit is zero
+    /// (when normalized to an integer in [0..p-1]). Otherwise, this returns
+    /// (zero, 0x00000000).
+    pub fn invsqrt(self) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_invsqrt();
+        (x, r)
+    }
+
+    /// Return 0xFFFFFFFF if this value, as an integer in [0..p-1], is odd,
+    /// or 0x00000000 otherwise.
+    pub fn is_odd(self) -> u32 {
+        ((self.encode().as_ref()[0] as u32) & 1).wrapping_neg()
+    }
+
+    /// Return 0xFFFFFFFF if the sign of this value is 1 for `sgn0` of
+    /// RFC 9380, i.e. if it is odd, or 0x00000000 otherwise.
+    pub fn sgn0(self) -> u32 {
+        self.is_odd()
+    }
+
+    /// Return 0xFFFFFFFF if this value, as an integer in [0..p-1], is
+    /// larger than (p-1)/2, or 0x00000000 otherwise.
+    pub fn is_lexicographically_largest(self) -> u32 {
+        // 2*x < p if x <= (p-1)/2; otherwise, 2*x - p is odd.
+        self.mul2().is_odd()
+    }
+
+    /// Return 0xFFFFFFFF if the sign of this value is 1 for the convention
+    /// `sc`, or 0x00000000 otherwise.
+    pub fn sign(self, sc: SignConvention) -> u32 {
+        match sc {
+            SignConvention::Sgn0 => self.sgn0(),
+            SignConvention::Positive => self.is_lexicographically_largest(),
+        }
+    }
+
+    /// Negate this value if its sign is 1 for the convention `sc`, so that
+    /// it becomes the root of its square chosen by `sc`.
+    pub fn set_canonical_sign(&mut self, sc: SignConvention) {
+        let ctl = self.sign(sc);
+        self.set_cond_neg(ctl);
+    }
+
+    /// Set this value to its square root, as `set_sqrt()`, but choosing the
+    /// root with the sign convention `sc`.
+    pub fn set_sqrt_signed(&mut self, sc: SignConvention) -> u32 {
+        let r = self.set_sqrt();
+        self.set_canonical_sign(sc);
+        r
+    }
+
+    /// Compute the square root of this value, as `sqrt()`, but choosing the
+    /// root with the sign convention `sc`.
+    pub fn sqrt_signed(self, sc: SignConvention) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_sqrt_signed(sc);
+        (x, r)
+    }
+
+    /// Set this value to its fourth root, as `set_fourth_root()`, but
+    /// choosing the root with the sign convention `sc`.
+    pub fn set_fourth_root_signed(&mut self, sc: SignConvention) -> u32 {
+        let r = self.set_fourth_root();
+        self.set_canonical_sign(sc);
+        r
+    }
+
+    /// Compute the fourth root of this value, as `fourth_root()`, but
+    /// choosing the root with the sign convention `sc`.
+    pub fn fourth_root_signed(self, sc: SignConvention) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_fourth_root_signed(sc);
+        (x, r)
+    }
+
+    /// Compute the fourth root of this value. If this value is indeed some
+    /// element to the power of four, then this returns (x, 0xFFFFFFFF), with x being
+    /// the (unique) fourth root of this value whose least significant bit
+    /// is zero (when normalized to an integer in [0..p-1]). If this value
+    /// is not some element to the power of four, then this returns (zero, 0x00000000).
+    pub fn fourth_root(self) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_fourth_root();
+        (x, r)
+    }
+
+    /// Raise this value to the provided exponent. The exponent is non-zero
+    /// and is public. The exponent is encoded over N 64-bit limbs.
+    pub fn set_pow_pubexp(&mut self, e: &[u64; N]) {
+        // Make a 4-bit window; win[i] contains x^(i+1)
+        let mut win = [Self::ZERO; 15];
+        win[0] = *self;
+        for i in 1..8 {
+            let j = i * 2;
+            win[j - 1] = win[i - 1].square();
+            win[j] = win[j - 1] * win[0];
+        }
+
+        // Explore 4-bit chunks of the exponent, high to low. Skip leading
+        // chunks of value 0.
+        let mut z = false;
+        for i in (0..Self::N).rev() {
+            let ew = e[i];
+            for j in (0..16).rev() {
+                if z {
+                    self.set_n_square(4);
+                }
+                let c = ((ew >> (j << 2)) & 0x0F) as usize;
+                if c != 0 {
+                    if z {
+                        self.set_mul(&win[c - 1]);
+                    } else {
+                        z = true;
+                        *self = win[c - 1];
+                    }
+                }
+            }
+        }
+        if !z {
+            *self = Self::ONE;
+        }
+    }
+
+    /// Return this value to the provided exponent. The exponent is non-zero
+    /// and is public. The exponent is encoded over N 64-bit limbs.
+    pub fn pow_pubexp(self, e: &[u64; N]) -> Self {
+        let mut r = self;
+        r.set_pow_pubexp(e);
+        r
+    }
+
+    /// Raise this value to the power e. Exponent e is encoded in
+    /// unsigned little-endian convention over exactly ebitlen bits.
+    pub fn set_pow(&mut self, e: &[u8], ebitlen: usize) {
+        self.set_pow_ext(e, 0, ebitlen);
+    }
+
+    /// Raise this value to the power e. Exponent e is encoded in
+    /// unsigned little-endian convention, over exactly ebitlen bits,
+    /// and starting at the bit offset eoff.
+    pub fn set_pow_ext(&mut self, e: &[u8], eoff: usize, ebitlen: usize) {
+        // TODO: implement a window optimization to make fewer
+        // multiplications.
+        let x = *self;
+        *self = Self::ONE;
+        for i in (eoff..(eoff + ebitlen)).rev() {
+            let y = *self * x;
+            let ctl = (((e[i >> 3] >> (i & 7)) as u32) & 1).wrapping_neg();
+            self.set_cond(&y, ctl);
+            if i == eoff {
+                break;
+            }
+            self.set_square();
+        }
+    }
+
+    /// Return this value to the power e (as a new element). Exponent e
+    /// is encoded in unsigned little-endian convention over exactly
+    /// ebitlen bits.
+    pub fn pow(self, e: &[u8], ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow(e, ebitlen);
+        x
+    }
+
+    /// Return this value to the power e (as a new element). Exponent e
+    /// is encoded in unsigned little-endian convention over exactly
+    /// ebitlen bits, and starting at the bit offset eoff.
+    pub fn pow_ext(self, e: &[u8], eoff: usize, ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow_ext(e, eoff, ebitlen);
+        x
+    }
+
+    /// Raise this value to the power e. The exponent length (in bits)
+    /// MUST be at most ebitlen. This is constant-time for both the
+    /// base value (self) and the exponent (e); the exponent maximum
+    /// size (ebitlen) is considered non-secret.
+    pub fn set_pow_u64(&mut self, e: u64, ebitlen: usize) {
+        match ebitlen {
+            0 => {
+                *self = Self::ONE;
+            }
+            1 => {
+                self.set_cond(&Self::ONE, ((e as u32) & 1).wrapping_sub(1));
+            }
+            _ => {
+                let x = *self;
+                self.set_cond(
+                    &Self::ONE,
+                    (((e >> (ebitlen - 1)) as u32) & 1).wrapping_sub(1),
+                );
+                for i in (0..(ebitlen - 1)).rev() {
+                    self.set_square();
+                    let y = *self * x;
+                    self.set_cond(&y, (((e >> i) as u32) & 1).wrapping_neg());
+                }
+            }
+        }
+    }
+
+    /// Return this value to the power e. The exponent length (in bits)
+    /// MUST be at most ebitlen. This is constant-time for both the
+    /// base value (self) and the exponent (e); the exponent maximum
+    /// size (ebitlen) is considered non-secret.
+    pub fn pow_u64(self, e: u64, ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow_u64(e, ebitlen);
+        x
+    }
+
+    /// Raise this value to the power e. The exponent is considered
+    /// non-secret.
+    pub fn set_pow_u64_vartime(&mut self, e: u64) {
+        match e {
+            0 => {
+                *self = Self::ONE;
+            }
+            1 => {}
+            2 => {
+                self.set_square();
+            }
+            3 => {
+                *self *= self.square();
+            }
+            4 => {
+                self.set_square();
+                self.set_square();
+            }
+            _ => {
+                let xx = self.square();
+                let xw = [*self, xx, xx * *self];
+                let mut j = 63 - e.leading_zeros();
+                j &= !1u32;
+                *self = xw[((e >> j) as usize) - 1];
+                while j > 0 {
+                    j -= 2;
+                    self.set_square();
+                    self.set_square();
+                    let k = ((e >> j) as usize) & 3;
+                    if k > 0 {
+                        self.set_mul(&xw[k - 1]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return this value to the power e. The exponent is considered
+    /// non-secret.
+    pub fn pow_u64_vartime(self, e: u64) -> Self {
+        let mut x = self;
+        x.set_pow_u64_vartime(e);
+        x
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a square in GF(p^2) and
+    /// `0x00000000` otherwise.
+    #[inline]
+    pub fn is_square(self) -> u32 {
+        !((self.legendre() >> 1) as u32)
+    }
+
+    // Return the exponent (p-1)/g of the K-th power residue symbol, with
+    // g = gcd(K, p-1), and g.
+    const fn residue_exponent(k: u64) -> ([u64; N], u64) {
+        assert!(k > 0, "power residue symbols are defined for K > 0");
+        // p is odd
+        let mut e = P::MODULUS;
+        e[0] -= 1;
+        let g = utils64::gcd_u64(k, utils64::mod_small(&e, k));
+        utils64::div_small(&mut e, g);
+        (e, g)
+    }
+
+    /// Return the `K`-th power residue symbol of this value, i.e.
+    /// x^((p-1)/g) with g = gcd(K, p-1): this is a g-th root of unity,
+    /// which is 1 exactly when x is a non-zero `K`-th power, and zero when
+    /// x is zero. For `K = 2`, this is the Legendre symbol (as an element).
+    /// The exponent is computed at compile time.
+    pub fn power_residue_symbol<const K: u64>(self) -> Self {
+        let (e, g) = const { Self::residue_exponent(K) };
+        if g == 1 {
+            // All elements are K-th powers.
+            return Self::select(&Self::ONE, &Self::ZERO, self.is_zero());
+        }
+        self.pow_pubexp(&e)
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a `K`-th power in GF(p)
+    /// (zero included) and `0x00000000` otherwise, see
+    /// `power_residue_symbol()`. When gcd(K, p-1) = 2, e.g. for `K = 4`,
+    /// this is `is_square()`.
+    pub fn is_nth_power<const K: u64>(self) -> u32 {
+        let (_, g) = const { Self::residue_exponent(K) };
+        if g == 2 {
+            return self.is_square();
+        }
+        self.power_residue_symbol::<K>().equals(&Self::ONE) | self.is_zero()
+    }
+
+    /// Set this element by decoding the provided bytes. The source slice
+    /// can have arbitrary length; the bytes are interpreted with the
+    /// unsigned little-endian convention (no sign bit), and the resulting
+    /// integer is reduced modulo the field modulus p. By definition, this
+    /// function does not enforce canonicality of the source value.
+    #[inline]
+    pub fn set_decode_reduce(&mut self, buf: &[u8]) {
+        let mut n = buf.len();
+        if n == 0 {
+            *self = Self::ZERO;
+            return;
+        }
+
+        // At least ENCODED_LENGTH bytes.
+        let mut tmpw = [[0u8; 8]; N];
+        let tmp = tmpw.as_flattened_mut();
+        let mut nn = n % Self::CLEN;
+        if nn == 0 {
+            nn = Self::CLEN;
+        }
+        n -= nn;
+        tmp[..nn].copy_from_slice(&buf[n..]);
+        self.set_decode_nocheck(tmp);
+
+        while n > 0 {
+            n -= Self::CLEN;
+            tmp[..Self::CLEN].copy_from_slice(&buf[n..(n + Self::CLEN)]);
+            let mut d = Self::ZERO;
+            d.set_decode_nocheck(tmp);
+            self.set_mul(&Self::TDEC);
+            self.set_add(&d);
+        }
+
+        self.set_mul(&Self::R2);
+    }
+
+    /// Decode the provided bytes into a field element. The source slice
+    /// can have arbitrary length; the bytes are interpreted with the
+    /// unsigned little-endian convention (no sign bit), and the resulting
+    /// integer is reduced modulo the field modulus p. By definition, this
+    /// function does not enforce canonicality of the source value.
+    #[inline(always)]
+    pub fn decode_reduce(buf: &[u8]) -> Self {
+        let mut x = Self::ZERO;
+        x.set_decode_reduce(buf);
+        x
+    }
+
+    /// Set this structure to a random field element (indistinguishable
+    /// from uniform generation).
+    pub fn set_rand<T: ::rand_core::CryptoRng + ::rand_core::RngCore>(&mut self, rng: &mut T) {
+        let mut tmpw = [[[0u8; 8]; N]; 2];
+        let tmp = &mut tmpw.as_flattened_mut().as_flattened_mut()[..(Self::ENCODED_LENGTH + 16)];
+        rng.fill_bytes(tmp);
+        self.set_decode_reduce(tmp);
+    }
+
+    /// Return a new random field element (indistinguishable from
+    /// uniform generation).
+    pub fn rand<T: ::rand_core::CryptoRng + ::rand_core::RngCore>(rng: &mut T) -> Self {
+        let mut x = Self::ZERO;
+        x.set_rand(rng);
+        x
+    }
+
+    pub fn batch_invert(xx: &mut [Self]) {
+        // We use Montgomery's trick:
+        //   1/u = v*(1/(u*v))
+        //   1/v = u*(1/(u*v))
+        // Applied recursively on n elements, this computes an inversion
+        // with a single inversion in the field, and 3*(n-1) multiplications.
+        // We use batches of 200 elements; larger batches only yield
+        // moderate improvements, while sticking to a fixed moderate batch
+        // size allows stack-based allocation.
+        let n = xx.len();
+        let mut i = 0;
+        while i < n {
+            let blen = if (n - i) > 200 { 200 } else { n - i };
+            let mut tt = [Self::ZERO; 200];
+            tt[0] = xx[i];
+            let zz0 = tt[0].is_zero();
+            tt[0].set_cond(&Self::ONE, zz0);
+            for j in 1..blen {
+                tt[j] = xx[i + j];
+                tt[j].set_cond(&Self::ONE, tt[j].is_zero());
+                tt[j] *= tt[j - 1];
+            }
+            let mut k = Self::ONE / tt[blen - 1];
+            for j in (1..blen).rev() {
+                let mut x = xx[i + j];
+                let zz = x.is_zero();
+                x.set_cond(&Self::ONE, zz);
+                xx[i + j].set_cond(&(k * tt[j - 1]), !zz);
+                k *= x;
+            }
+            xx[i].set_cond(&k, !zz0);
+            i += blen;
+        }
+    }
+
+    const fn const_fourth_root_exp() -> [u64; N] {
+        let mut d = [0u64; N];
+        let mut dd = 0u64;
+        let mut cc = true;
+        let mut i = 0;
+        while i < Self::N {
+            let (x, c) = Self::MODULUS[i].overflowing_add(cc as u64);
+            cc = c;
+            if i > 0 {
+                d[i - 1] = dd | (x << 61);
+            }
+            dd = x >> 3;
+            i += 1;
+        }
+        d[Self::N - 1] = dd;
+        d
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> From<i64> for Fp<P, N> {
+    fn from(x: i64) -> Fp<P, N> {
+        let sx = (x >> 63) as u64;
+        let ax = ((x as u64) ^ sx).wrapping_sub(sx);
+        let mut r = Self::from(ax);
+        r.set_cond_neg(sx as u32);
+        r
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> From<u32> for Fp<P, N> {
+    fn from(x: u32) -> Fp<P, N> {
+        Self::from(x as u64)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> From<i32> for Fp<P, N> {
+    fn from(x: i32) -> Fp<P, N> {
+        let mut r = Self::ONE;
+        r.set_mul_small(x);
+        r
+    }
+}
+
+/*
+ * Implementations of all the traits needed to use the simple operators
+ * (+, *, /...) on field element instances, with or without references.
+ * as well as a display method.
+ */
+
+/// Write an integer given in unsigned little-endian convention in base 16,
+/// without leading zeros.
+fn write_hex(x: &[u8], w: &mut dyn fmt::Write, upper: bool) -> fmt::Result {
+    let digits = if upper {
+        b"0123456789ABCDEF"
+    } else {
+        b"0123456789abcdef"
+    };
+    let mut leading = true;
+    for byte in x.iter().rev() {
+        for nibble in [byte >> 4, byte & 0x0F] {
+            leading &= nibble == 0;
+            if !leading {
+                w.write_char(digits[nibble as usize] as char)?;
+            }
+        }
+    }
+
+    // If the value was zero, we need to add a zero back
+    if leading {
+        w.write_char('0')?;
+    }
+    Ok(())
+}
+
+/// Write an integer given in unsigned little-endian convention in base 10.
+/// The integer is overwritten.
+fn write_decimal(x: &mut [u8], w: &mut dyn fmt::Write) -> fmt::Result {
+    // Divide by 10^19 < 2^64 and write the quotient first (recursively),
+    // then the remainder over 19 digits.
+    const D: u128 = 10_000_000_000_000_000_000;
+    let mut r = 0u128;
+    let mut zero = true;
+    for byte in x.iter_mut().rev() {
+        r = (r << 8) | (*byte as u128);
+        *byte = (r / D) as u8;
+        r %= D;
+        zero &= *byte == 0;
+    }
+    if zero {
+        write!(w, "{}", r)
+    } else {
+        write_decimal(x, w)?;
+        write!(w, "{:019}", r)
+    }
+}
+
+/// Counts the bytes written, to compute the padding.
+struct WriteCount(usize);
+
+impl fmt::Write for WriteCount {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Write `prefix` followed by the digits produced by `digits`, applying the
+/// width, fill, alignment and `0` flag of the formatter as for integers
+/// (`Formatter::pad_integral()`, without the need for a buffer). `digits` is
+/// called twice when a width is set.
+fn pad_digits(
+    f: &mut fmt::Formatter,
+    prefix: &str,
+    digits: impl Fn(&mut dyn fmt::Write) -> fmt::Result,
+) -> fmt::Result {
+    let pad = match f.width() {
+        Some(width) => {
+            let mut n = WriteCount(prefix.len());
+            digits(&mut n)?;
+            width.saturating_sub(n.0)
+        }
+        None => 0,
+    };
+    if f.sign_aware_zero_pad() {
+        f.write_str(prefix)?;
+        for _ in 0..pad {
+            f.write_char('0')?;
+        }
+        return digits(f);
+    }
+    let (before, after) = match f.align() {
+        Some(fmt::Alignment::Left) => (0, pad),
+        Some(fmt::Alignment::Center) => (pad / 2, pad - pad / 2),
+        _ => (pad, 0),
+    };
+    let fill = f.fill();
+    for _ in 0..before {
+        f.write_char(fill)?;
+    }
+    f.write_str(prefix)?;
+    digits(f)?;
+    for _ in 0..after {
+        f.write_char(fill)?;
+    }
+    Ok(())
+}
+
+/// Format an integer given in unsigned little-endian convention in base 16,
+/// without leading zeros, with the `0x` prefix if requested.
+pub(crate) fn fmt_hex(x: &[u8], f: &mut fmt::Formatter, upper: bool, prefix: bool) -> fmt::Result {
+    let prefix = if prefix { "0x" } else { "" };
+    pad_digits(f, prefix, |w| write_hex(x, w, upper))
+}
+
+/// Format an integer given in unsigned little-endian convention in base 10.
+pub(crate) fn fmt_decimal<E: Copy + AsMut<[u8]>>(x: E, f: &mut fmt::Formatter) -> fmt::Result {
+    pad_digits(f, "", |w| {
+        let mut x = x;
+        write_decimal(x.as_mut(), w)
+    })
+}
+
+/// A wrapper displaying a field element as decimal integers, see
+/// `Fp::decimal()` and `Fp2::decimal()`.
+#[derive(Clone, Copy, Debug)]
+pub struct Decimal<T>(pub T);
+
+// Display and the hexadecimal formats write the canonical value, honouring
+// the width, fill, alignment and `0` flag as integers do. Debug writes the
+// value as well unless the `redact-debug` feature is enabled, so that field
+// elements in derived Debug outputs do not leak secrets.
+impl<P: FieldParams<N>, const N: usize> fmt::Display for Fp<P, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_hex(self.encode().as_ref(), f, false, true)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> fmt::LowerHex for Fp<P, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_hex(self.encode().as_ref(), f, false, f.alternate())
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> fmt::UpperHex for Fp<P, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_hex(self.encode().as_ref(), f, true, f.alternate())
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> fmt::Display for Decimal<Fp<P, N>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_decimal(self.0.encode(), f)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> fmt::Debug for Fp<P, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if cfg!(feature = "redact-debug") {
+            f.write_str("Fp(<redacted>)")
+        } else {
+            write!(f, "Fp({})", self)
+        }
+    }
+}
+
+// Equality is constant-time. Hashes use the canonical encoding, so that
+// they do not depend on the internal representation.
+impl<P: FieldParams<N>, const N: usize> PartialEq for Fp<P, N> {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other) != 0
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> Eq for Fp<P, N> {}
+
+impl<P: FieldParams<N>, const N: usize> Hash for Fp<P, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.encode().as_ref());
+    }
+}
+
+// The order is NOT constant-time, see cmp_vartime().
+impl<P: FieldParams<N>, const N: usize> PartialOrd for Fp<P, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> Ord for Fp<P, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_vartime(other)
+    }
+}
+
+macro_rules! impl_fp_binop {
+    ($tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident, $set:ident) => {
+        impl<P: FieldParams<N>, const N: usize> $tr<Fp<P, N>> for Fp<P, N> {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn $f(self, other: Fp<P, N>) -> Fp<P, N> {
+                let mut r = self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr<&Fp<P, N>> for Fp<P, N> {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn $f(self, other: &Fp<P, N>) -> Fp<P, N> {
+                let mut r = self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr<Fp<P, N>> for &Fp<P, N> {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn $f(self, other: Fp<P, N>) -> Fp<P, N> {
+                let mut r = *self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr<&Fp<P, N>> for &Fp<P, N> {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn $f(self, other: &Fp<P, N>) -> Fp<P, N> {
+                let mut r = *self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr_assign<Fp<P, N>> for Fp<P, N> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: Fp<P, N>) {
+                self.$set(&other);
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr_assign<&Fp<P, N>> for Fp<P, N> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: &Fp<P, N>) {
+                self.$set(other);
+            }
+        }
+    };
+}
+
+impl_fp_binop!(Add, add, AddAssign, add_assign, set_add);
+impl_fp_binop!(Sub, sub, SubAssign, sub_assign, set_sub);
+impl_fp_binop!(Mul, mul, MulAssign, mul_assign, set_mul);
+impl_fp_binop!(Div, div, DivAssign, div_assign, set_div);
+
+impl<P: FieldParams<N>, const N: usize> Neg for Fp<P, N> {
+    type Output = Fp<P, N>;
+
+    #[inline(always)]
+    fn neg(self) -> Fp<P, N> {
+        let mut r = self;
+        r.set_neg();
+        r
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> Neg for &Fp<P, N> {
+    type Output = Fp<P, N>;
+
+    #[inline(always)]
+    fn neg(self) -> Fp<P, N> {
+        let mut r = *self;
+        r.set_neg();
+        r
+    }
+}
+
+// Operators with integers. Multiplications by an i32 use mul_small(); the
+// u64 operations convert the integer into a field element, so that they are
+// constant-time in the integer as well.
+impl<P: FieldParams<N>, const N: usize> Fp<P, N> {
+    #[inline(always)]
+    fn set_add_int<T>(&mut self, k: T)
+    where
+        Self: From<T>,
+    {
+        self.set_add(&Self::from(k));
+    }
+
+    #[inline(always)]
+    fn set_sub_int<T>(&mut self, k: T)
+    where
+        Self: From<T>,
+    {
+        self.set_sub(&Self::from(k));
+    }
+
+    #[inline(always)]
+    fn set_mul_u64(&mut self, k: u64) {
+        self.set_mul(&Self::from(k));
+    }
+}
+
+macro_rules! impl_fp_int_binop {
+    ($t:ty, $tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident, $set:ident) => {
+        impl<P: FieldParams<N>, const N: usize> $tr<$t> for Fp<P, N> {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn $f(self, k: $t) -> Fp<P, N> {
+                let mut r = self;
+                r.$set(k);
+                r
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr<$t> for &Fp<P, N> {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn $f(self, k: $t) -> Fp<P, N> {
+                let mut r = *self;
+                r.$set(k);
+                r
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> $tr_assign<$t> for Fp<P, N> {
+            #[inline(always)]
+            fn $f_assign(&mut self, k: $t) {
+                self.$set(k);
+            }
+        }
+    };
+}
+
+// With the integer on the left: k + x = x + k, k - x = -(x - k) and
+// k * x = x * k.
+macro_rules! impl_fp_int_lhs {
+    ($t:ty) => {
+        impl<P: FieldParams<N>, const N: usize> Add<Fp<P, N>> for $t {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn add(self, x: Fp<P, N>) -> Fp<P, N> {
+                x + self
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> Add<&Fp<P, N>> for $t {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn add(self, x: &Fp<P, N>) -> Fp<P, N> {
+                x + self
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> Sub<Fp<P, N>> for $t {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn sub(self, x: Fp<P, N>) -> Fp<P, N> {
+                -(x - self)
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> Sub<&Fp<P, N>> for $t {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn sub(self, x: &Fp<P, N>) -> Fp<P, N> {
+                -(x - self)
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> Mul<Fp<P, N>> for $t {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn mul(self, x: Fp<P, N>) -> Fp<P, N> {
+                x * self
+            }
+        }
+
+        impl<P: FieldParams<N>, const N: usize> Mul<&Fp<P, N>> for $t {
+            type Output = Fp<P, N>;
+
+            #[inline(always)]
+            fn mul(self, x: &Fp<P, N>) -> Fp<P, N> {
+                x * self
+            }
+        }
+    };
+}
+
+impl_fp_int_binop!(i32, Add, add, AddAssign, add_assign, set_add_int);
+impl_fp_int_binop!(i32, Sub, sub, SubAssign, sub_assign, set_sub_int);
+impl_fp_int_binop!(i32, Mul, mul, MulAssign, mul_assign, set_mul_small);
+impl_fp_int_binop!(u64, Add, add, AddAssign, add_assign, set_add_int);
+impl_fp_int_binop!(u64, Sub, sub, SubAssign, sub_assign, set_sub_int);
+impl_fp_int_binop!(u64, Mul, mul, MulAssign, mul_assign, set_mul_u64);
+impl_fp_int_lhs!(i32);
+impl_fp_int_lhs!(u64);
+
+impl<P: FieldParams<N>, const N: usize> Sum for Fp<P, N> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a, P: FieldParams<N>, const N: usize> Sum<&'a Fp<P, N>> for Fp<P, N> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> Product for Fp<P, N> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<'a, P: FieldParams<N>, const N: usize> Product<&'a Fp<P, N>> for Fp<P, N> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> crate::traits::Fq for Fp<P, N> {
+    // Reexport constants for base field Trait
+    const N: usize = Self::N;
+    const ENCODED_LENGTH: usize = Self::ENCODED_LENGTH;
+    type Encoding = P::Encoding;
+    type Words = [u64; N];
+    const ZERO: Self = Self::ZERO;
+    const ONE: Self = Self::ONE;
+    const TWO: Self = Self::TWO;
+    const THREE: Self = Self::THREE;
+    const FOUR: Self = Self::FOUR;
+    const MINUS_ONE: Self = Self::MINUS_ONE;
+
+    fn is_zero(self) -> u32 {
+        self.is_zero()
+    }
+
+    fn equals(self, rhs: &Self) -> u32 {
+        self.equals(rhs)
+    }
+
+    fn set_neg(&mut self) {
+        self.set_neg()
+    }
+
+    fn set_half(&mut self) {
+        self.set_half()
+    }
+
+    fn set_mul2(&mut self) {
+        self.set_mul2()
+    }
+
+    fn set_mul3(&mut self) {
+        self.set_mul3()
+    }
+
+    fn set_mul4(&mut self) {
+        self.set_mul4()
+    }
+
+    fn set_mul8(&mut self) {
+        self.set_mul8()
+    }
+
+    fn half(self) -> Self {
+        self.half()
+    }
+    fn mul2(self) -> Self {
+        self.mul2()
+    }
+    fn mul3(self) -> Self {
+        self.mul3()
+    }
+    fn mul4(self) -> Self {
+        self.mul4()
+    }
+    fn mul8(self) -> Self {
+        self.mul8()
+    }
+
+    fn set_mul_small(&mut self, k: i32) {
+        self.set_mul_small(k)
+    }
+    fn set_square(&mut self) {
+        self.set_square()
+    }
+    fn set_n_square(&mut self, n: u32) {
+        self.set_n_square(n)
+    }
+    fn set_invert(&mut self) {
+        self.set_invert()
+    }
+    fn mul_small(self, k: i32) -> Self {
+        self.mul_small(k)
+    }
+    fn square(self) -> Self {
+        self.square()
+    }
+    fn n_square(self, n: u32) -> Self {
+        self.n_square(n)
+    }
+    fn invert(self) -> Self {
+        self.invert()
+    }
+    fn set_sqrt(&mut self) -> u32 {
+        self.set_sqrt()
+    }
+    fn sqrt(self) -> (Self, u32) {
+        self.sqrt()
+    }
+    fn sgn0(self) -> u32 {
+        self.sgn0()
+    }
+    fn is_lexicographically_largest(self) -> u32 {
+        self.is_lexicographically_largest()
+    }
+    fn legendre(self) -> i32 {
+        self.legendre()
+    }
+    fn is_square(self) -> u32 {
+        self.is_square()
+    }
+    fn batch_invert(xx: &mut [Self]) {
+        <Fp<P, N>>::batch_invert(xx)
+    }
+
+    fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+        self.set_select(a, b, ctl)
+    }
+    fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+        self.set_cond(rhs, ctl)
+    }
+    fn set_cond_neg(&mut self, ctl: u32) {
+        self.set_cond_neg(ctl)
+    }
+    fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+        <Fp<P, N>>::select(a, b, ctl)
+    }
+    fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+        <Fp<P, N>>::cond_swap(a, b, ctl)
+    }
+
+    fn encode(self) -> P::Encoding {
+        self.encode()
+    }
+    fn decode(buf: &[u8]) -> (Self, u32) {
+        <Fp<P, N>>::decode(buf)
+    }
+    fn decode_reduce(buf: &[u8]) -> Self {
+        <Fp<P, N>>::decode_reduce(buf)
+    }
+
+    fn hashcode(self) -> u64 {
+        self.hashcode()
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> crate::traits::FqRoots for Fp<P, N> {
+    fn set_fourth_root(&mut self) -> u32 {
+        self.set_fourth_root()
+    }
+    fn fourth_root(self) -> (Self, u32) {
+        self.fourth_root()
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> crate::traits::FqExp for Fp<P, N> {
+    fn set_pow(&mut self, e: &[u8], ebitlen: usize) {
+        self.set_pow(e, ebitlen)
+    }
+    fn set_pow_ext(&mut self, e: &[u8], eoff: usize, ebitlen: usize) {
+        self.set_pow_ext(e, eoff, ebitlen)
+    }
+    fn set_pow_u64(&mut self, e: u64, ebitlen: usize) {
+        self.set_pow_u64(e, ebitlen)
+    }
+    fn set_pow_u64_vartime(&mut self, e: u64) {
+        self.set_pow_u64_vartime(e)
+    }
+    fn set_pow_pubexp(&mut self, e: &[u64; N]) {
+        self.set_pow_pubexp(e)
+    }
+    fn pow(self, e: &[u8], ebitlen: usize) -> Self {
+        self.pow(e, ebitlen)
+    }
+    fn pow_ext(self, e: &[u8], eoff: usize, ebitlen: usize) -> Self {
+        self.pow_ext(e, eoff, ebitlen)
+    }
+    fn pow_u64(self, e: u64, ebitlen: usize) -> Self {
+        self.pow_u64(e, ebitlen)
+    }
+    fn pow_u64_vartime(self, e: u64) -> Self {
+        self.pow_u64_vartime(e)
+    }
+    fn pow_pubexp(self, e: &[u64; N]) -> Self {
+        self.pow_pubexp(e)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> crate::traits::FqRnd for Fp<P, N> {
+    fn set_rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(&mut self, rng: &mut R) {
+        self.set_rand(rng)
+    }
+    fn rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(rng: &mut R) -> Self {
+        <Fp<P, N>>::rand(rng)
+    }
+}
+
+impl<P: FieldParams<N>, const N: usize> crate::traits::Fp2BaseField for Fp<P, N> {
+    // Reexport constants for Trait
+    const BIT_LENGTH: usize = Self::BIT_LENGTH;
+    type Fp2Encoding = P::Fp2Encoding;
+    const SUM_OF_PRODUCTS_ADDITIONAL_SUB: bool = Self::SUM_OF_PRODUCTS_ADDITIONAL_SUB;
+
+    fn sum_of_products(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        <Fp<P, N>>::sum_of_products(a1, b1, a2, b2)
+    }
+    fn difference_of_products(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        <Fp<P, N>>::difference_of_products(a1, b1, a2, b2)
+    }
+    fn progenitor(self) -> Self {
+        self.progenitor()
+    }
+}
@@ -0,0 +1,2599 @@
+//! A generic implementation of efficient and constant-time arithmetic for the
+//! finite field Fp^2 with modulus x^2 + 1.
+//!
+//! # Traits
+//!
+//! The type `Fp2<F>` is the degree two extension of the base field `F`, and
+//! implements the traits `Fq` and `Fp2` for any base field implementing the
+//! trait `Fp2BaseField`, such as `fp::Fp`:
+//!
+//! ```
+//! use fp2::fp::{FieldParams, Fp};
+//! use fp2::fp2::Fp2;
+//!
+//! pub struct Fp127Params;
+//!
+//! impl FieldParams<2> for Fp127Params {
+//!     const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+//!     type Encoding = [u8; 16];
+//!     type Fp2Encoding = [u8; 32];
+//! }
+//!
+//! pub type Fp127Ext = Fp2<Fp<Fp127Params, 2>>;
+//!
+//! let z = Fp127Ext::ZETA;
+//! assert!(z.square().equals(&Fp127Ext::MINUS_ONE) == u32::MAX);
+//! ```
+//!
+//! The macros `define_fp2_from_modulus` and `define_fp2_from_type` are thin
+//! wrappers which define the base field (for the former) and the alias as
+//! above.
+//!
+//! # Authorship and History
+//!
+//! The majority of this code has been adapted from code written by Thomas Pornin
+//! from collaboration in previous projects and several methods which appear in other
+//! macros in the cryptographic research library crrl <https://github.com/pornin/crrl>
+//!
+//! This code has also been used in a handful of isogeny-based cryptography research
+//! projects before being rewritten for this crate, including:
+//! - <https://github.com/ThetaIsogenies/two-isogenies>
+//! - <https://github.com/GiacomoPope/cubical-pairings>
+//! - <https://github.com/GiacomoPope/ThetaCGL>
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::fp::{ByteArray, Decimal, FieldParams, Fp, fmt_decimal, fmt_hex};
+use crate::traits::{Fp2BaseField, SignConvention};
+
+/// GF(p^2) implementation, with modulus x^2 + 1 over the base field `F`.
+///
+/// Multiplications use the sum of products of the base field (see
+/// `Fp2BaseField`), unless `SOP` is `false`, in which case they always use
+/// the schoolbook method and `set_mul_products()` panics.
+#[derive(Clone, Copy)]
+pub struct Fp2<F, const SOP: bool = true> {
+    x0: F,
+    x1: F,
+}
+
+impl<F: Fp2BaseField, const SOP: bool> Fp2<F, SOP> {
+    pub const ZERO: Self = Self {
+        x0: F::ZERO,
+        x1: F::ZERO,
+    };
+    pub const ONE: Self = Self {
+        x0: F::ONE,
+        x1: F::ZERO,
+    };
+    pub const TWO: Self = Self {
+        x0: F::TWO,
+        x1: F::ZERO,
+    };
+    pub const THREE: Self = Self {
+        x0: F::THREE,
+        x1: F::ZERO,
+    };
+    pub const FOUR: Self = Self {
+        x0: F::FOUR,
+        x1: F::ZERO,
+    };
+    pub const MINUS_ONE: Self = Self {
+        x0: F::MINUS_ONE,
+        x1: F::ZERO,
+    };
+    pub const ZETA: Self = Self {
+        x0: F::ZERO,
+        x1: F::ONE,
+    };
+    pub const MINUS_ZETA: Self = Self {
+        x0: F::ZERO,
+        x1: F::MINUS_ONE,
+    };
+
+    pub const ENCODED_LENGTH: usize = 2 * F::ENCODED_LENGTH;
+    pub const CHAR_BIT_LENGTH: usize = F::BIT_LENGTH;
+
+    pub const fn new(re: &F, im: &F) -> Self {
+        Self { x0: *re, x1: *im }
+    }
+
+    /// Create an element [x0, x1] from a pair of integers
+    #[inline(always)]
+    pub fn from_u32_pair(x0: u32, x1: u32) -> Self {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x0);
+        r.x1 = F::from(x1);
+        r
+    }
+
+    /// Create an element [x0, x1] from a pair of integers
+    #[inline(always)]
+    pub fn from_i32_pair(x0: i32, x1: i32) -> Self {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x0);
+        r.x1 = F::from(x1);
+        r
+    }
+
+    /// Create an element [x0, x1] from a pair of integers
+    #[inline(always)]
+    pub fn from_u64_pair(x0: u64, x1: u64) -> Self {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x0);
+        r.x1 = F::from(x1);
+        r
+    }
+
+    /// Create an element [x0, x1] from a pair of integers
+    #[inline(always)]
+    pub fn from_i64_pair(x0: i64, x1: i64) -> Self {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x0);
+        r.x1 = F::from(x1);
+        r
+    }
+
+    /// Set the real part of the value to a small integer value
+    #[inline(always)]
+    pub fn set_x0_small(&mut self, x: i32) {
+        self.x0 = F::from(x);
+    }
+
+    /// Set the real part of the value to a small integer value
+    #[inline(always)]
+    pub fn set_x1_small(&mut self, x: i32) {
+        self.x1 = F::from(x);
+    }
+
+    #[inline]
+    pub fn is_zero(self) -> u32 {
+        self.x0.is_zero() & self.x1.is_zero()
+    }
+
+    /// Return the x0 value such that self = x0 + i*x1
+    #[inline]
+    pub fn x0(self) -> F {
+        self.x0
+    }
+
+    /// Return the x0 value such that self = x0 + i*x1
+    #[inline]
+    pub fn x1(self) -> F {
+        self.x1
+    }
+
+    /// Return the x0 and x1 values such that self = x0 + i*x1
+    #[inline]
+    pub fn xi(self) -> (F, F) {
+        (self.x0, self.x1)
+    }
+
+    #[inline]
+    pub fn equals(self, rhs: &Self) -> u32 {
+        self.x0.equals(&rhs.x0) & self.x1.equals(&rhs.x1)
+    }
+
+    #[inline]
+    pub fn set_add(&mut self, rhs: &Self) {
+        self.x0 += rhs.x0;
+        self.x1 += rhs.x1;
+    }
+
+    #[inline]
+    pub fn set_sub(&mut self, rhs: &Self) {
+        self.x0 -= rhs.x0;
+        self.x1 -= rhs.x1;
+    }
+
+    #[inline]
+    pub fn set_neg(&mut self) {
+        self.x0.set_neg();
+        self.x1.set_neg();
+    }
+
+    #[inline]
+    pub fn set_conjugate(&mut self) {
+        self.x1.set_neg();
+    }
+
+    #[inline]
+    pub fn conjugate(self) -> Self {
+        Self {
+            x0: self.x0,
+            x1: -self.x1,
+        }
+    }
+
+    #[inline]
+    // This schoolbook method always works and is available for benchmarking
+    // Depending on whether the base field has `sum_of_products` as an optimised
+    // method, either this or `mul_products` is used as the default within `set_mul`
+    pub fn set_mul_schoolbook(&mut self, rhs: &Self) {
+        // a <- x0*y0
+        // b <- x1*y1
+        // c <- (x0 + x1)*(y0 + y1)
+        // (x0 + i*x1)*(y0 + i*y1) = (x0*y0 - x1*y1) + i*(x0*y1 + y0*x1)
+        //                         = (a - b) + i*(c - a - b)
+        let a = self.x0 * rhs.x0;
+        let b = self.x1 * rhs.x1;
+        let c = (self.x0 + self.x1) * (rhs.x0 + rhs.x1);
+        self.x0 = a;
+        self.x0 -= b;
+        self.x1 = c;
+        self.x1 -= a;
+        self.x1 -= b;
+    }
+
+    #[inline]
+    pub fn mul_schoolbook(&self, rhs: &Self) -> Self {
+        let mut r = *self;
+        r.set_mul_schoolbook(rhs);
+        r
+    }
+
+    #[inline]
+    pub fn mul_sum_of_products(&self, rhs: &Self) -> Self {
+        let mut r = *self;
+        r.set_mul_products(rhs);
+        r
+    }
+
+    #[inline]
+    pub fn set_mul_products(&mut self, other: &Self) {
+        if !SOP {
+            unimplemented!("sum of products is disabled for this field");
+        }
+
+        // Computes x*y from:
+        // x = (x0 + i*x1)
+        // y = (y0 + i*y1)
+        // x*y = (x0 + i*x1)*(y0 + i*y1)
+        //     = (x0*y0 - x1*y1) + i*(x0*y1 + y0*x1)
+        // Computes (x0*y0 - x1*y1)
+        let x0 = F::difference_of_products(&self.x0, &other.x0, &self.x1, &other.x1);
+        // Computes (x0*y1 + y0*x1)
+        let x1 = F::sum_of_products(&self.x0, &other.x1, &self.x1, &other.x0);
+
+        self.x0 = x0;
+        self.x1 = x1;
+    }
+
+    #[inline(always)]
+    pub fn set_mul(&mut self, other: &Self) {
+        // If the sum of products needs additional subtractions, then
+        // most of the time schoolbook is better.
+        if !SOP || F::SUM_OF_PRODUCTS_ADDITIONAL_SUB {
+            self.set_mul_schoolbook(other);
+        } else {
+            self.set_mul_products(other);
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_square(&mut self) {
+        // (x0 + i*x1)^2 = (x0^2 - x1^2) + 2*i*(x0*x1)
+        //               = (x0 + x1)*(x0 - x1) + i*(2*x0*x1)
+        let a = self.x0 + self.x1;
+        let b = self.x0 - self.x1;
+        self.x1 *= self.x0;
+        self.x1.set_mul2();
+        self.x0 = a;
+        self.x0 *= b;
+    }
+
+    #[inline]
+    pub fn square(self) -> Self {
+        let mut r = self;
+        r.set_square();
+        r
+    }
+
+    /// Square this value n times in place
+    #[inline]
+    pub fn set_n_square(&mut self, n: u32) {
+        for _ in 0..n {
+            self.set_square();
+        }
+    }
+
+    /// Square this value n times
+    #[inline]
+    pub fn n_square(self, n: u32) -> Self {
+        let mut r = self;
+        r.set_n_square(n);
+        r
+    }
+
+    #[inline]
+    pub fn set_half(&mut self) {
+        self.x0.set_half();
+        self.x1.set_half();
+    }
+
+    #[inline]
+    pub fn half(self) -> Self {
+        let mut r = self;
+        r.set_half();
+        r
+    }
+
+    #[inline]
+    pub fn set_mul2(&mut self) {
+        self.x0.set_mul2();
+        self.x1.set_mul2();
+    }
+
+    #[inline]
+    pub fn mul2(self) -> Self {
+        let mut r = self;
+        r.set_mul2();
+        r
+    }
+
+    #[inline]
+    pub fn set_mul3(&mut self) {
+        self.x0.set_mul3();
+        self.x1.set_mul3();
+    }
+
+    #[inline]
+    pub fn mul3(self) -> Self {
+        let mut r = self;
+        r.set_mul3();
+        r
+    }
+
+    #[inline]
+    pub fn set_mul4(&mut self) {
+        self.x0.set_mul4();
+        self.x1.set_mul4();
+    }
+
+    #[inline]
+    pub fn mul4(self) -> Self {
+        let mut r = self;
+        r.set_mul4();
+        r
+    }
+
+    #[inline]
+    pub fn set_mul8(&mut self) {
+        self.x0.set_mul8();
+        self.x1.set_mul8();
+    }
+
+    #[inline]
+    pub fn mul8(self) -> Self {
+        let mut r = self;
+        r.set_mul8();
+        r
+    }
+
+    #[inline]
+    pub fn set_mul_small(&mut self, k: i32) {
+        self.x0.set_mul_small(k);
+        self.x1.set_mul_small(k);
+    }
+
+    #[inline]
+    pub fn mul_small(self, k: i32) -> Self {
+        let mut r = self;
+        r.set_mul_small(k);
+        r
+    }
+
+    /// Add the element `rhs` of the base field to this value.
+    #[inline]
+    pub fn set_add_base(&mut self, rhs: &F) {
+        self.x0 += *rhs;
+    }
+
+    /// Subtract the element `rhs` of the base field from this value.
+    #[inline]
+    pub fn set_sub_base(&mut self, rhs: &F) {
+        self.x0 -= *rhs;
+    }
+
+    /// Multiply this value by the element `rhs` of the base field, with two
+    /// multiplications in the base field.
+    #[inline]
+    pub fn set_mul_base(&mut self, rhs: &F) {
+        self.x0 *= *rhs;
+        self.x1 *= *rhs;
+    }
+
+    #[inline]
+    pub fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+        self.x0.set_select(&a.x0, &b.x0, ctl);
+        self.x1.set_select(&a.x1, &b.x1, ctl);
+    }
+
+    #[inline]
+    pub fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+        Self {
+            x0: F::select(&a.x0, &b.x0, ctl),
+            x1: F::select(&a.x1, &b.x1, ctl),
+        }
+    }
+
+    #[inline]
+    pub fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+        self.x0.set_cond(&rhs.x0, ctl);
+        self.x1.set_cond(&rhs.x1, ctl);
+    }
+
+    #[inline]
+    pub fn set_cond_neg(&mut self, ctl: u32) {
+        let y0 = -self.x0;
+        let y1 = -self.x1;
+        self.x0.set_cond(&y0, ctl);
+        self.x1.set_cond(&y1, ctl);
+    }
+
+    #[inline]
+    pub fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+        F::cond_swap(&mut a.x0, &mut b.x0, ctl);
+        F::cond_swap(&mut a.x1, &mut b.x1, ctl);
+    }
+
+    #[inline]
+    pub fn set_div(&mut self, rhs: &Self) {
+        // 1/(x0 + i*x1) = (x0 - i*x1)/(x0^2 + x1^2)
+        let mut z = rhs.x0.square();
+        z += rhs.x1.square();
+        z.set_invert();
+        let mut r = *rhs;
+        r.x1.set_neg();
+        r.x0 *= z;
+        r.x1 *= z;
+        self.set_mul(&r);
+    }
+
+    #[inline]
+    pub fn set_invert(&mut self) {
+        // 1/(x0 + i*x1) = (x0 - i*x1)/(x0^2 + x1^2)
+        let mut z = self.x0.square();
+        z += self.x1.square();
+        z.set_invert();
+        self.x0 *= z;
+        self.x1 *= z;
+        self.x1.set_neg();
+    }
+
+    #[inline]
+    pub fn invert(self) -> Self {
+        let mut r = self;
+        r.set_invert();
+        r
+    }
+
+    /// Legendre symbol on this value. Return value is:
+    ///   0   if this value is zero
+    ///  +1   if this value is a non-zero quadratic residue
+    ///  -1   if this value is not a quadratic residue
+    #[inline]
+    pub fn legendre(self) -> i32 {
+        // x = x0 + i*x1 is a square in GF(p^2) if and only if
+        // x0^2 + x1^2 is a square in GF(p). Moreover, x0^2 + x1^2 is
+        // zero if and only if x is zero.
+        (self.x0.square() + self.x1.square()).legendre()
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a square in GF(p^2) and
+    /// `0x00000000` otherwise.
+    #[inline]
+    pub fn is_square(self) -> u32 {
+        !((self.legendre() >> 1) as u32)
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a square in GF(p) and
+    /// `0x00000000` otherwise.
+    #[inline]
+    pub fn is_square_base_field(self) -> u32 {
+        // x = x0 + i*x1 is a square in GF(p) if and only if
+        // x0 is a square and x1 is zero;
+        self.x0.is_square() & self.x1.is_zero()
+    }
+
+    /// Set this value to its square root. Returned value is 0xFFFFFFFF if
+    /// the operation succeeded (value was indeed a quadratic residue), or
+    /// 0x00000000 otherwise. On success, the chosen root is the one whose
+    /// sign is 0 (i.e. if the "real part" is non-zero, then it is an even
+    /// integer; if the "real part" is zero, then the "imaginary part" is
+    /// an even integer). On failure, this value is set to 0.
+    pub fn set_sqrt(&mut self) -> u32 {
+        // x^p = (x0 + i*x1)^p = x0 - i*x1  (Frobenius automorphism)
+        // Thus: x^(p+1) = (x0 + i*x1)*(x0 - i*x1) = x0^2 + x1^2, which
+        // is an element of GF(p). All elements of GF(p) are squares in
+        // GF(p^2), but x0^2 + x1^2 is not necessarily a square in GF(p).
+        //
+        // Let conj(p) = x^p = x0 - i*x1. Note that conj() is analogous to
+        // the conjugate in complex numbers. In particular:
+        //    conj(a + b) = conj(a) + conj(b)
+        //    conj(a * b) = conj(a) * conj(b)
+        // This implies that conj(x) is a square if and only if x is a
+        // square, and conj(sqrt(x)) = sqrt(conj(x)). Thus, if x is a
+        // square, then:
+        //    (sqrt(x)*conj(sqrt(x)))^2 = x*conj(x) = x0^2 + x1^2
+        // But sqrt(x)*conj(sqrt(x)) is in GF(p); therefore, if x is a
+        // square, then x0^2 + x1^2 must be a square in GF(p).
+        //
+        // Suppose that y = y0 + i*y1 such that y^2 = x. Then:
+        //   y0^2 - y1^2 = x0
+        //   2*y0*y1 = x1
+        // If x1 = 0 then:
+        //    if x0.legendre() >= 0 then y = sqrt(x0)
+        //                          else y = i*sqrt(-x0)
+        // else:
+        //    y0 != 0 (necessarily) and y1 = x1 / (2*y0)
+        //    Thus:
+        //       y0^4 - x0*y0^2 - (x1^2)/4 = 0
+        //    Discriminant is delta = x0^2 + x1^2, which is always a square
+        //    (see above). Therefore:
+        //       y0^2 = (x0 +/- sqrt(delta))/2
+        //    We can thus compute (x0 + sqrt(delta))/2 and check its
+        //    Legendre symbol; we subtract sqrt(delta) from it if it is
+        //    not a square. We then extract y0 as a square root of the
+        //    result, and compute y1 from it.
+        //
+        // Main cost is the two square roots in GF(p) (for delta and
+        // for y0); Legendre symbols and inversions are vastly faster.
+
+        // sqrt_delta <- sqrt(x0^2 + x1^2)
+        let (sqrt_delta, r1) = (self.x0.square() + self.x1.square()).sqrt();
+        // y0sq <- (x0 + sqrt(delta)) / 2
+        let mut y0sq = (self.x0 + sqrt_delta).half();
+        // If x1 = 0, then replace y0sq with x0
+        let x1z = self.x1.is_zero();
+        y0sq.set_cond(&self.x0, x1z);
+        // Get the Legendre symbol and set nqr to 0xFFFFFFFF when y0sq
+        // is not a square
+        let ls = y0sq.legendre();
+        let nqr = (ls >> 1) as u32;
+        // If not a square:
+        //    if x1 = 0, then y0sq contains x0 and we want -x0
+        //    if x1 != 0, then y0sq <- y0sq - sqrt(delta)
+        y0sq.set_cond_neg(nqr & x1z);
+        y0sq.set_cond(&(y0sq - sqrt_delta), nqr & !x1z);
+        // Get the square root.
+        let (mut y0, r2) = y0sq.sqrt();
+        let r = r1 & r2;
+        // Compute y1 = x1 / (2*y0).
+        let mut y1 = self.x1 / y0.mul2();
+        // If x1 = 0, then the square root worked, and y1 = 0 at this point;
+        // we must still exchange y0 and y1 if x0 was not a square.
+        F::cond_swap(&mut y0, &mut y1, nqr & x1z);
+        // Result goes into this object. If there was a failure (r == 0),
+        // then we must clear both x0 and x1.
+        self.x0.set_select(&F::ZERO, &y0, r);
+        self.x1.set_select(&F::ZERO, &y1, r);
+        // Sign management: negate the result if needed.
+        let ctl = self.sgn0();
+        self.set_cond_neg(ctl);
+        r
+    }
+
+    pub fn sqrt(self) -> (Self, u32) {
+        let mut y = self;
+        let r = y.set_sqrt();
+        (y, r)
+    }
+
+    // Compute a square root of u/v with the complex method of M. Scott
+    // (ePrint 2020/1497), without inversion: see sqrt_ratio(). If u/v is not a square and a non-square
+    // z is provided, along with c such that c^2 = -N(z), then this returns
+    // a square root of z*u/v; otherwise, the result is zero. The returned
+    // flag is set if v != 0 and u/v is a square.
+    fn sqrt_ratio_inner(u: &Self, v: &Self, fallback: Option<(&Self, &F)>) -> (Self, u32) {
+        // Let a = u*conj(v) and n = N(v), so that u/v = a/n. As in
+        // set_sqrt(), with x = a/n and delta = N(a)/n^2, the real part y0
+        // of the root is a square root of w = (a0 + sqrt(N(a)))/(2*n) (or
+        // a0/n if a1 = 0), or of w'' = (a0 - sqrt(N(a)))/(2*n) if w is not
+        // a square, and w*w'' = -(a1/n)^2/4.
+        //
+        // Write w = w'/n, let m = w'*n^3 (which is w*n^4) and
+        // z = m^((p-3)/4). If w is a square, then m*z^2 = 1, and
+        // y0 = sqrt(w) = w'*n*z and y1 = (a1/n)/(2*y0) = a1*n*z/2.
+        // Otherwise, m*z^2 = -1, so that sqrt(-1/w) = n^2*z and
+        // y0 = (a1/n)*sqrt(-1/w)/2 = a1*n*z/2 and y1 = -w'*n*z.
+        let n = v.x0.square() + v.x1.square();
+        let mut a = *u * v.conjugate();
+
+        // s = sqrt(N(a)) if N(a) is a square, or sqrt(-N(a)) otherwise.
+        let d = a.x0.square() + a.x1.square();
+        let mut s = d * d.progenitor();
+        let mut r = s.square().equals(&d);
+        if let Some((z, c)) = fallback {
+            // N(z*a) = N(z)*N(a) = (c*s)^2
+            a.set_cond(&(a * *z), !r);
+            s.set_cond(&(s * *c), !r);
+        }
+
+        // w' = (a0 + s)/2, or a0 if a1 = 0
+        let mut w = (a.x0 + s).half();
+        w.set_cond(&a.x0, a.x1.is_zero());
+        let wn = w * n;
+        let z = (wn * n.square()).progenitor();
+        let mut y0 = wn * z;
+        let mut y1 = (a.x1 * n * z).half();
+        // If w is not a square, y0 = a1*n*z/2 and y1 = -w'*n*z
+        let nqr = !(y0.square() * n).equals(&w);
+        y0.set_cond_neg(nqr);
+        F::cond_swap(&mut y0, &mut y1, nqr);
+        let mut y = Self { x0: y0, x1: y1 };
+        if fallback.is_none() {
+            y.set_cond(&Self::ZERO, !r);
+        }
+        r &= !n.is_zero();
+
+        // Sign management: negate the result if needed.
+        let ctl = y.sgn0();
+        y.set_cond_neg(ctl);
+        (y, r)
+    }
+
+    /// Set this value to its inverse square root. Returned value is
+    /// 0xFFFFFFFF if the operation succeeded (value was a non-zero
+    /// quadratic residue), or 0x00000000 otherwise. On success, the chosen
+    /// root is the one whose sign is 0 (as in `set_sqrt()`). On failure,
+    /// this value is set to 0.
+    ///
+    /// This costs two exponentiations in GF(p), as `set_sqrt()`, and no
+    /// inversion.
+    pub fn set_invsqrt(&mut self) -> u32 {
+        let r;
+        (*self, r) = Self::sqrt_ratio_inner(&Self::ONE, self, None);
+        r
+    }
+
+    /// Compute the inverse square root of this value. If this value is a
+    /// non-zero quadratic residue, then this returns (y, 0xFFFFFFFF), with
+    /// y being the square root of 1/x whose sign is 0. Otherwise, this
+    /// returns (zero, 0x00000000).
+    pub fn invsqrt(self) -> (Self, u32) {
+        Self::sqrt_ratio_inner(&Self::ONE, &self, None)
+    }
+
+    /// Return 0xFFFFFFFF if the sign of this value is 1 for `sgn0` of
+    /// RFC 9380, or 0x00000000 otherwise: if the "real part" is non-zero,
+    /// then this is whether it is odd, otherwise whether the "imaginary
+    /// part" is odd. This is the sign used by `sqrt()` and `fourth_root()`.
+    pub fn sgn0(self) -> u32 {
+        self.x0.sgn0() | (self.x0.is_zero() & self.x1.sgn0())
+    }
+
+    /// Return 0xFFFFFFFF if this value is lexicographically largest, or
+    /// 0x00000000 otherwise: if the "imaginary part" is non-zero, then this
+    /// is whether it is larger than (p-1)/2, otherwise whether the "real
+    /// part" is.
+    pub fn is_lexicographically_largest(self) -> u32 {
+        self.x1.is_lexicographically_largest()
+            | (self.x1.is_zero() & self.x0.is_lexicographically_largest())
+    }
+
+    /// Return 0xFFFFFFFF if the sign of this value is 1 for the convention
+    /// `sc`, or 0x00000000 otherwise.
+    pub fn sign(self, sc: SignConvention) -> u32 {
+        match sc {
+            SignConvention::Sgn0 => self.sgn0(),
+            SignConvention::Positive => self.is_lexicographically_largest(),
+        }
+    }
+
+    /// Negate this value if its sign is 1 for the convention `sc`, so that
+    /// it becomes the root of its square chosen by `sc`.
+    pub fn set_canonical_sign(&mut self, sc: SignConvention) {
+        let ctl = self.sign(sc);
+        self.set_cond_neg(ctl);
+    }
+
+    /// Set this value to its square root, as `set_sqrt()`, but choosing the
+    /// root with the sign convention `sc`.
+    pub fn set_sqrt_signed(&mut self, sc: SignConvention) -> u32 {
+        let r = self.set_sqrt();
+        self.set_canonical_sign(sc);
+        r
+    }
+
+    /// Compute the square root of this value, as `sqrt()`, but choosing the
+    /// root with the sign convention `sc`.
+    pub fn sqrt_signed(self, sc: SignConvention) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_sqrt_signed(sc);
+        (x, r)
+    }
+
+    /// Set this value to its fourth root. Returned value is 0xFFFFFFFF if
+    /// the operation succeeded (value was indeed a fourth root), or
+    /// 0x00000000 otherwise. On success, the chosen root is the one whose
+    /// sign is 0 (i.e. if the "real part" is non-zero, then it is an even
+    /// integer; if the "real part" is zero, then the "imaginary part" is
+    /// an even integer). On failure, this value is set to 0.
+    pub fn set_fourth_root(&mut self) -> u32 {
+        // The aim of this function is to generalise set_sqrt by finding
+        // an element of Fp^2, y = y0 + i*y1 such that x = x0 + i x1 = y^4
+        //
+        // Ultimately, this is done by writing out relationships between
+        // xi and yi to solve a quadratic equation.
+        //
+        // If we have y^4 = (y0 + i*y1)^4 = x0 + i*x1 then:
+        //     x0 = y0^4 - 6*y0^2*y1^2 + y1^4
+        //     x1 = 4*y0*y1*(y0^2 - y1^2)
+        // Additionally, using that the norm is multiplicative,
+        // we have that
+        //
+        //     norm(x) = (x0^2 + x1^2)
+        //     norm(y) = n = (y0^2 + y1^2) = norm(x)^4
+        //
+        // We can compute n = y0^2 + y1^2 with only a fourth-root
+        // in Fp: n = (x0^2 + x1^2)^((p+1) / 8)
+        // where we use p = 7 mod 8, otherwise we do two sqrt which
+        // will be slower (2x the cost).
+        //
+        // Combining the expanded result and the norm equation
+        // gives a quartic polynomial in y0 which only appears
+        // with even powers:
+        //
+        //    8*y0^4 - 8*n*y0^2 + n^2 - x0 = 0
+        //    y0^4 - n*y0^2 + (n^2 - x0) / 8 = 0
+        //
+        // We can write this as a quadratic equation in y0^2
+        // and solve for y0^2 as:
+        //
+        //    y0^2 = (n ± sqrt(n^2 - (n^2 - x0)/2)) / 2
+        //
+        // and so y0^2 is recovered from the sqrt of the disc.
+        //    disc = n^2 - (n^2 - x0)/2 = (n^2 + x0)/2
+        //
+        // To recover y0 itself we require one last sqrt in Fp
+        // which one of the two values
+        //
+        //    y0^2 = sqrt(n ± sqrt_disc) / 2
+        //
+        // We can tell which value to pick by looking at the
+        // legendre symbol of y0^2 and flipping the sign of n
+        // when a QNR is found.
+        //
+        // Finally, we can compute y1 from the above using that
+        //     y1 = x1 / (4 * y0 * sqrt_disc)
+        //
+        // TODO: explain edge cases carefully.
+        let norm = self.x0.square() + self.x1.square();
+        let (mut n, r1) = norm.fourth_root();
+        // Now we need to solve a quadratic equation for y0
+        // 8y0^4 - 8ny0^2 + n^2 - x0 = 0
+        // The disc of this polynomial is given as
+        // y0^2 = [8n + sqrt(32(n^2 + x0))] / 16
+        // disc = 32(n^2 + x0)
+        let disc = (n.square() + self.x0).half();
+
+        // This has a solution, so we can always take a sqrt
+        let (disc_sqrt, r2) = disc.sqrt();
+
+        // Solving this polynomial gives y0^2, the solution
+        // will be one of these two, which we pick by ensuring
+        // y0^2 has a rational sqrt
+        let mut y02 = (disc_sqrt + n).half();
+
+        // Computing y0 means taking a sqrt. First, we
+        // need to check if the sqrt is rational in Fp
+        // If y0^2 is not a square, we use y0^2 - n
+        // and also flip the sign of n
+        let lsy02 = y02.legendre();
+        let nqr = (lsy02 >> 1) as u32;
+        y02.set_cond(&(y02 - n), nqr);
+        n.set_cond_neg(nqr);
+
+        // When y0^2 is zero, the correct value
+        // is insead n, so we can do a conditional
+        // swap
+        y02.set_cond(&n, y02.is_zero());
+
+        // Now we can take the sqrt no problem, for all
+        // cases!
+        let (y0, r3) = y02.sqrt();
+
+        // y1 is computed from y0 with an inversion for
+        // all cases, except when x1 = 0 (see below)
+        let mut y1 = self.x1 / (y0 * disc_sqrt.mul4());
+
+        // The final check comes from the case when x1 = 0
+        // Generally, we have that:
+        //     If x1 == 0 and x0 is a square, y1 = 0
+        //     If x1 == 0 and x0 is not a square, y1 = y0
+        //
+        // However, when x1 == 0 then y1 is already zero, so
+        // all we need to account for is the case when we need
+        // to set y1 = y0.
+        //
+        // if x1 is zero and x0 is a NQR then we want to return
+        // F(y0, y0) so we conditionally set y1 = y0
+        // Rather than check whether x0 is a square, we can instead
+        // check whether the discrim. is zero in this case
+        y1.set_cond(&y0, self.x1.is_zero() & disc.is_zero());
+
+        // As long has nothing bad has happened, we can
+        // now return the fourth root. If any of the r are
+        // falsey, we return 0
+        let r = r1 & r2 & r3;
+        self.x0.set_select(&F::ZERO, &y0, r);
+        self.x1.set_select(&F::ZERO, &y1, r);
+
+        // Sign management: negate the result if needed.
+        let ctl = self.sgn0();
+        self.set_cond_neg(ctl);
+
+        r
+    }
+
+    pub fn fourth_root(self) -> (Self, u32) {
+        let mut y = self;
+        let r = y.set_fourth_root();
+        (y, r)
+    }
+
+    /// Set this value to its fourth root, as `set_fourth_root()`, but
+    /// choosing between the roots `y` and `-y` with the sign convention
+    /// `sc`.
+    pub fn set_fourth_root_signed(&mut self, sc: SignConvention) -> u32 {
+        let r = self.set_fourth_root();
+        self.set_canonical_sign(sc);
+        r
+    }
+
+    /// Compute the fourth root of this value, as `fourth_root()`, but
+    /// choosing between the roots `y` and `-y` with the sign convention
+    /// `sc`.
+    pub fn fourth_root_signed(self, sc: SignConvention) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_fourth_root_signed(sc);
+        (x, r)
+    }
+
+    /// Raise this value to the power e. Exponent e is encoded in
+    /// unsigned little-endian convention over exactly ebitlen bits.
+    pub fn set_pow(&mut self, e: &[u8], ebitlen: usize) {
+        self.set_pow_ext(e, 0, ebitlen);
+    }
+
+    /// Raise this value to the power e. Exponent e is encoded in
+    /// unsigned little-endian convention, over exactly ebitlen bits,
+    /// and starting at the bit offset eoff.
+    pub fn set_pow_ext(&mut self, e: &[u8], eoff: usize, ebitlen: usize) {
+        // TODO: implement a window optimization to make fewer
+        // multiplications.
+        let x = *self;
+        *self = Self::ONE;
+        for i in (eoff..(eoff + ebitlen)).rev() {
+            let y = *self * x;
+            let ctl = (((e[i >> 3] >> (i & 7)) as u32) & 1).wrapping_neg();
+            self.set_cond(&y, ctl);
+            if i == eoff {
+                break;
+            }
+            self.set_square();
+        }
+    }
+
+    /// Return this value to the power e (as a new element). Exponent e
+    /// is encoded in unsigned little-endian convention over exactly
+    /// ebitlen bits.
+    pub fn pow(self, e: &[u8], ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow(e, ebitlen);
+        x
+    }
+
+    /// Return this value to the power e (as a new element). Exponent e
+    /// is encoded in unsigned little-endian convention over exactly
+    /// ebitlen bits, and starting at the bit offset eoff.
+    pub fn pow_ext(self, e: &[u8], eoff: usize, ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow_ext(e, eoff, ebitlen);
+        x
+    }
+
+    /// Raise this value to the power e. Exponent e is encoded in
+    /// unsigned little-endian convention over exactly ebitlen bits.
+    pub fn set_pow_limbs(&mut self, e: &[u64], ebitlen: usize) {
+        self.set_pow_ext_limbs(e, 0, ebitlen);
+    }
+
+    /// Raise this value to the power e. Exponent e is encoded in
+    /// unsigned little-endian convention, over exactly ebitlen bits,
+    /// and starting at the bit offset eoff.
+    pub fn set_pow_ext_limbs(&mut self, e: &[u64], eoff: usize, ebitlen: usize) {
+        let x = *self;
+        *self = Self::ONE;
+        for i in (eoff..(eoff + ebitlen)).rev() {
+            let y = *self * x;
+            let ctl = (((e[i >> 6] >> (i & 63)) as u32) & 1).wrapping_neg();
+            self.set_cond(&y, ctl);
+            if i == eoff {
+                break;
+            }
+            self.set_square();
+        }
+    }
+
+    /// Return this value to the power e (as a new element). Exponent e
+    /// is encoded in unsigned little-endian convention over exactly
+    /// ebitlen bits.
+    pub fn pow_limbs(self, e: &[u64], ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow_limbs(e, ebitlen);
+        x
+    }
+
+    /// Return this value to the power e (as a new element). Exponent e
+    /// is encoded in unsigned little-endian convention over exactly
+    /// ebitlen bits, and starting at the bit offset eoff.
+    pub fn pow_ext_limbs(self, e: &[u64], eoff: usize, ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow_ext_limbs(e, eoff, ebitlen);
+        x
+    }
+
+    /// Enocde this value into Self::ENCODED_LENGTH bytes
+    pub fn encode(self) -> F::Fp2Encoding {
+        let mut r = F::Fp2Encoding::ZERO;
+        let (r0, r1) = r.as_mut().split_at_mut(F::ENCODED_LENGTH);
+        r0.copy_from_slice(self.x0.encode().as_ref());
+        r1.copy_from_slice(self.x1.encode().as_ref());
+        r
+    }
+
+    /// Decode this value from bytes and return (val, u32::MAX) if the input decodes
+    /// to a canoncial value in [0, p-1], otherwise return (0, 0)
+    pub fn decode(buf: &[u8]) -> (Self, u32) {
+        if buf.len() != Self::ENCODED_LENGTH {
+            return (Self::ZERO, 0);
+        }
+        let (mut x0, c0) = F::decode(&buf[..F::ENCODED_LENGTH]);
+        let (mut x1, c1) = F::decode(&buf[F::ENCODED_LENGTH..]);
+        let cx = c0 & c1;
+        x0.set_cond(&F::ZERO, !cx);
+        x1.set_cond(&F::ZERO, !cx);
+        (Self { x0, x1 }, cx)
+    }
+
+    /// Decode the provided bytes into a field element. The source slice
+    /// can have arbitrary length; the bytes are interpreted with the
+    /// unsigned little-endian convention (no sign bit), with the first half
+    /// of the bytes corresponding to x0 and the latter half to x1. For each
+    /// resulting integer, the result is reduced modulo the field modulus p.
+    /// By definition, this function does not enforce canonicality of the source
+    /// value.
+    pub fn decode_reduce(buf: &[u8]) -> Self {
+        let n = buf.len() >> 1;
+        let x0 = F::decode_reduce(&buf[..n]);
+        let x1 = F::decode_reduce(&buf[n..]);
+        Self { x0, x1 }
+    }
+
+    /// Set this structure to a random field element (indistinguishable
+    /// from uniform generation).
+    pub fn set_rand<T: ::rand_core::CryptoRng + ::rand_core::RngCore>(&mut self, rng: &mut T) {
+        self.x0.set_rand(rng);
+        self.x1.set_rand(rng);
+    }
+
+    /// Return a new random field element (indistinguishable from
+    /// uniform generation).
+    pub fn rand<T: ::rand_core::CryptoRng + ::rand_core::RngCore>(rng: &mut T) -> Self {
+        let mut x = Self::ZERO;
+        x.set_rand(rng);
+        x
+    }
+
+    /// Raise this value to the power e. The exponent length (in bits)
+    /// MUST be at most ebitlen. This is constant-time for both the
+    /// base value (self) and the exponent (e); the exponent maximum
+    /// size (ebitlen) is considered non-secret.
+    pub fn set_pow_u64(&mut self, e: u64, ebitlen: usize) {
+        match ebitlen {
+            0 => {
+                *self = Self::ONE;
+            }
+            1 => {
+                self.set_cond(&Self::ONE, ((e as u32) & 1).wrapping_sub(1));
+            }
+            _ => {
+                let x = *self;
+                self.set_cond(
+                    &Self::ONE,
+                    (((e >> (ebitlen - 1)) as u32) & 1).wrapping_sub(1),
+                );
+                for i in (0..(ebitlen - 1)).rev() {
+                    self.set_square();
+                    let y = *self * x;
+                    self.set_cond(&y, (((e >> i) as u32) & 1).wrapping_neg());
+                }
+            }
+        }
+    }
+
+    /// Return this value to the power e. The exponent length (in bits)
+    /// MUST be at most ebitlen. This is constant-time for both the
+    /// base value (self) and the exponent (e); the exponent maximum
+    /// size (ebitlen) is considered non-secret.
+    pub fn pow_u64(self, e: u64, ebitlen: usize) -> Self {
+        let mut x = self;
+        x.set_pow_u64(e, ebitlen);
+        x
+    }
+
+    /// Raise this value to the power e. The exponent is considered
+    /// non-secret.
+    pub fn set_pow_u64_vartime(&mut self, e: u64) {
+        match e {
+            0 => {
+                *self = Self::ONE;
+            }
+            1 => {}
+            2 => {
+                self.set_square();
+            }
+            3 => {
+                *self *= self.square();
+            }
+            4 => {
+                self.set_square();
+                self.set_square();
+            }
+            _ => {
+                let xx = self.square();
+                let xw = [*self, xx, xx * *self];
+                let mut j = 63 - e.leading_zeros();
+                j &= !1u32;
+                *self = xw[((e >> j) as usize) - 1];
+                while j > 0 {
+                    j -= 2;
+                    self.set_square();
+                    self.set_square();
+                    let k = ((e >> j) as usize) & 3;
+                    if k > 0 {
+                        self.set_mul(&xw[k - 1]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Raise this value to the provided exponent. The exponent is non-zero
+    /// and is public. The exponent is encoded over N 64-bit limbs.
+    pub fn set_pow_pubexp(&mut self, e: &F::Words) {
+        // Make a 4-bit window; win[i] contains x^(i+1)
+        let mut win = [Self::ZERO; 15];
+        win[0] = *self;
+        for i in 1..8 {
+            let j = i * 2;
+            win[j - 1] = win[i - 1].square();
+            win[j] = win[j - 1] * win[0];
+        }
+
+        // Explore 4-bit chunks of the exponent, high to low. Skip leading
+        // chunks of value 0.
+        let mut z = false;
+        for i in (0..F::N).rev() {
+            let ew = e.as_ref()[i];
+            for j in (0..16).rev() {
+                if z {
+                    self.set_n_square(4);
+                }
+                let c = ((ew >> (j << 2)) & 0x0F) as usize;
+                if c != 0 {
+                    if z {
+                        self.set_mul(&win[c - 1]);
+                    } else {
+                        z = true;
+                        *self = win[c - 1];
+                    }
+                }
+            }
+        }
+        if !z {
+            *self = Self::ONE;
+        }
+    }
+
+    /// Return this value to the provided exponent. The exponent is non-zero
+    /// and is public. The exponent is encoded over N 64-bit limbs.
+    pub fn pow_pubexp(self, e: &F::Words) -> Self {
+        let mut r = self;
+        r.set_pow_pubexp(e);
+        r
+    }
+
+    /// Return this value to the power e. The exponent is considered
+    /// non-secret.
+    pub fn pow_u64_vartime(self, e: u64) -> Self {
+        let mut x = self;
+        x.set_pow_u64_vartime(e);
+        x
+    }
+
+    /// Raise this value to the provided exponent, of any length. The
+    /// exponent is public, and encoded in unsigned little-endian convention
+    /// over 64-bit limbs (e.g. as `Self::ORDER_MINUS_ONE.as_flattened()`).
+    pub fn set_pow_pubexp_limbs(&mut self, e: &[u64]) {
+        let mut n = e.len();
+        while n > 0 && e[n - 1] == 0 {
+            n -= 1;
+        }
+        if n == 0 {
+            *self = Self::ONE;
+            return;
+        }
+        let bitlen = 64 * n - (e[n - 1].leading_zeros() as usize);
+        let bit = |i: usize| ((e[i >> 6] >> (i & 63)) & 1) as usize;
+
+        // Sliding window over w bits; win[i] contains x^(2*i+1)
+        let w = match bitlen {
+            0..=24 => 1,
+            25..=80 => 3,
+            81..=240 => 4,
+            _ => 5,
+        };
+        let mut win = [*self; 16];
+        if w > 1 {
+            let xx = self.square();
+            for i in 1..(1 << (w - 1)) {
+                win[i] = win[i - 1] * xx;
+            }
+        }
+
+        // Explore the exponent from the top bit, which is one. Each window
+        // ends with a one bit.
+        let mut first = true;
+        let mut i = bitlen;
+        while i > 0 {
+            if bit(i - 1) == 0 {
+                self.set_square();
+                i -= 1;
+                continue;
+            }
+            let mut j = i.saturating_sub(w);
+            while bit(j) == 0 {
+                j += 1;
+            }
+            let mut c = 0;
+            for k in (j..i).rev() {
+                c = (c << 1) | bit(k);
+            }
+            if first {
+                *self = win[c >> 1];
+                first = false;
+            } else {
+                self.set_n_square((i - j) as u32);
+                self.set_mul(&win[c >> 1]);
+            }
+            i = j;
+        }
+    }
+
+    /// Return this value to the provided exponent, of any length (see
+    /// `set_pow_pubexp_limbs()`). The exponent is public.
+    pub fn pow_pubexp_limbs(self, e: &[u64]) -> Self {
+        let mut r = self;
+        r.set_pow_pubexp_limbs(e);
+        r
+    }
+
+    /// Get the "hash" of the value. For x = x0 + i*x1, this is:
+    ///    (hashcode(x0) << 1) | (hashcode(x1) & 1)
+    /// i.e. bit 0 is bit 0 of x1, and bits 1..63 are bits 0..62 of x0
+    /// (both in Montgomery representation).
+    pub fn hashcode(self) -> u64 {
+        (self.x0.hashcode() << 1) | (self.x1.hashcode() & 1)
+    }
+
+    /// Return a wrapper displaying this value as "x0 + x1*i" with decimal
+    /// integers x0 and x1 in the [0..(p-1)] range, e.g. with
+    /// `format!("{}", x.decimal())`.
+    pub fn decimal(self) -> Decimal<Self> {
+        Decimal(self)
+    }
+
+    /// Compare this value with `rhs`, lexicographically on x1 and then x0,
+    /// each as integers in the [0..(p-1)] range. This is the order used by
+    /// the `Ord` implementation.
+    ///
+    /// This function is NOT constant-time, and shall be used only on public
+    /// values (e.g. to sort public j-invariants).
+    pub fn cmp_vartime(self, rhs: &Self) -> Ordering {
+        // Encodings are little-endian, with the same length.
+        let cmp = |a: F, b: F| {
+            let (ea, eb) = (a.encode(), b.encode());
+            ea.as_ref().iter().rev().cmp(eb.as_ref().iter().rev())
+        };
+        cmp(self.x1, rhs.x1).then_with(|| cmp(self.x0, rhs.x0))
+    }
+
+    pub fn batch_invert(xx: &mut [Self]) {
+        // We use Montgomery's trick:
+        //   1/u = v*(1/(u*v))
+        //   1/v = u*(1/(u*v))
+        // Applied recursively on n elements, this computes an inversion
+        // with a single inversion in the field, and 3*(n-1) multiplications.
+        // We use batches of 200 elements; larger batches only yield
+        // moderate improvements, while sticking to a fixed moderate batch
+        // size allows stack-based allocation.
+        let n = xx.len();
+        let mut i = 0;
+        while i < n {
+            let blen = if (n - i) > 200 { 200 } else { n - i };
+            let mut tt = [Self::ZERO; 200];
+            tt[0] = xx[i];
+            let zz0 = tt[0].is_zero();
+            tt[0].set_cond(&Self::ONE, zz0);
+            for j in 1..blen {
+                tt[j] = xx[i + j];
+                tt[j].set_cond(&Self::ONE, tt[j].is_zero());
+                tt[j] *= tt[j - 1];
+            }
+            let mut k = Self::ONE / tt[blen - 1];
+            for j in (1..blen).rev() {
+                let mut x = xx[i + j];
+                let zz = x.is_zero();
+                x.set_cond(&Self::ONE, zz);
+                xx[i + j].set_cond(&(k * tt[j - 1]), !zz);
+                k *= x;
+            }
+            xx[i].set_cond(&k, !zz0);
+            i += blen;
+        }
+    }
+
+    /// Precompute an array of indicies to optimally compute the look-up table for
+    /// discrete log computations for elements of order 2^n. The indices are
+    /// written in increasing order in `dd`, and their number is returned.
+    /// Panics if `dd` has less than `dlp_table_capacity(n)` entries.
+    pub fn precompute_dlp_table_index(n: usize, dd: &mut [usize]) -> usize {
+        // TODO: this may not be the fastest method when this table is pre-computed on
+        // the fly, and this should be experimented with in the future.
+        fn dlp_table_index_inner(dd: &mut [usize], len: &mut usize, base: usize, n: usize) {
+            // Insert base, keeping dd[..len] sorted and without duplicates.
+            let mut i = 0;
+            while i < *len && dd[i] < base {
+                i += 1;
+            }
+            if i == *len || dd[i] != base {
+                assert!(*len < dd.len(), "DLP table capacity is too small");
+                dd.copy_within(i..*len, i + 1);
+                dd[i] = base;
+                *len += 1;
+            }
+            if n == 1 {
+                return;
+            }
+            let n0 = n >> 1;
+            let n1 = n - n0;
+            dlp_table_index_inner(dd, len, base + n1, n0);
+            dlp_table_index_inner(dd, len, base + n0, n1);
+        }
+
+        let mut len = 0;
+        dlp_table_index_inner(dd, &mut len, 0, n);
+        len
+    }
+
+    /// Compute `gpp[j] = g^(2^dlog_table[j])` for all `j`, with `g` being
+    /// self. Returned value is `0xFFFFFFFF` if g has order exactly
+    /// 2^n, with n the value used to compute `dlog_table`, or
+    /// `0x00000000` otherwise.
+    pub fn precompute_dlp_values(self, dlog_table: &[usize], gpp: &mut [Self]) -> u32 {
+        gpp[0] = self;
+        let mut j = 1;
+        let mut g = self;
+        let mut lg = 0;
+        while j < gpp.len() {
+            g.set_square();
+            lg += 1;
+            if lg == dlog_table[j] {
+                gpp[j] = g;
+                j += 1;
+            }
+        }
+
+        // Ensure that that g has indeed order exactly n.
+        g.equals(&Self::MINUS_ONE)
+    }
+
+    /// Precompute two vectors of values used to optimally solve the dlog
+    /// for elements of order 2^n exactly.
+    ///
+    /// Explicitly, this involves computing:
+    /// - A table dlog_table of indicies corresponding to where to split
+    ///   the dlog recursively of type Vec<usize>
+    /// - A table of Fp2 elements `gpp[j] = g^(2^dlog_table[j])` of type
+    ///   of type Vec<Self>
+    ///
+    /// Note that the first value (gpp[0]) is g itself, and the last one must
+    /// be -1 (otherwise, g does not have order exactly 2^e).
+    #[cfg(feature = "alloc")]
+    pub fn precompute_dlp_tables(self, n: usize) -> (Vec<usize>, Vec<Self>, u32) {
+        // First compute a table of indicies, we will compute and store
+        // the values g^(2^dlog_table[j])
+        let mut dlog_table = vec![0; crate::fp2_gen::dlp_table_capacity(n)];
+        let len = Self::precompute_dlp_table_index(n, &mut dlog_table);
+        dlog_table.truncate(len);
+
+        // Compute g^(2^dlog_table[j])
+        let mut gpp = vec![Self::ZERO; len];
+        let ok = self.precompute_dlp_values(&dlog_table, &mut gpp);
+
+        (dlog_table, gpp, ok)
+    }
+
+    /// Fixed-capacity variant of `precompute_dlp_tables`, which does not
+    /// allocate. The tables are returned in arrays of `T` entries, which
+    /// MUST be at least `dlp_table_capacity(n)`; unused entries are
+    /// padded with `usize::MAX` and zero, respectively.
+    pub fn precompute_dlp_tables_fixed<const T: usize>(
+        self,
+        n: usize,
+    ) -> ([usize; T], [Self; T], u32) {
+        let mut dlog_table = [usize::MAX; T];
+        let len = Self::precompute_dlp_table_index(n, &mut dlog_table);
+        let mut gpp = [Self::ZERO; T];
+        let ok = self.precompute_dlp_values(&dlog_table[..len], &mut gpp[..len]);
+        (dlog_table, gpp, ok)
+    }
+
+    /// Inner function for solving DLP with order 2^e.
+    /// If gk = -1, then base is self; otherwise, it is gpp[gk]
+    /// (equal to self^(2^dlog_table[gk])). Order of the base is 2^lg.
+    /// Output (of size lg bits) is written in v[], starting at offset
+    /// voff (counted in bits). The target bit values MUST be all zero
+    /// initially in v[] (non-target bits are not modified).
+    /// Returned value is `0xFFFFFFFF` on success, `x00000000` on error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_dlp_n_inner(
+        self,
+        gpp: &[Self],
+        gk: usize,
+        x: &Self,
+        v: &mut [u8],
+        voff: usize,
+        e: usize,
+        dlog_table: &[usize],
+    ) -> u32 {
+        let lg = e - dlog_table[gk];
+
+        // At the deepest recursion level, lg = 1, g = -1,
+        // and x = 1 or -1.
+        if lg == 1 {
+            let hz = x.x1.is_zero();
+            let lp = x.x0.equals(&F::ONE);
+            let ln = x.x0.equals(&F::MINUS_ONE);
+            v[voff >> 3] |= ((ln & 1) << (voff & 7)) as u8;
+            return hz & (lp | ln);
+        }
+
+        // Split lg = lg0 + lg1.
+        // Precomputed indices (in dlog_table) assume that the split is
+        // done such that lg0 = floor(lg/2).
+        let lg0 = lg >> 1;
+        let lg1 = lg - lg0;
+
+        // Solve for v0.
+        //   g' = g^(2^lg1)
+        //   x' = x^(2^lg1)
+        let mut gk0 = gk + 1;
+        while dlog_table[gk0] != e - lg0 {
+            gk0 += 1;
+        }
+        let mut x0 = *x;
+        for _ in 0..lg1 {
+            x0.set_square();
+        }
+        let ok0 = self.solve_dlp_n_inner(gpp, gk0, &x0, v, voff, e, dlog_table);
+
+        // Solve for v1.
+        //   g' = g^(2^lg0)
+        //   x' = x/g^v0
+        let mut gk1 = gk + 1;
+        while dlog_table[gk1] != e - lg1 {
+            gk1 += 1;
+        }
+        let mut x1 = gpp[gk].conjugate();
+        x1.set_pow_ext(v, voff, lg0);
+        x1 *= x;
+        let ok1 = self.solve_dlp_n_inner(gpp, gk1, &x1, v, voff + lg0, e, dlog_table);
+
+        ok0 & ok1
+    }
+
+    // Method: consider g, x and lg such that:
+    //   g has multiplicative order 2^lg
+    //   x = g^v for some v (in the 0 to 2^lg-1 range)
+    // If lg = 1 then g = -1, and x = 0 or -1.
+    //   -> if g != -1, or x is not 0 or -1, then the input is
+    //      erroneous and we can report it
+    // If lg > 1:
+    //   Let lg0 = floor(lg / 2) and lg1 = lg - lg0.
+    //   Let v = v0 + (2^lg0)*v1
+    //   Then:
+    //      x^(2^lg1) = (g^(2^lg1))^v0
+    //   We get v0 with a recursive call on base g^(2^lg1) and
+    //   value x^(2^lg1). Once we have v0:
+    //      x/g^v0 = (g^(2^lg0))^v1
+    //   Another recursive call on base g^(2^lg0) and value
+    //   x/g^v0 yields v1, from which we easily obtain v.
+    //   Note that 1/g = conj(g), since g is a 2n-th root of 1.
+    //
+    // We avoid recomputing the same g^(2^lg) values by keeping
+    // the relevant values in a local array; the important indices
+    // are the ones specified in the dlog_table array.
+    //
+    // We use the function precompute_dlp_table to precompute the
+    // values g^(2^lg), keeping the relevant values
+    // in the gpp[] array. We have:
+    //    gpp[j] = g^(2^dlog_table[j])
+    // Note that the first value (gpp[0]) is g itself, and the
+    // last one must be -1 (otherwise, g does not have order
+    // exactly n).
+
+    /// Find integer v (modulo 2^e) such that x = self^v. If self
+    /// has order exactly 2^e, and there is a solution v, then this
+    /// function returns (v, 0xFFFFFFFF). If self does not have order
+    /// exactly 2^e (including if self^(2^(e-1)) = 1, i.e. the order of
+    /// self is a strict divisor or 2^e), or if there is no solution,
+    /// then this function returns (0, 0).
+    ///
+    /// Optionally include precomputed values from the method precompute_dlp_tables
+    /// otherwise these are computed at runtime.
+    #[cfg(feature = "alloc")]
+    pub fn solve_dlp_2e(
+        self,
+        x: &Self,
+        e: usize,
+        precomputed_tables: Option<(&Vec<usize>, &Vec<Self>)>,
+    ) -> (Vec<u8>, u32) {
+        // If a user has supplied the precomputations, use them, otherwise
+        // compute them at runtime.
+        let computed;
+        let (dlog_table, gpp, ok0) = match precomputed_tables {
+            Some((exps, values)) => (exps, values, u32::MAX),
+            None => {
+                computed = self.precompute_dlp_tables(e);
+                (&computed.0, &computed.1, computed.2)
+            }
+        };
+
+        // Apply the recursion.
+        let mut v = vec![0u8; (e + 7) >> 3];
+        let ok1 = self.solve_dlp_n_inner(gpp, 0, x, &mut v, 0, e, dlog_table);
+        (v, ok0 & ok1)
+    }
+
+    /// Fixed-capacity variant of `solve_dlp_2e`, which does not allocate.
+    /// The tables have `T` entries, as output by
+    /// `precompute_dlp_tables_fixed`, and the solution is returned over
+    /// `L` bytes, which MUST be at least `(e + 7) >> 3`.
+    pub fn solve_dlp_2e_fixed<const T: usize, const L: usize>(
+        self,
+        x: &Self,
+        e: usize,
+        precomputed_tables: Option<(&[usize; T], &[Self; T])>,
+    ) -> ([u8; L], u32) {
+        assert!(L >= (e + 7) >> 3, "DLP output length is too small");
+        let computed;
+        let (dlog_table, gpp, ok0) = match precomputed_tables {
+            Some((exps, values)) => (exps, values, u32::MAX),
+            None => {
+                computed = self.precompute_dlp_tables_fixed::<T>(e);
+                (&computed.0, &computed.1, computed.2)
+            }
+        };
+
+        let mut v = [0u8; L];
+        let ok1 = self.solve_dlp_n_inner(gpp, 0, x, &mut v, 0, e, dlog_table);
+        (v, ok0 & ok1)
+    }
+}
+
+// Trait methods cannot be called in const contexts, and limbs are specific
+// to the base fields of this crate, hence these are only available there.
+impl<P: FieldParams<N>, const N: usize, const SOP: bool> Fp2<Fp<P, N>, SOP> {
+    /// p - 1, over `N` limbs.
+    pub const P_MINUS_ONE: [u64; N] = Self::const_p_plus(-1);
+
+    /// p + 1, over `N` limbs (p is not 2^(64*N) - 1, which is a multiple
+    /// of 3).
+    pub const P_PLUS_ONE: [u64; N] = Self::const_p_plus(1);
+
+    /// The order p^2 - 1 of the multiplicative group, over `2*N` limbs:
+    /// use `Self::ORDER_MINUS_ONE.as_flattened()` as exponent.
+    pub const ORDER_MINUS_ONE: [[u64; N]; 2] = Self::order_cofactor(0);
+
+    // Return p + 1 or p - 1.
+    const fn const_p_plus(d: i32) -> [u64; N] {
+        let mut r = P::MODULUS;
+        let mut i = 0;
+        while i < N {
+            let (x, c) = if d > 0 {
+                r[i].overflowing_add(1)
+            } else {
+                r[i].overflowing_sub(1)
+            };
+            r[i] = x;
+            if !c {
+                break;
+            }
+            i += 1;
+        }
+        r
+    }
+
+    /// Return the cofactor (p^2 - 1)/2^e, over `2*N` limbs (low limbs
+    /// first), e.g. to clear the 2-torsion part of an element with
+    /// `x.pow_pubexp_limbs(c.as_flattened())`. Evaluation fails (at compile
+    /// time in a constant) if 2^e does not divide p^2 - 1.
+    pub const fn order_cofactor(e: u32) -> [[u64; N]; 2] {
+        // p^2 - 1 = (p - 1)*(p + 1)
+        let a = Self::const_p_plus(-1);
+        let b = Self::const_p_plus(1);
+        let mut r = [[0u64; N]; 2];
+        let mut i = 0;
+        while i < N {
+            let mut hi = 0u64;
+            let mut j = 0;
+            while j < N {
+                let k = i + j;
+                let z = (a[i] as u128) * (b[j] as u128) + (r[k / N][k % N] as u128) + (hi as u128);
+                r[k / N][k % N] = z as u64;
+                hi = (z >> 64) as u64;
+                j += 1;
+            }
+            let k = i + N;
+            r[k / N][k % N] = hi;
+            i += 1;
+        }
+
+        // Shift right by e bits, which must all be zero.
+        let mut s = 0;
+        while s < e {
+            assert!(r[0][0] & 1 == 0, "2^e does not divide p^2 - 1");
+            let mut k = 0;
+            while k < 2 * N {
+                let mut x = r[k / N][k % N] >> 1;
+                if k + 1 < 2 * N {
+                    x |= r[(k + 1) / N][(k + 1) % N] << 63;
+                }
+                r[k / N][k % N] = x;
+                k += 1;
+            }
+            s += 1;
+        }
+        r
+    }
+
+    /// The non-square Z = k + i of GF(p^2) used by `sqrt_ratio()`, with the
+    /// smallest k >= 1 such that k^2 + 1 (its norm) is not a square in
+    /// GF(p).
+    pub const NON_SQUARE: Self = Self {
+        x0: <Fp<P, N>>::const_from_u64(Self::NON_SQUARE_K),
+        x1: <Fp<P, N>>::ONE,
+    };
+
+    const NON_SQUARE_K: u64 = {
+        let mut k = 1;
+        while crate::utils64::jacobi_u64(k * k + 1, &P::MODULUS) != -1 {
+            k += 1;
+        }
+        k
+    };
+
+    // A square root of -N(Z) = -(k^2 + 1), which is a square as both -1 and
+    // N(Z) are not.
+    const NON_SQUARE_C: Fp<P, N> = <Fp<P, N>>::MINUS_ONE
+        .const_mul(<Fp<P, N>>::const_from_u64(
+            Self::NON_SQUARE_K * Self::NON_SQUARE_K + 1,
+        ))
+        .const_pow(&crate::addchain::sqrt_exponent(&P::MODULUS));
+
+    /// Compute a square root of `u/v`, as `sqrt_ratio` in RFC 9380 with
+    /// Z = `Self::NON_SQUARE`. If `v` is non-zero and `u/v` is a square,
+    /// then this returns `(y, 0xFFFFFFFF)` with `y^2 = u/v`; otherwise, this
+    /// returns `(y, 0x00000000)` with `y^2 = Z*u/v` (and `y = 0` if
+    /// `v = 0`). In both cases, `y` is the root whose sign is 0 (as in
+    /// `sqrt()`).
+    ///
+    /// This costs two exponentiations in GF(p), as `set_sqrt()`, and no
+    /// inversion. The constants for Z are computed at compile time
+    /// (slowly for very large moduli) when this function is used.
+    pub fn sqrt_ratio(u: &Self, v: &Self) -> (Self, u32) {
+        Self::sqrt_ratio_inner(u, v, Some((&Self::NON_SQUARE, &Self::NON_SQUARE_C)))
+    }
+
+    // Return the exponent (p^2-1)/g of the K-th power residue symbol, with
+    // g = gcd(K, p^2-1), then g, and (p-1)/g if g divides p-1.
+    const fn residue_exponent(k: u64) -> ([[u64; N]; 2], u64, Option<[u64; N]>) {
+        assert!(k > 0, "power residue symbols are defined for K > 0");
+        let mut e = Self::ORDER_MINUS_ONE;
+        let g = crate::utils64::gcd_u64(k, crate::utils64::mod_small(e.as_flattened(), k));
+        crate::utils64::div_small(e.as_flattened_mut(), g);
+        let mut e1 = Self::P_MINUS_ONE;
+        if crate::utils64::div_small(&mut e1, g) == 0 {
+            (e, g, Some(e1))
+        } else {
+            (e, g, None)
+        }
+    }
+
+    /// Return the `K`-th power residue symbol of this value, i.e.
+    /// x^((p^2-1)/g) with g = gcd(K, p^2-1): this is a g-th root of unity,
+    /// which is 1 exactly when x is a non-zero `K`-th power, and zero when
+    /// x is zero. The exponent is computed at compile time.
+    ///
+    /// When g divides p - 1 (e.g. for `K = 2`), this is computed in GF(p)
+    /// on the norm x^(p+1) of x; otherwise, e.g. for `K = 3` or `K = 4`, this
+    /// is an exponentiation in GF(p^2).
+    pub fn power_residue_symbol<const K: u64>(self) -> Self {
+        let (e, g, e1) = const { Self::residue_exponent(K) };
+        if g == 1 {
+            // All elements are K-th powers.
+            return Self::select(&Self::ONE, &Self::ZERO, self.is_zero());
+        }
+        if let Some(e1) = e1 {
+            // x^((p^2-1)/g) = (x^(p+1))^((p-1)/g)
+            let n = self.x0.square() + self.x1.square();
+            return Self {
+                x0: n.pow_pubexp(&e1),
+                x1: <Fp<P, N>>::ZERO,
+            };
+        }
+        self.pow_pubexp_limbs(e.as_flattened())
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a `K`-th power in GF(p^2)
+    /// (zero included) and `0x00000000` otherwise, see
+    /// `power_residue_symbol()`. For `K = 2`, this is `is_square()`.
+    pub fn is_nth_power<const K: u64>(self) -> u32 {
+        let (_, g, _) = const { Self::residue_exponent(K) };
+        if g == 2 {
+            return self.is_square();
+        }
+        self.power_residue_symbol::<K>().equals(&Self::ONE) | self.is_zero()
+    }
+
+    /// The largest e such that 2^e divides p^2 - 1, i.e. k + 1 for
+    /// p = c*2^k - 1 with c odd.
+    pub const TWO_ADICITY: u32 = {
+        let a = Self::P_MINUS_ONE;
+        let b = Self::P_PLUS_ONE;
+        let mut e = 0;
+        let mut i = 0;
+        while a[i] == 0 {
+            i += 1;
+        }
+        e += i as u32 * 64 + a[i].trailing_zeros();
+        i = 0;
+        while b[i] == 0 {
+            i += 1;
+        }
+        e + i as u32 * 64 + b[i].trailing_zeros()
+    };
+
+    /// A generator of the (cyclic) subgroup of order 2^`TWO_ADICITY` of the
+    /// multiplicative group, e.g. as a base for `solve_dlp_2e()`. It is
+    /// chosen such that its power of order 4 is `ZETA`. This is computed at
+    /// compile time (slowly for very large moduli) when used.
+    pub const TWO_ADIC_GENERATOR: Self = {
+        // With Z = NON_SQUARE and p + 1 = c*2^k, Z^((p-1)/2) is a square
+        // root of conj(Z)^2/N(Z), i.e. +/- i*u with u = conj(Z)/C and C a
+        // square root of -N(Z) in GF(p); hence g = i*u^c is
+        // +/- Z^((p^2-1)/2^(k+1)), which generates the subgroup as Z is not
+        // a square.
+        let (z, y) = Self::two_adic_steps(Self::TWO_ADIC_STEP3, 3);
+        let g = Self {
+            x0: <Fp<P, N>>::ZERO.const_sub(y.x1),
+            x1: y.x0,
+        };
+
+        // g^(2^(k-1)) is z (or -z if k = 2), which is ZETA or -ZETA; in the
+        // latter case, use conj(g) = g^p instead, as p = -1 mod 4.
+        let z = if Self::TWO_ADICITY == 3 {
+            Self {
+                x0: <Fp<P, N>>::ZERO.const_sub(z.x0),
+                x1: <Fp<P, N>>::ZERO.const_sub(z.x1),
+            }
+        } else {
+            z
+        };
+        if z.const_equals(&Self::ZETA) {
+            g
+        } else {
+            Self {
+                x0: g.x0,
+                x1: <Fp<P, N>>::ZERO.const_sub(g.x1),
+            }
+        }
+    };
+
+    // u = conj(Z)/C, with 1/C = (-N(Z))^((p-3)/4).
+    const TWO_ADIC_BASE: Self = {
+        let d = <Fp<P, N>>::MINUS_ONE
+            .const_mul(<Fp<P, N>>::const_from_u64(
+                Self::NON_SQUARE_K * Self::NON_SQUARE_K + 1,
+            ))
+            .const_pow(&crate::addchain::progenitor_exponent(&P::MODULUS));
+        Self {
+            x0: <Fp<P, N>>::const_from_u64(Self::NON_SQUARE_K).const_mul(d),
+            x1: <Fp<P, N>>::ZERO.const_sub(d),
+        }
+    };
+
+    // Raising u to (p+1)/2 = c*2^(k-1) with square-and-multiply yields
+    // y = u^c after the top bits, then z = y^(2^(k-1)). The bits are
+    // processed a quarter at a time, in separate constants, so that none
+    // of them is much longer to evaluate than an exponentiation in GF(p)
+    // (as NON_SQUARE_C).
+    const TWO_ADIC_STEP1: (Self, Self) = Self::two_adic_steps((Self::ONE, Self::ONE), 0);
+    const TWO_ADIC_STEP2: (Self, Self) = Self::two_adic_steps(Self::TWO_ADIC_STEP1, 1);
+    const TWO_ADIC_STEP3: (Self, Self) = Self::two_adic_steps(Self::TWO_ADIC_STEP2, 2);
+
+    // Process the t-th quarter of the bits of (p+1)/2, from the state
+    // (u^e, y) for the previous bits e.
+    const fn two_adic_steps(state: (Self, Self), t: usize) -> (Self, Self) {
+        let mut e = Self::P_PLUS_ONE;
+        let mut i = 0;
+        while i < N - 1 {
+            e[i] = (e[i] >> 1) | (e[i + 1] << 63);
+            i += 1;
+        }
+        e[N - 1] >>= 1;
+        let n = N * 64 - e[N - 1].leading_zeros() as usize;
+        let k1 = Self::TWO_ADICITY as usize - 2;
+
+        let (mut r, mut y) = state;
+        let mut i = n * (4 - t) / 4;
+        while i > n * (3 - t) / 4 {
+            i -= 1;
+            r = r.const_square();
+            if (e[i >> 6] >> (i & 63)) & 1 != 0 {
+                r = r.const_mul(Self::TWO_ADIC_BASE);
+            }
+            if i == k1 {
+                y = r;
+            }
+        }
+        (r, y)
+    }
+
+    /// Return an element of order exactly 2^`j` (a primitive 2^`j`-th root
+    /// of unity), computed as a power of `TWO_ADIC_GENERATOR`, with
+    /// `root_of_unity(1) = -1` and `root_of_unity(2) = ZETA`. The value
+    /// `j` is considered public and MUST be at most `TWO_ADICITY` (panics
+    /// otherwise).
+    pub fn root_of_unity(j: u32) -> Self {
+        assert!(j <= Self::TWO_ADICITY, "2^j does not divide p^2 - 1");
+        let mut r = Self::TWO_ADIC_GENERATOR;
+        for _ in j..Self::TWO_ADICITY {
+            r.set_square();
+        }
+        r
+    }
+
+    /// The two primitive cube roots of unity (-1 + sqrt(3)*i)/2 and its
+    /// square (-1 - sqrt(3)*i)/2, with sqrt(3) = 3^((p+1)/4), which are in
+    /// GF(p^2) but not in GF(p) when 3 divides p + 1. Evaluation fails (at
+    /// compile time) otherwise.
+    pub const CUBE_ROOTS_OF_UNITY: [Self; 2] = {
+        assert!(
+            crate::utils64::mod_small(&Self::P_PLUS_ONE, 3) == 0,
+            "3 does not divide p + 1"
+        );
+
+        // As p = 3 mod 4 and p = 2 mod 3, 3 is a square in GF(p).
+        let h = <Fp<P, N>>::MINUS_ONE.const_half();
+        let s = <Fp<P, N>>::const_from_u64(3)
+            .const_pow(&crate::addchain::sqrt_exponent(&P::MODULUS))
+            .const_half();
+        [
+            Self { x0: h, x1: s },
+            Self {
+                x0: h,
+                x1: <Fp<P, N>>::ZERO.const_sub(s),
+            },
+        ]
+    };
+
+    /// Return `self * rhs`, for use in const contexts.
+    pub const fn const_mul(self, rhs: Self) -> Self {
+        Self {
+            x0: self
+                .x0
+                .const_mul(rhs.x0)
+                .const_sub(self.x1.const_mul(rhs.x1)),
+            x1: self
+                .x0
+                .const_mul(rhs.x1)
+                .const_add(self.x1.const_mul(rhs.x0)),
+        }
+    }
+
+    /// Return `self^2`, for use in const contexts.
+    pub const fn const_square(self) -> Self {
+        Self {
+            x0: self
+                .x0
+                .const_add(self.x1)
+                .const_mul(self.x0.const_sub(self.x1)),
+            x1: self.x0.const_add(self.x0).const_mul(self.x1),
+        }
+    }
+
+    /// Return `true` if `self` is equal to `rhs`, for use in const contexts.
+    pub const fn const_equals(self, rhs: &Self) -> bool {
+        self.x0.const_equals(&rhs.x0) && self.x1.const_equals(&rhs.x1)
+    }
+
+    /// Decode an element from bytes, no check is made that the input
+    /// value is reduced except that the buffer is of the excpected
+    /// length of `Self::ENCODED_LENGTH` (handled within the Fp decode).
+    pub const fn const_decode_no_check(x0_buf: &[u8], x1_buf: &[u8]) -> Self {
+        let x0 = <Fp<P, N>>::const_decode_no_check(x0_buf);
+        let x1 = <Fp<P, N>>::const_decode_no_check(x1_buf);
+        Self { x0, x1 }
+    }
+
+    /// Return the Montgomery representations of x0 and x1 such that
+    /// self = x0 + i*x1 (see `Fp::to_montgomery_limbs()`).
+    pub fn to_montgomery_limbs(self) -> ([u64; N], [u64; N]) {
+        (self.x0.to_montgomery_limbs(), self.x1.to_montgomery_limbs())
+    }
+
+    /// Build the element x0 + i*x1 from the Montgomery representations of
+    /// x0 and x1 (see `Fp::from_montgomery_limbs()`). Returned values are
+    /// the element and `0xFFFFFFFF` on success, or the zero element and
+    /// `0x00000000` if either component is not less than p.
+    pub fn from_montgomery_limbs(x0: &[u64; N], x1: &[u64; N]) -> (Self, u32) {
+        let (x0, c0) = <Fp<P, N>>::from_montgomery_limbs(x0);
+        let (x1, c1) = <Fp<P, N>>::from_montgomery_limbs(x1);
+        let mut r = Self { x0, x1 };
+        let cc = c0 & c1;
+        r.set_cond(&Self::ZERO, !cc);
+        (r, cc)
+    }
+
+    /// Return the canonical integers x0 and x1 in the [0..(p-1)] range such
+    /// that self = x0 + i*x1 (see `Fp::to_canonical_limbs()`).
+    pub fn to_canonical_limbs(self) -> ([u64; N], [u64; N]) {
+        (self.x0.to_canonical_limbs(), self.x1.to_canonical_limbs())
+    }
+
+    /// Build the element x0 + i*x1 from integers x0 and x1 in the
+    /// [0..(p-1)] range (see `Fp::from_canonical_limbs()`). Returned values
+    /// are the element and `0xFFFFFFFF` on success, or the zero element and
+    /// `0x00000000` if either integer is not less than p.
+    pub fn from_canonical_limbs(x0: &[u64; N], x1: &[u64; N]) -> (Self, u32) {
+        let (x0, c0) = <Fp<P, N>>::from_canonical_limbs(x0);
+        let (x1, c1) = <Fp<P, N>>::from_canonical_limbs(x1);
+        let mut r = Self { x0, x1 };
+        let cc = c0 & c1;
+        r.set_cond(&Self::ZERO, !cc);
+        (r, cc)
+    }
+}
+
+// ========================================================================
+
+// All formats write x0 + i*x1 as "x0 + x1*i", which can be pasted in Sage.
+// The width, fill, alignment and `0` flag apply to each of x0 and x1. As for
+// Fp, Debug writes the value unless the `redact-debug` feature is enabled.
+impl<F: Fp2BaseField, const SOP: bool> fmt::Display for Fp2<F, SOP> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.x0, f)?;
+        f.write_str(" + ")?;
+        fmt::Display::fmt(&self.x1, f)?;
+        f.write_str("*i")
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> fmt::LowerHex for Fp2<F, SOP> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_hex(self.x0.encode().as_ref(), f, false, f.alternate())?;
+        f.write_str(" + ")?;
+        fmt_hex(self.x1.encode().as_ref(), f, false, f.alternate())?;
+        f.write_str("*i")
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> fmt::UpperHex for Fp2<F, SOP> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_hex(self.x0.encode().as_ref(), f, true, f.alternate())?;
+        f.write_str(" + ")?;
+        fmt_hex(self.x1.encode().as_ref(), f, true, f.alternate())?;
+        f.write_str("*i")
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> fmt::Display for Decimal<Fp2<F, SOP>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_decimal(self.0.x0.encode(), f)?;
+        f.write_str(" + ")?;
+        fmt_decimal(self.0.x1.encode(), f)?;
+        f.write_str("*i")
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> fmt::Debug for Fp2<F, SOP> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if cfg!(feature = "redact-debug") {
+            f.write_str("Fp2(<redacted>)")
+        } else {
+            write!(f, "Fp2({})", self)
+        }
+    }
+}
+
+// Equality is constant-time. Hashes use the canonical encoding, so that
+// they do not depend on the internal representation.
+impl<F: Fp2BaseField, const SOP: bool> PartialEq for Fp2<F, SOP> {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other) != 0
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> Eq for Fp2<F, SOP> {}
+
+impl<F: Fp2BaseField, const SOP: bool> Hash for Fp2<F, SOP> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.encode().as_ref());
+    }
+}
+
+// The order is NOT constant-time, see cmp_vartime().
+impl<F: Fp2BaseField, const SOP: bool> PartialOrd for Fp2<F, SOP> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> Ord for Fp2<F, SOP> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_vartime(other)
+    }
+}
+
+/*
+ * Implementations of from methods from simple integer types
+ */
+
+impl<F: Fp2BaseField, const SOP: bool> From<F> for Fp2<F, SOP> {
+    fn from(x: F) -> Fp2<F, SOP> {
+        Self::new(&x, &F::ZERO)
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> From<u64> for Fp2<F, SOP> {
+    fn from(x: u64) -> Fp2<F, SOP> {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x);
+        r
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> From<i64> for Fp2<F, SOP> {
+    fn from(x: i64) -> Fp2<F, SOP> {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x);
+        r
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> From<u32> for Fp2<F, SOP> {
+    fn from(x: u32) -> Fp2<F, SOP> {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x);
+        r
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> From<i32> for Fp2<F, SOP> {
+    fn from(x: i32) -> Fp2<F, SOP> {
+        let mut r = Self::ZERO;
+        r.x0 = F::from(x);
+        r
+    }
+}
+
+macro_rules! impl_fp2_binop {
+    ($tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident, $set:ident) => {
+        impl<F: Fp2BaseField, const SOP: bool> $tr<Fp2<F, SOP>> for Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: Fp2<F, SOP>) -> Fp2<F, SOP> {
+                let mut r = self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<&Fp2<F, SOP>> for Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: &Fp2<F, SOP>) -> Fp2<F, SOP> {
+                let mut r = self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<Fp2<F, SOP>> for &Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: Fp2<F, SOP>) -> Fp2<F, SOP> {
+                let mut r = *self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<&Fp2<F, SOP>> for &Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: &Fp2<F, SOP>) -> Fp2<F, SOP> {
+                let mut r = *self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr_assign<Fp2<F, SOP>> for Fp2<F, SOP> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: Fp2<F, SOP>) {
+                self.$set(&other);
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr_assign<&Fp2<F, SOP>> for Fp2<F, SOP> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: &Fp2<F, SOP>) {
+                self.$set(other);
+            }
+        }
+    };
+}
+
+impl_fp2_binop!(Add, add, AddAssign, add_assign, set_add);
+impl_fp2_binop!(Sub, sub, SubAssign, sub_assign, set_sub);
+impl_fp2_binop!(Mul, mul, MulAssign, mul_assign, set_mul);
+impl_fp2_binop!(Div, div, DivAssign, div_assign, set_div);
+
+impl<F: Fp2BaseField, const SOP: bool> Neg for Fp2<F, SOP> {
+    type Output = Fp2<F, SOP>;
+
+    #[inline(always)]
+    fn neg(self) -> Fp2<F, SOP> {
+        let mut r = self;
+        r.set_neg();
+        r
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> Neg for &Fp2<F, SOP> {
+    type Output = Fp2<F, SOP>;
+
+    #[inline(always)]
+    fn neg(self) -> Fp2<F, SOP> {
+        let mut r = *self;
+        r.set_neg();
+        r
+    }
+}
+
+// Operators with elements of the base field, on the right.
+macro_rules! impl_fp2_base_binop {
+    ($tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident, $set:ident) => {
+        impl<F: Fp2BaseField, const SOP: bool> $tr<F> for Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: F) -> Fp2<F, SOP> {
+                let mut r = self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<&F> for Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: &F) -> Fp2<F, SOP> {
+                let mut r = self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<F> for &Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: F) -> Fp2<F, SOP> {
+                let mut r = *self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<&F> for &Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, other: &F) -> Fp2<F, SOP> {
+                let mut r = *self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr_assign<F> for Fp2<F, SOP> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: F) {
+                self.$set(&other);
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr_assign<&F> for Fp2<F, SOP> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: &F) {
+                self.$set(other);
+            }
+        }
+    };
+}
+
+impl_fp2_base_binop!(Add, add, AddAssign, add_assign, set_add_base);
+impl_fp2_base_binop!(Sub, sub, SubAssign, sub_assign, set_sub_base);
+impl_fp2_base_binop!(Mul, mul, MulAssign, mul_assign, set_mul_base);
+
+// Operators with elements of the base field on the left can only be
+// implemented for the base fields of this crate: k + x = x + k,
+// k - x = -(x - k) and k * x = x * k.
+macro_rules! impl_fp2_lhs {
+    ($t:ty, [$($gen:tt)*], $F:ty) => {
+        impl<$($gen)*, const SOP: bool> Add<Fp2<$F, SOP>> for $t {
+            type Output = Fp2<$F, SOP>;
+
+            #[inline(always)]
+            fn add(self, x: Fp2<$F, SOP>) -> Fp2<$F, SOP> {
+                x + self
+            }
+        }
+
+        impl<$($gen)*, const SOP: bool> Add<&Fp2<$F, SOP>> for $t {
+            type Output = Fp2<$F, SOP>;
+
+            #[inline(always)]
+            fn add(self, x: &Fp2<$F, SOP>) -> Fp2<$F, SOP> {
+                x + self
+            }
+        }
+
+        impl<$($gen)*, const SOP: bool> Sub<Fp2<$F, SOP>> for $t {
+            type Output = Fp2<$F, SOP>;
+
+            #[inline(always)]
+            fn sub(self, x: Fp2<$F, SOP>) -> Fp2<$F, SOP> {
+                -(x - self)
+            }
+        }
+
+        impl<$($gen)*, const SOP: bool> Sub<&Fp2<$F, SOP>> for $t {
+            type Output = Fp2<$F, SOP>;
+
+            #[inline(always)]
+            fn sub(self, x: &Fp2<$F, SOP>) -> Fp2<$F, SOP> {
+                -(x - self)
+            }
+        }
+
+        impl<$($gen)*, const SOP: bool> Mul<Fp2<$F, SOP>> for $t {
+            type Output = Fp2<$F, SOP>;
+
+            #[inline(always)]
+            fn mul(self, x: Fp2<$F, SOP>) -> Fp2<$F, SOP> {
+                x * self
+            }
+        }
+
+        impl<$($gen)*, const SOP: bool> Mul<&Fp2<$F, SOP>> for $t {
+            type Output = Fp2<$F, SOP>;
+
+            #[inline(always)]
+            fn mul(self, x: &Fp2<$F, SOP>) -> Fp2<$F, SOP> {
+                x * self
+            }
+        }
+    };
+}
+
+impl_fp2_lhs!(Fp<P, N>, [P: FieldParams<N>, const N: usize], Fp<P, N>);
+impl_fp2_lhs!(&Fp<P, N>, [P: FieldParams<N>, const N: usize], Fp<P, N>);
+impl_fp2_lhs!(i32, [F: Fp2BaseField], F);
+impl_fp2_lhs!(u64, [F: Fp2BaseField], F);
+
+// Operators with integers, on the right. As for Fp, multiplications by an
+// i32 use mul_small(), and u64 operands are converted into base field
+// elements, which keeps these operations constant-time in the integer.
+impl<F: Fp2BaseField, const SOP: bool> Fp2<F, SOP> {
+    #[inline(always)]
+    fn set_add_int<T>(&mut self, k: T)
+    where
+        F: From<T>,
+    {
+        self.x0 += F::from(k);
+    }
+
+    #[inline(always)]
+    fn set_sub_int<T>(&mut self, k: T)
+    where
+        F: From<T>,
+    {
+        self.x0 -= F::from(k);
+    }
+
+    #[inline(always)]
+    fn set_mul_u64(&mut self, k: u64) {
+        self.set_mul_base(&F::from(k));
+    }
+}
+
+macro_rules! impl_fp2_int_binop {
+    ($t:ty, $tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident, $set:ident) => {
+        impl<F: Fp2BaseField, const SOP: bool> $tr<$t> for Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, k: $t) -> Fp2<F, SOP> {
+                let mut r = self;
+                r.$set(k);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr<$t> for &Fp2<F, SOP> {
+            type Output = Fp2<F, SOP>;
+
+            #[inline(always)]
+            fn $f(self, k: $t) -> Fp2<F, SOP> {
+                let mut r = *self;
+                r.$set(k);
+                r
+            }
+        }
+
+        impl<F: Fp2BaseField, const SOP: bool> $tr_assign<$t> for Fp2<F, SOP> {
+            #[inline(always)]
+            fn $f_assign(&mut self, k: $t) {
+                self.$set(k);
+            }
+        }
+    };
+}
+
+impl_fp2_int_binop!(i32, Add, add, AddAssign, add_assign, set_add_int);
+impl_fp2_int_binop!(i32, Sub, sub, SubAssign, sub_assign, set_sub_int);
+impl_fp2_int_binop!(i32, Mul, mul, MulAssign, mul_assign, set_mul_small);
+impl_fp2_int_binop!(u64, Add, add, AddAssign, add_assign, set_add_int);
+impl_fp2_int_binop!(u64, Sub, sub, SubAssign, sub_assign, set_sub_int);
+impl_fp2_int_binop!(u64, Mul, mul, MulAssign, mul_assign, set_mul_u64);
+
+impl<F: Fp2BaseField, const SOP: bool> Sum for Fp2<F, SOP> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a, F: Fp2BaseField, const SOP: bool> Sum<&'a Fp2<F, SOP>> for Fp2<F, SOP> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> Product for Fp2<F, SOP> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<'a, F: Fp2BaseField, const SOP: bool> Product<&'a Fp2<F, SOP>> for Fp2<F, SOP> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> crate::traits::Fq for Fp2<F, SOP> {
+    // Reexport constants for base field Trait
+    const N: usize = F::N;
+    const ENCODED_LENGTH: usize = Self::ENCODED_LENGTH;
+    type Encoding = F::Fp2Encoding;
+    type Words = F::Words;
+    const ZERO: Self = Self::ZERO;
+    const ONE: Self = Self::ONE;
+    const TWO: Self = Self::TWO;
+    const THREE: Self = Self::THREE;
+    const FOUR: Self = Self::FOUR;
+    const MINUS_ONE: Self = Self::MINUS_ONE;
+
+    fn is_zero(self) -> u32 {
+        self.is_zero()
+    }
+    fn equals(self, rhs: &Self) -> u32 {
+        self.equals(rhs)
+    }
+
+    fn set_neg(&mut self) {
+        self.set_neg()
+    }
+    fn set_half(&mut self) {
+        self.set_half()
+    }
+    fn set_mul2(&mut self) {
+        self.set_mul2()
+    }
+    fn set_mul3(&mut self) {
+        self.set_mul3()
+    }
+    fn set_mul4(&mut self) {
+        self.set_mul4()
+    }
+    fn set_mul8(&mut self) {
+        self.set_mul8()
+    }
+
+    fn half(self) -> Self {
+        self.half()
+    }
+    fn mul2(self) -> Self {
+        self.mul2()
+    }
+    fn mul3(self) -> Self {
+        self.mul3()
+    }
+    fn mul4(self) -> Self {
+        self.mul4()
+    }
+    fn mul8(self) -> Self {
+        self.mul8()
+    }
+
+    fn set_mul_small(&mut self, k: i32) {
+        self.set_mul_small(k)
+    }
+    fn set_square(&mut self) {
+        self.set_square()
+    }
+    fn set_n_square(&mut self, n: u32) {
+        self.set_n_square(n)
+    }
+    fn set_invert(&mut self) {
+        self.set_invert()
+    }
+    fn mul_small(self, k: i32) -> Self {
+        self.mul_small(k)
+    }
+    fn square(self) -> Self {
+        self.square()
+    }
+    fn n_square(self, n: u32) -> Self {
+        self.n_square(n)
+    }
+    fn invert(self) -> Self {
+        self.invert()
+    }
+    fn set_sqrt(&mut self) -> u32 {
+        self.set_sqrt()
+    }
+    fn sqrt(self) -> (Self, u32) {
+        self.sqrt()
+    }
+    fn sgn0(self) -> u32 {
+        self.sgn0()
+    }
+    fn is_lexicographically_largest(self) -> u32 {
+        self.is_lexicographically_largest()
+    }
+    fn legendre(self) -> i32 {
+        self.legendre()
+    }
+
+    fn is_square(self) -> u32 {
+        self.is_square()
+    }
+    fn batch_invert(xx: &mut [Self]) {
+        <Fp2<F, SOP>>::batch_invert(xx)
+    }
+
+    fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+        self.set_select(a, b, ctl)
+    }
+    fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+        self.set_cond(rhs, ctl)
+    }
+    fn set_cond_neg(&mut self, ctl: u32) {
+        self.set_cond_neg(ctl)
+    }
+    fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+        <Fp2<F, SOP>>::select(a, b, ctl)
+    }
+    fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+        <Fp2<F, SOP>>::cond_swap(a, b, ctl)
+    }
+
+    fn encode(self) -> F::Fp2Encoding {
+        self.encode()
+    }
+    fn decode(buf: &[u8]) -> (Self, u32) {
+        <Fp2<F, SOP>>::decode(buf)
+    }
+    fn decode_reduce(buf: &[u8]) -> Self {
+        <Fp2<F, SOP>>::decode_reduce(buf)
+    }
+
+    fn hashcode(self) -> u64 {
+        self.hashcode()
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> crate::traits::FqExp for Fp2<F, SOP> {
+    fn set_pow(&mut self, e: &[u8], ebitlen: usize) {
+        self.set_pow(e, ebitlen)
+    }
+    fn set_pow_ext(&mut self, e: &[u8], eoff: usize, ebitlen: usize) {
+        self.set_pow_ext(e, eoff, ebitlen)
+    }
+    fn set_pow_u64(&mut self, e: u64, ebitlen: usize) {
+        self.set_pow_u64(e, ebitlen)
+    }
+    fn set_pow_u64_vartime(&mut self, e: u64) {
+        self.set_pow_u64_vartime(e)
+    }
+    fn set_pow_pubexp(&mut self, e: &F::Words) {
+        self.set_pow_pubexp(e)
+    }
+    fn pow(self, e: &[u8], ebitlen: usize) -> Self {
+        self.pow(e, ebitlen)
+    }
+    fn pow_ext(self, e: &[u8], eoff: usize, ebitlen: usize) -> Self {
+        self.pow_ext(e, eoff, ebitlen)
+    }
+    fn pow_u64(self, e: u64, ebitlen: usize) -> Self {
+        self.pow_u64(e, ebitlen)
+    }
+    fn pow_u64_vartime(self, e: u64) -> Self {
+        self.pow_u64_vartime(e)
+    }
+    fn pow_pubexp(self, e: &F::Words) -> Self {
+        self.pow_pubexp(e)
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> crate::traits::FqRoots for Fp2<F, SOP> {
+    fn set_fourth_root(&mut self) -> u32 {
+        self.set_fourth_root()
+    }
+    fn fourth_root(self) -> (Self, u32) {
+        self.fourth_root()
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> crate::traits::FqRnd for Fp2<F, SOP> {
+    fn set_rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(&mut self, rng: &mut R) {
+        self.set_rand(rng)
+    }
+    fn rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(rng: &mut R) -> Self {
+        <Fp2<F, SOP>>::rand(rng)
+    }
+}
+
+impl<F: Fp2BaseField, const SOP: bool> crate::traits::Fp2 for Fp2<F, SOP> {
+    type BaseField = F;
+
+    // Reexport constants for Trait
+    const ZETA: Self = Self::ZETA;
+    const MINUS_ZETA: Self = Self::MINUS_ZETA;
+
+    /// Return the x0 value such that self = x0 + i*x1
+    fn x0(self) -> F {
+        self.x0()
+    }
+
+    /// Return the x1 value such that self = x0 + i*x1
+    fn x1(self) -> F {
+        self.x1()
+    }
+
+    /// Return the x0 and x1 values such that self = x0 + i*x1
+    fn xi(self) -> (F, F) {
+        self.xi()
+    }
+
+    /// Return the value x0 + i*x1 for a given two integers of type `i32`.
+    fn from_i32_pair(x0: i32, x1: i32) -> Self {
+        <Fp2<F, SOP>>::from_i32_pair(x0, x1)
+    }
+
+    /// Return the value x0 + i*x1 for a given two integers of type `u32`.
+    fn from_u32_pair(x0: u32, x1: u32) -> Self {
+        <Fp2<F, SOP>>::from_u32_pair(x0, x1)
+    }
+
+    /// Return the value x0 + i*x1 for a given two integers of type `i64`.
+    fn from_i64_pair(x0: i64, x1: i64) -> Self {
+        <Fp2<F, SOP>>::from_i64_pair(x0, x1)
+    }
+
+    /// Return the value x0 + i*x1 for a given two integers of type `u64`.
+    fn from_u64_pair(x0: u64, x1: u64) -> Self {
+        <Fp2<F, SOP>>::from_u64_pair(x0, x1)
+    }
+
+    /// Set the "real" component of self to an integer of type `i32` in place.
+    fn set_x0_small(&mut self, x: i32) {
+        self.set_x0_small(x)
+    }
+
+    /// Set the "imaginary" component of self to an integer of type `i32` in place.
+    fn set_x1_small(&mut self, x: i32) {
+        self.set_x1_small(x)
+    }
+
+    fn set_conjugate(&mut self) {
+        self.set_conjugate();
+    }
+
+    fn conjugate(self) -> Self {
+        self.conjugate()
+    }
+
+    fn is_square_base_field(self) -> u32 {
+        self.is_square_base_field()
+    }
+
+    #[cfg(feature = "alloc")]
+    fn precompute_dlp_tables(self, n: usize) -> (Vec<usize>, Vec<Self>, u32) {
+        self.precompute_dlp_tables(n)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn solve_dlp_2e(
+        self,
+        x: &Self,
+        e: usize,
+        precomputed_tables: Option<(&Vec<usize>, &Vec<Self>)>,
+    ) -> (Vec<u8>, u32) {
+        self.solve_dlp_2e(x, e, precomputed_tables)
+    }
+
+    fn precompute_dlp_tables_fixed<const T: usize>(self, n: usize) -> ([usize; T], [Self; T], u32) {
+        self.precompute_dlp_tables_fixed(n)
+    }
+
+    fn solve_dlp_2e_fixed<const T: usize, const L: usize>(
+        self,
+        x: &Self,
+        e: usize,
+        precomputed_tables: Option<(&[usize; T], &[Self; T])>,
+    ) -> ([u8; L], u32) {
+        self.solve_dlp_2e_fixed(x, e, precomputed_tables)
+    }
+}
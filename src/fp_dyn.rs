@@ -0,0 +1,1228 @@
+//! Finite fields GF(p) and GF(p^2) whose modulus is only known at runtime.
+//!
+//! The types generated by `define_fp_core` and `define_fp2_from_type` fix the
+//! modulus at compile time, which is what we want for implementations of a
+//! given protocol, but not for experiments over thousands of candidate primes.
+//! Here, a context `FpCtx` holds the modulus together with the Montgomery
+//! constants derived from it, all computed at runtime, and the elements
+//! `FpDyn` of GF(p) and `Fp2Dyn` of GF(p^2) = GF(p)[i]/(i^2 + 1) borrow this
+//! context. The maximum size of the modulus is set by the number of 64-bit
+//! words `N`, given as a const generic.
+//!
+//! Elements use the same Montgomery representation (with R = 2^(64*N)) and the
+//! same encodings as the types generated by `define_fp_core` with the same
+//! number of words. All functions are constant-time with regards to the
+//! values of the elements; the modulus is considered public. Inversions and
+//! Legendre symbols are computed with exponentiations.
+//!
+//! Since the field is not known at compile time, these types cannot implement
+//! the `Fq` family of traits (which require, e.g., constants `Fq::ZERO`), but
+//! they provide methods with the same names and semantics, as well as all the
+//! arithmetic operators. Combining elements from distinct contexts is a logic
+//! error, which is only caught by debug assertions.
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::addchain;
+use crate::fp::Precomputed;
+use crate::traits::SignConvention;
+use crate::utils64::{addcarry_u64, is_probable_prime, ninv64, subborrow_u64};
+
+/// The modulus p of a finite field GF(p) over `N` words, and the constants
+/// derived from it.
+#[derive(Clone, Debug)]
+pub struct FpCtx<const N: usize> {
+    modulus: [u64; N],
+    // -1/p mod 2^64
+    p0i: u64,
+    // R = 2^(64*N) mod p and R^2 mod p
+    r: [u64; N],
+    r2: [u64; N],
+    // Exponents (p + 1)/4, p - 2 and (p - 1)/2 for the square roots,
+    // inversions and Legendre symbols.
+    sqrt_exp: [u64; N],
+    inv_exp: [u64; N],
+    legendre_exp: [u64; N],
+    bit_length: usize,
+    encoded_length: usize,
+}
+
+impl<const N: usize> FpCtx<N> {
+    /// Create the context for the modulus p, given over `N` words in base
+    /// 2^64 (little-endian order). This returns `None` for the moduli which
+    /// `define_fp_core` rejects: p must span at least two words (`N >= 2`
+    /// and a non-zero top word), and be a prime p = 3 mod 4. Primality is
+    /// checked with `utils64::is_probable_prime`, at all sizes.
+    pub fn new(modulus: &[u64; N]) -> Option<Self> {
+        if N < 2 || modulus[N - 1] == 0 || modulus[0] & 3 != 3 || !is_probable_prime(modulus) {
+            return None;
+        }
+
+        // The Montgomery constants are the ones of the static types.
+        let pc = Precomputed::new(modulus);
+        let bit_length = 64 * N - (modulus[N - 1].leading_zeros() as usize);
+        Some(Self {
+            modulus: *modulus,
+            p0i: ninv64(modulus[0]),
+            r: pc.r,
+            r2: pc.r2,
+            sqrt_exp: pc.sqrt_exp,
+            inv_exp: addchain::inverse_exponent(modulus),
+            legendre_exp: addchain::legendre_exponent(modulus),
+            bit_length,
+            encoded_length: (bit_length + 7) >> 3,
+        })
+    }
+
+    /// Return the modulus p.
+    pub fn modulus(&self) -> &[u64; N] {
+        &self.modulus
+    }
+
+    /// Return the bit length of the modulus p.
+    pub fn bit_length(&self) -> usize {
+        self.bit_length
+    }
+
+    /// Return the length in bytes of the encoding of an element of GF(p).
+    pub fn encoded_length(&self) -> usize {
+        self.encoded_length
+    }
+
+    // Set a to a + b mod p. Both a and b MUST be less than p.
+    #[inline]
+    fn add_mod(&self, a: &mut [u64; N], b: &[u64; N]) {
+        let mut cc = 0u8;
+        for i in 0..N {
+            (a[i], cc) = addcarry_u64(a[i], b[i], cc);
+        }
+        self.cond_sub_mod(a, cc);
+    }
+
+    // Set a to a - p if a + 2^(64*N)*hi is at least p. The value MUST be less
+    // than 2*p.
+    #[inline]
+    fn cond_sub_mod(&self, a: &mut [u64; N], hi: u8) {
+        let mut t = [0u64; N];
+        let mut bb = 0u8;
+        for i in 0..N {
+            (t[i], bb) = subborrow_u64(a[i], self.modulus[i], bb);
+        }
+        // Keep a when the subtraction borrowed beyond hi.
+        let (_, bb) = subborrow_u64(hi as u64, 0, bb);
+        let m = (bb as u64).wrapping_neg();
+        for i in 0..N {
+            a[i] = t[i] ^ (m & (a[i] ^ t[i]));
+        }
+    }
+
+    // Montgomery multiplication: return a*b/R mod p. At least one of a and b
+    // MUST be less than p, and the other less than 2^(64*N).
+    #[inline]
+    fn mmul(&self, a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+        let p = &self.modulus;
+        let mut d = [0u64; N];
+        let mut dh = 0u64;
+        for &bj in b.iter() {
+            let f = a[0]
+                .wrapping_mul(bj)
+                .wrapping_add(d[0])
+                .wrapping_mul(self.p0i) as u128;
+            let bj = bj as u128;
+            let mut cc1 = 0u128;
+            let mut cc2 = 0u128;
+            for i in 0..N {
+                let z = (a[i] as u128) * bj + (d[i] as u128) + cc1;
+                cc1 = z >> 64;
+                let z = (p[i] as u128) * f + ((z as u64) as u128) + cc2;
+                cc2 = z >> 64;
+                if i > 0 {
+                    d[i - 1] = z as u64;
+                }
+            }
+            let z = (dh as u128) + cc1 + cc2;
+            d[N - 1] = z as u64;
+            dh = (z >> 64) as u64;
+        }
+
+        // The result is less than 2*p.
+        self.cond_sub_mod(&mut d, dh as u8);
+        d
+    }
+}
+
+/// An element of the finite field GF(p), for a modulus p given at runtime
+/// through a context `FpCtx`. Contents are opaque.
+/// All functions are constant-time.
+#[derive(Clone, Copy, Debug)]
+pub struct FpDyn<'a, const N: usize> {
+    ctx: &'a FpCtx<N>,
+    // Montgomery representation x*R mod p of the element x
+    v: [u64; N],
+}
+
+impl<'a, const N: usize> FpDyn<'a, N> {
+    /// Return the zero element.
+    pub fn zero(ctx: &'a FpCtx<N>) -> Self {
+        Self { ctx, v: [0u64; N] }
+    }
+
+    /// Return the element 1.
+    pub fn one(ctx: &'a FpCtx<N>) -> Self {
+        Self { ctx, v: ctx.r }
+    }
+
+    /// Return the element -1.
+    pub fn minus_one(ctx: &'a FpCtx<N>) -> Self {
+        -Self::one(ctx)
+    }
+
+    /// Return the element x (reduced modulo p).
+    pub fn from_u64(ctx: &'a FpCtx<N>, x: u64) -> Self {
+        let mut v = [0u64; N];
+        v[0] = x;
+        Self {
+            ctx,
+            v: ctx.mmul(&v, &ctx.r2),
+        }
+    }
+
+    /// Return the element x (reduced modulo p).
+    pub fn from_i64(ctx: &'a FpCtx<N>, x: i64) -> Self {
+        let mut r = Self::from_u64(ctx, x.unsigned_abs());
+        r.set_cond_neg(((x >> 63) as u64) as u32);
+        r
+    }
+
+    /// Return the context of this element.
+    pub fn ctx(&self) -> &'a FpCtx<N> {
+        self.ctx
+    }
+
+    #[inline(always)]
+    fn check_ctx(&self, rhs: &Self) {
+        debug_assert!(
+            core::ptr::eq(self.ctx, rhs.ctx),
+            "elements from distinct contexts"
+        );
+    }
+
+    // Return the integer x in the [0..p-1] range.
+    #[inline]
+    fn to_canonical(self) -> [u64; N] {
+        let mut one = [0u64; N];
+        one[0] = 1;
+        self.ctx.mmul(&self.v, &one)
+    }
+
+    /// Return `0xFFFFFFFF` if this value is zero, or `0x00000000` otherwise.
+    pub fn is_zero(self) -> u32 {
+        let mut t = 0u64;
+        for i in 0..N {
+            t |= self.v[i];
+        }
+        (((t | t.wrapping_neg()) >> 63) as u32).wrapping_sub(1)
+    }
+
+    /// Return `0xFFFFFFFF` if this value is equal to rhs, or `0x00000000`
+    /// otherwise.
+    pub fn equals(self, rhs: &Self) -> u32 {
+        self.check_ctx(rhs);
+        let mut t = 0u64;
+        for i in 0..N {
+            t |= self.v[i] ^ rhs.v[i];
+        }
+        (((t | t.wrapping_neg()) >> 63) as u32).wrapping_sub(1)
+    }
+
+    #[inline]
+    fn set_add(&mut self, rhs: &Self) {
+        self.check_ctx(rhs);
+        self.ctx.add_mod(&mut self.v, &rhs.v);
+    }
+
+    #[inline]
+    fn set_sub(&mut self, rhs: &Self) {
+        self.check_ctx(rhs);
+        let mut bb = 0u8;
+        for i in 0..N {
+            (self.v[i], bb) = subborrow_u64(self.v[i], rhs.v[i], bb);
+        }
+        // Add back p on underflow.
+        let m = (bb as u64).wrapping_neg();
+        let mut cc = 0u8;
+        for i in 0..N {
+            (self.v[i], cc) = addcarry_u64(self.v[i], self.ctx.modulus[i] & m, cc);
+        }
+    }
+
+    /// Negate this value.
+    pub fn set_neg(&mut self) {
+        let mut r = Self::zero(self.ctx);
+        r.set_sub(self);
+        *self = r;
+    }
+
+    #[inline]
+    fn set_mul(&mut self, rhs: &Self) {
+        self.check_ctx(rhs);
+        self.v = self.ctx.mmul(&self.v, &rhs.v);
+    }
+
+    /// Replace this value with its square.
+    pub fn set_square(&mut self) {
+        self.v = self.ctx.mmul(&self.v, &self.v);
+    }
+
+    /// Compute the square of this value.
+    pub fn square(self) -> Self {
+        let mut r = self;
+        r.set_square();
+        r
+    }
+
+    /// Square this value n times in place.
+    pub fn set_n_square(&mut self, n: u32) {
+        for _ in 0..n {
+            self.set_square();
+        }
+    }
+
+    /// Compute the square of this value n times.
+    pub fn n_square(self, n: u32) -> Self {
+        let mut r = self;
+        r.set_n_square(n);
+        r
+    }
+
+    /// Halve this value.
+    pub fn set_half(&mut self) {
+        // Add p if the value is odd, then shift.
+        let m = (self.v[0] & 1).wrapping_neg();
+        let mut cc = 0u8;
+        for i in 0..N {
+            (self.v[i], cc) = addcarry_u64(self.v[i], self.ctx.modulus[i] & m, cc);
+        }
+        for i in 0..(N - 1) {
+            self.v[i] = (self.v[i] >> 1) | (self.v[i + 1] << 63);
+        }
+        self.v[N - 1] = (self.v[N - 1] >> 1) | ((cc as u64) << 63);
+    }
+
+    /// Compute the half of this value.
+    pub fn half(self) -> Self {
+        let mut r = self;
+        r.set_half();
+        r
+    }
+
+    /// Double this value.
+    pub fn set_mul2(&mut self) {
+        let r = *self;
+        self.set_add(&r);
+    }
+
+    /// Compute the sum of this value with itself.
+    pub fn mul2(self) -> Self {
+        let mut r = self;
+        r.set_mul2();
+        r
+    }
+
+    /// Multiply this value by a small signed integer.
+    pub fn set_mul_small(&mut self, k: i32) {
+        let r = Self::from_i64(self.ctx, k as i64);
+        self.set_mul(&r);
+    }
+
+    /// Compute the product of this value by a small signed integer `k`.
+    pub fn mul_small(self, k: i32) -> Self {
+        let mut r = self;
+        r.set_mul_small(k);
+        r
+    }
+
+    /// Set this value to `a` or `b`, if `ctl` is `0x00000000` or
+    /// `0xFFFFFFFF`, respectively.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+        a.check_ctx(b);
+        let m = (ctl as u64) | ((ctl as u64) << 32);
+        for i in 0..N {
+            self.v[i] = a.v[i] ^ (m & (a.v[i] ^ b.v[i]));
+        }
+        self.ctx = a.ctx;
+    }
+
+    /// Return `a` or `b`, if `ctl` is `0x00000000` or `0xFFFFFFFF`,
+    /// respectively.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+        let mut r = *a;
+        r.set_select(a, b, ctl);
+        r
+    }
+
+    /// Set this value to `rhs` if `ctl` is `0xFFFFFFFF`; leave it unchanged
+    /// if `ctl` is `0x00000000`.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+        let a = *self;
+        self.set_select(&a, rhs, ctl);
+    }
+
+    /// Negate this value if `ctl` is `0xFFFFFFFF`; leave it unchanged if
+    /// `ctl` is `0x00000000`.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn set_cond_neg(&mut self, ctl: u32) {
+        let r = -*self;
+        self.set_cond(&r, ctl);
+    }
+
+    /// Exchange the values of `a` and `b` if `ctl` is `0xFFFFFFFF`; leave
+    /// both values unchanged if `ctl` is `0x00000000`.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+        a.check_ctx(b);
+        let m = (ctl as u64) | ((ctl as u64) << 32);
+        for i in 0..N {
+            let t = m & (a.v[i] ^ b.v[i]);
+            a.v[i] ^= t;
+            b.v[i] ^= t;
+        }
+    }
+
+    /// Raise this value to the power e, given over 64-bit words in
+    /// little-endian order. The exponent is considered non-secret.
+    pub fn set_pow_pubexp(&mut self, e: &[u64]) {
+        let x = *self;
+        *self = Self::one(self.ctx);
+        for i in (0..(64 * e.len())).rev() {
+            self.set_square();
+            if (e[i >> 6] >> (i & 63)) & 1 != 0 {
+                self.set_mul(&x);
+            }
+        }
+    }
+
+    /// Return this value to the power e, given over 64-bit words in
+    /// little-endian order. The exponent is considered non-secret.
+    pub fn pow_pubexp(self, e: &[u64]) -> Self {
+        let mut r = self;
+        r.set_pow_pubexp(e);
+        r
+    }
+
+    /// Raise this value to the power `e`. Exponent `e` is encoded in
+    /// unsigned little-endian convention over exactly `ebitlen` bits.
+    pub fn set_pow(&mut self, e: &[u8], ebitlen: usize) {
+        let x = *self;
+        *self = Self::one(self.ctx);
+        for i in (0..ebitlen).rev() {
+            self.set_square();
+            let y = *self * x;
+            let ctl = (((e[i >> 3] >> (i & 7)) as u32) & 1).wrapping_neg();
+            self.set_cond(&y, ctl);
+        }
+    }
+
+    /// Return this value to the power `e` (as a new element). Exponent `e`
+    /// is encoded in unsigned little-endian convention over exactly
+    /// `ebitlen` bits.
+    pub fn pow(self, e: &[u8], ebitlen: usize) -> Self {
+        let mut r = self;
+        r.set_pow(e, ebitlen);
+        r
+    }
+
+    /// Replace this value with its inverse. The inverse of zero is zero.
+    pub fn set_invert(&mut self) {
+        let ctx = self.ctx;
+        self.set_pow_pubexp(&ctx.inv_exp);
+    }
+
+    /// Compute the inverse of this value. The inverse of zero is zero.
+    pub fn invert(self) -> Self {
+        let mut r = self;
+        r.set_invert();
+        r
+    }
+
+    /// Legendre symbol on this value. Return value is:
+    /// -  0   if this value is zero
+    /// - +1   if this value is a non-zero quadratic residue
+    /// - -1   if this value is not a quadratic residue
+    pub fn legendre(self) -> i32 {
+        let r = self.pow_pubexp(&self.ctx.legendre_exp);
+        let o = r.equals(&Self::one(self.ctx));
+        let z = r.is_zero();
+        ((o & 1) as i32) - ((!(o | z) & 1) as i32)
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a square in GF(p) and
+    /// `0x00000000` otherwise.
+    pub fn is_square(self) -> u32 {
+        !((self.legendre() >> 1) as u32)
+    }
+
+    /// Set this value to its square root. Returned value is `0xFFFFFFFF` if
+    /// the operation succeeded (value was indeed a quadratic residue), or
+    /// `0x00000000` otherwise. On success, the chosen root is the one whose
+    /// least significant bit (as an integer in `[0..p-1]`) is zero. On
+    /// failure, this value is set to 0.
+    pub fn set_sqrt(&mut self) -> u32 {
+        // Compute x^((p+1)/4)
+        let x = *self;
+        let ctx = self.ctx;
+        self.set_pow_pubexp(&ctx.sqrt_exp);
+
+        // Check whether the square of the result equals the input and zeroize
+        // on failure
+        let r = self.square().equals(&x);
+        self.set_cond(&Self::zero(ctx), !r);
+
+        // Normalise the output so that the LSB is zero
+        let ctl = self.is_odd();
+        self.set_cond_neg(ctl);
+
+        r
+    }
+
+    /// Compute the square root of this value. If this value is indeed a
+    /// quadratic residue, then this returns `(x, 0xFFFFFFFF)`, with `x` being
+    /// the (unique) square root of this value whose least significant bit
+    /// is zero (when normalized to an integer in `[0..p-1]`). If this value
+    /// is not a quadratic residue, then this returns (zero, `0x00000000`).
+    pub fn sqrt(self) -> (Self, u32) {
+        let mut r = self;
+        let ok = r.set_sqrt();
+        (r, ok)
+    }
+
+    /// Return `0xFFFFFFFF` if this value, as an integer in `[0..p-1]`, is
+    /// odd, or `0x00000000` otherwise.
+    pub fn is_odd(self) -> u32 {
+        ((self.to_canonical()[0] as u32) & 1).wrapping_neg()
+    }
+
+    /// Return `0xFFFFFFFF` if the sign of this value is 1 for `sgn0` of
+    /// RFC 9380, i.e. if it is odd, or `0x00000000` otherwise.
+    pub fn sgn0(self) -> u32 {
+        self.is_odd()
+    }
+
+    /// Return `0xFFFFFFFF` if this value, as an integer in `[0..p-1]`, is
+    /// larger than (p-1)/2, or `0x00000000` otherwise.
+    pub fn is_lexicographically_largest(self) -> u32 {
+        // 2*x < p if x <= (p-1)/2; otherwise, 2*x - p is odd.
+        self.mul2().is_odd()
+    }
+
+    /// Return `0xFFFFFFFF` if the sign of this value is 1 for the convention
+    /// `sc`, or `0x00000000` otherwise.
+    pub fn sign(self, sc: SignConvention) -> u32 {
+        match sc {
+            SignConvention::Sgn0 => self.sgn0(),
+            SignConvention::Positive => self.is_lexicographically_largest(),
+        }
+    }
+
+    /// Negate this value if its sign is 1 for the convention `sc`.
+    pub fn set_canonical_sign(&mut self, sc: SignConvention) {
+        let ctl = self.sign(sc);
+        self.set_cond_neg(ctl);
+    }
+
+    /// Compute the square root of this value, as `sqrt()`, but choosing the
+    /// root with the sign convention `sc`.
+    pub fn sqrt_signed(self, sc: SignConvention) -> (Self, u32) {
+        let (mut r, ok) = self.sqrt();
+        r.set_canonical_sign(sc);
+        (r, ok)
+    }
+
+    /// Encode this value into the first `ctx.encoded_length()` bytes of
+    /// `buf`. Encoding uses little-endian, has a fixed size (for a given
+    /// field), and is canonical.
+    pub fn encode_into(self, buf: &mut [u8]) {
+        let x = self.to_canonical();
+        for (i, b) in buf[..self.ctx.encoded_length].iter_mut().enumerate() {
+            *b = (x[i >> 3] >> ((i & 7) << 3)) as u8;
+        }
+    }
+
+    // Set this value to the integer encoded (little-endian) in buf, which
+    // has length at most 8*N. No reduction is performed.
+    #[inline]
+    fn set_bytes_nocheck(&mut self, buf: &[u8]) {
+        self.v = [0u64; N];
+        for (i, &b) in buf.iter().enumerate() {
+            self.v[i >> 3] |= (b as u64) << ((i & 7) << 3);
+        }
+    }
+
+    /// Decode the provided bytes into a field element. Returned values
+    /// are the element and `0xFFFFFFFF` on success, or the zero element and
+    /// `0x00000000` on failure. A failure is reported if the source slice
+    /// does not have exactly the canonical encoding length of a field
+    /// element (`ctx.encoded_length()`), or if the source encodes an
+    /// integer which is not in the `[0..(p-1)]` range.
+    pub fn decode(ctx: &'a FpCtx<N>, buf: &[u8]) -> (Self, u32) {
+        let mut r = Self::zero(ctx);
+        if buf.len() != ctx.encoded_length {
+            return (r, 0);
+        }
+        r.set_bytes_nocheck(buf);
+
+        // Check that the value is less than p.
+        let mut bb = 0u8;
+        for i in 0..N {
+            (_, bb) = subborrow_u64(r.v[i], ctx.modulus[i], bb);
+        }
+        let ok = (bb as u32).wrapping_neg();
+
+        r.v = ctx.mmul(&r.v, &ctx.r2);
+        r.set_cond(&Self::zero(ctx), !ok);
+        (r, ok)
+    }
+
+    /// Decode the provided bytes into a field element. The source slice
+    /// can have arbitrary length; the bytes are interpreted with the
+    /// unsigned little-endian convention (no sign bit), and the resulting
+    /// integer is reduced modulo p.
+    pub fn decode_reduce(ctx: &'a FpCtx<N>, buf: &[u8]) -> Self {
+        // Process the input by chunks of 8*N bytes, from the most
+        // significant one: x <- x*2^(64*N) + chunk, where multiplying by
+        // 2^(64*N) = R is a Montgomery multiplication by R^2.
+        let clen = 8 * N;
+        let mut r = Self::zero(ctx);
+        let mut n = buf.len();
+        let mut d = Self::zero(ctx);
+        while n > 0 {
+            let k = if n.is_multiple_of(clen) {
+                clen
+            } else {
+                n % clen
+            };
+            n -= k;
+            d.set_bytes_nocheck(&buf[n..(n + k)]);
+            d.v = ctx.mmul(&d.v, &ctx.r2);
+            r.v = ctx.mmul(&r.v, &ctx.r2);
+            r.set_add(&d);
+        }
+        r
+    }
+
+    /// Return a new random field element (indistinguishable from uniform
+    /// generation).
+    pub fn rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(
+        ctx: &'a FpCtx<N>,
+        rng: &mut R,
+    ) -> Self {
+        // Reduce a random integer of 128*N bits.
+        let mut r = Self::zero(ctx);
+        let mut d = Self::zero(ctx);
+        for _ in 0..2 {
+            for i in 0..N {
+                d.v[i] = rng.next_u64();
+            }
+            d.v = ctx.mmul(&d.v, &ctx.r2);
+            r.v = ctx.mmul(&r.v, &ctx.r2);
+            r.set_add(&d);
+        }
+        r
+    }
+
+    /// Get the "hash" of the value (low 64 bits of the Montgomery
+    /// representation).
+    pub fn hashcode(self) -> u64 {
+        self.v[0]
+    }
+}
+
+/// An element of the finite field GF(p^2) = GF(p)[i]/(i^2 + 1), for a modulus
+/// p = 3 mod 4 given at runtime through a context `FpCtx`. Contents are opaque.
+/// All functions are constant-time.
+#[derive(Clone, Copy, Debug)]
+pub struct Fp2Dyn<'a, const N: usize> {
+    x0: FpDyn<'a, N>,
+    x1: FpDyn<'a, N>,
+}
+
+impl<'a, const N: usize> Fp2Dyn<'a, N> {
+    /// Return the value x0 + i*x1.
+    pub fn new(x0: &FpDyn<'a, N>, x1: &FpDyn<'a, N>) -> Self {
+        x0.check_ctx(x1);
+        Self { x0: *x0, x1: *x1 }
+    }
+
+    /// Return the zero element.
+    pub fn zero(ctx: &'a FpCtx<N>) -> Self {
+        Self {
+            x0: FpDyn::zero(ctx),
+            x1: FpDyn::zero(ctx),
+        }
+    }
+
+    /// Return the element 1.
+    pub fn one(ctx: &'a FpCtx<N>) -> Self {
+        Self {
+            x0: FpDyn::one(ctx),
+            x1: FpDyn::zero(ctx),
+        }
+    }
+
+    /// Return the element -1.
+    pub fn minus_one(ctx: &'a FpCtx<N>) -> Self {
+        -Self::one(ctx)
+    }
+
+    /// Return the element i, such that i^2 = -1.
+    pub fn zeta(ctx: &'a FpCtx<N>) -> Self {
+        Self {
+            x0: FpDyn::zero(ctx),
+            x1: FpDyn::one(ctx),
+        }
+    }
+
+    /// Return the value x0 + i*x1 for a given two integers of type `i64`.
+    pub fn from_i64_pair(ctx: &'a FpCtx<N>, x0: i64, x1: i64) -> Self {
+        Self {
+            x0: FpDyn::from_i64(ctx, x0),
+            x1: FpDyn::from_i64(ctx, x1),
+        }
+    }
+
+    /// Return the value x0 + i*x1 for a given two integers of type `u64`.
+    pub fn from_u64_pair(ctx: &'a FpCtx<N>, x0: u64, x1: u64) -> Self {
+        Self {
+            x0: FpDyn::from_u64(ctx, x0),
+            x1: FpDyn::from_u64(ctx, x1),
+        }
+    }
+
+    /// Return the context of this element.
+    pub fn ctx(&self) -> &'a FpCtx<N> {
+        self.x0.ctx
+    }
+
+    /// Return the x0 value such that self = x0 + i*x1
+    pub fn x0(self) -> FpDyn<'a, N> {
+        self.x0
+    }
+
+    /// Return the x1 value such that self = x0 + i*x1
+    pub fn x1(self) -> FpDyn<'a, N> {
+        self.x1
+    }
+
+    /// Return the x0 and x1 values such that self = x0 + i*x1
+    pub fn xi(self) -> (FpDyn<'a, N>, FpDyn<'a, N>) {
+        (self.x0, self.x1)
+    }
+
+    /// Return `0xFFFFFFFF` if this value is zero, or `0x00000000` otherwise.
+    pub fn is_zero(self) -> u32 {
+        self.x0.is_zero() & self.x1.is_zero()
+    }
+
+    /// Return `0xFFFFFFFF` if this value is equal to rhs, or `0x00000000`
+    /// otherwise.
+    pub fn equals(self, rhs: &Self) -> u32 {
+        self.x0.equals(&rhs.x0) & self.x1.equals(&rhs.x1)
+    }
+
+    #[inline]
+    fn set_add(&mut self, rhs: &Self) {
+        self.x0.set_add(&rhs.x0);
+        self.x1.set_add(&rhs.x1);
+    }
+
+    #[inline]
+    fn set_sub(&mut self, rhs: &Self) {
+        self.x0.set_sub(&rhs.x0);
+        self.x1.set_sub(&rhs.x1);
+    }
+
+    /// Negate this value.
+    pub fn set_neg(&mut self) {
+        self.x0.set_neg();
+        self.x1.set_neg();
+    }
+
+    /// Negate the imaginary part of this value.
+    pub fn set_conjugate(&mut self) {
+        self.x1.set_neg();
+    }
+
+    /// Compute the complex conjugate of the value a + i*b, i.e. a - i*b.
+    pub fn conjugate(self) -> Self {
+        let mut r = self;
+        r.set_conjugate();
+        r
+    }
+
+    #[inline]
+    fn set_mul(&mut self, rhs: &Self) {
+        // (a + i*b)*(c + i*d) = (a*c - b*d) + i*((a + b)*(c + d) - a*c - b*d)
+        let ac = self.x0 * rhs.x0;
+        let bd = self.x1 * rhs.x1;
+        let t = (self.x0 + self.x1) * (rhs.x0 + rhs.x1);
+        self.x0 = ac - bd;
+        self.x1 = t - ac - bd;
+    }
+
+    /// Replace this value with its square.
+    pub fn set_square(&mut self) {
+        // (a + i*b)^2 = (a + b)*(a - b) + i*(2*a*b)
+        let t = self.x0 * self.x1;
+        self.x0 = (self.x0 + self.x1) * (self.x0 - self.x1);
+        self.x1 = t.mul2();
+    }
+
+    /// Compute the square of this value.
+    pub fn square(self) -> Self {
+        let mut r = self;
+        r.set_square();
+        r
+    }
+
+    /// Square this value n times in place.
+    pub fn set_n_square(&mut self, n: u32) {
+        for _ in 0..n {
+            self.set_square();
+        }
+    }
+
+    /// Compute the square of this value n times.
+    pub fn n_square(self, n: u32) -> Self {
+        let mut r = self;
+        r.set_n_square(n);
+        r
+    }
+
+    /// Halve this value.
+    pub fn set_half(&mut self) {
+        self.x0.set_half();
+        self.x1.set_half();
+    }
+
+    /// Compute the half of this value.
+    pub fn half(self) -> Self {
+        let mut r = self;
+        r.set_half();
+        r
+    }
+
+    /// Double this value.
+    pub fn set_mul2(&mut self) {
+        self.x0.set_mul2();
+        self.x1.set_mul2();
+    }
+
+    /// Compute the sum of this value with itself.
+    pub fn mul2(self) -> Self {
+        let mut r = self;
+        r.set_mul2();
+        r
+    }
+
+    /// Multiply this value by a small signed integer.
+    pub fn set_mul_small(&mut self, k: i32) {
+        self.x0.set_mul_small(k);
+        self.x1.set_mul_small(k);
+    }
+
+    /// Compute the product of this value by a small signed integer `k`.
+    pub fn mul_small(self, k: i32) -> Self {
+        let mut r = self;
+        r.set_mul_small(k);
+        r
+    }
+
+    /// Set this value to `a` or `b`, if `ctl` is `0x00000000` or
+    /// `0xFFFFFFFF`, respectively.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+        self.x0.set_select(&a.x0, &b.x0, ctl);
+        self.x1.set_select(&a.x1, &b.x1, ctl);
+    }
+
+    /// Return `a` or `b`, if `ctl` is `0x00000000` or `0xFFFFFFFF`,
+    /// respectively.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+        let mut r = *a;
+        r.set_select(a, b, ctl);
+        r
+    }
+
+    /// Set this value to `rhs` if `ctl` is `0xFFFFFFFF`; leave it unchanged
+    /// if `ctl` is `0x00000000`.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+        self.x0.set_cond(&rhs.x0, ctl);
+        self.x1.set_cond(&rhs.x1, ctl);
+    }
+
+    /// Negate this value if `ctl` is `0xFFFFFFFF`; leave it unchanged if
+    /// `ctl` is `0x00000000`.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn set_cond_neg(&mut self, ctl: u32) {
+        self.x0.set_cond_neg(ctl);
+        self.x1.set_cond_neg(ctl);
+    }
+
+    /// Exchange the values of `a` and `b` if `ctl` is `0xFFFFFFFF`; leave
+    /// both values unchanged if `ctl` is `0x00000000`.
+    /// The value of `ctl` MUST be either `0x00000000` or `0xFFFFFFFF`.
+    pub fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+        FpDyn::cond_swap(&mut a.x0, &mut b.x0, ctl);
+        FpDyn::cond_swap(&mut a.x1, &mut b.x1, ctl);
+    }
+
+    /// Replace this value with its inverse. The inverse of zero is zero.
+    pub fn set_invert(&mut self) {
+        // 1/(x0 + i*x1) = (x0 - i*x1)/(x0^2 + x1^2)
+        let z = (self.x0.square() + self.x1.square()).invert();
+        self.x0 *= z;
+        self.x1 *= z;
+        self.x1.set_neg();
+    }
+
+    /// Compute the inverse of this value. The inverse of zero is zero.
+    pub fn invert(self) -> Self {
+        let mut r = self;
+        r.set_invert();
+        r
+    }
+
+    /// Legendre symbol on this value. Return value is:
+    /// -  0   if this value is zero
+    /// - +1   if this value is a non-zero quadratic residue
+    /// - -1   if this value is not a quadratic residue
+    pub fn legendre(self) -> i32 {
+        // x = x0 + i*x1 is a square in GF(p^2) if and only if
+        // x0^2 + x1^2 is a square in GF(p).
+        (self.x0.square() + self.x1.square()).legendre()
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a square in GF(p^2) and
+    /// `0x00000000` otherwise.
+    pub fn is_square(self) -> u32 {
+        !((self.legendre() >> 1) as u32)
+    }
+
+    /// Return `0xFFFFFFFF` when this value is a square in GF(p) and
+    /// `0x00000000` otherwise.
+    pub fn is_square_base_field(self) -> u32 {
+        self.x0.is_square() & self.x1.is_zero()
+    }
+
+    /// Set this value to its square root. Returned value is `0xFFFFFFFF` if
+    /// the operation succeeded (value was indeed a quadratic residue), or
+    /// `0x00000000` otherwise. On success, the chosen root is the one whose
+    /// sign is 0 (i.e. if the "real part" is non-zero, then it is an even
+    /// integer; if the "real part" is zero, then the "imaginary part" is
+    /// an even integer). On failure, this value is set to 0.
+    pub fn set_sqrt(&mut self) -> u32 {
+        // Same method as for the types generated by define_fp2_from_type:
+        // with y = y0 + i*y1 and y^2 = x, we have y0^2 = (x0 +/- sqrt(delta))/2
+        // with delta = x0^2 + x1^2, and y1 = x1/(2*y0). When x1 = 0, then
+        // y = sqrt(x0) or y = i*sqrt(-x0).
+        let ctx = self.ctx();
+        let (sqrt_delta, r1) = (self.x0.square() + self.x1.square()).sqrt();
+        let mut y0sq = (self.x0 + sqrt_delta).half();
+        let x1z = self.x1.is_zero();
+        y0sq.set_cond(&self.x0, x1z);
+        let nqr = (y0sq.legendre() >> 1) as u32;
+        y0sq.set_cond_neg(nqr & x1z);
+        y0sq.set_cond(&(y0sq - sqrt_delta), nqr & !x1z);
+        let (mut y0, r2) = y0sq.sqrt();
+        let r = r1 & r2;
+        let mut y1 = self.x1 / y0.mul2();
+        FpDyn::cond_swap(&mut y0, &mut y1, nqr & x1z);
+        self.x0.set_select(&FpDyn::zero(ctx), &y0, r);
+        self.x1.set_select(&FpDyn::zero(ctx), &y1, r);
+
+        // Sign management: negate the result if needed.
+        let ctl = self.sgn0();
+        self.set_cond_neg(ctl);
+        r
+    }
+
+    /// Compute the square root of this value. If this value is indeed a
+    /// quadratic residue, then this returns `(x, 0xFFFFFFFF)`, with `x` being
+    /// the square root of this value whose sign is 0 (see `set_sqrt`). If
+    /// this value is not a quadratic residue, then this returns (zero,
+    /// `0x00000000`).
+    pub fn sqrt(self) -> (Self, u32) {
+        let mut r = self;
+        let ok = r.set_sqrt();
+        (r, ok)
+    }
+
+    /// Return `0xFFFFFFFF` if the sign of this value is 1 for `sgn0` of
+    /// RFC 9380, or `0x00000000` otherwise (see `fp2::Fp2::sgn0()`).
+    pub fn sgn0(self) -> u32 {
+        self.x0.sgn0() | (self.x0.is_zero() & self.x1.sgn0())
+    }
+
+    /// Return `0xFFFFFFFF` if this value is lexicographically largest, or
+    /// `0x00000000` otherwise (see `fp2::Fp2::is_lexicographically_largest()`).
+    pub fn is_lexicographically_largest(self) -> u32 {
+        self.x1.is_lexicographically_largest()
+            | (self.x1.is_zero() & self.x0.is_lexicographically_largest())
+    }
+
+    /// Return `0xFFFFFFFF` if the sign of this value is 1 for the convention
+    /// `sc`, or `0x00000000` otherwise.
+    pub fn sign(self, sc: SignConvention) -> u32 {
+        match sc {
+            SignConvention::Sgn0 => self.sgn0(),
+            SignConvention::Positive => self.is_lexicographically_largest(),
+        }
+    }
+
+    /// Negate this value if its sign is 1 for the convention `sc`.
+    pub fn set_canonical_sign(&mut self, sc: SignConvention) {
+        let ctl = self.sign(sc);
+        self.set_cond_neg(ctl);
+    }
+
+    /// Compute the square root of this value, as `sqrt()`, but choosing the
+    /// root with the sign convention `sc`.
+    pub fn sqrt_signed(self, sc: SignConvention) -> (Self, u32) {
+        let (mut r, ok) = self.sqrt();
+        r.set_canonical_sign(sc);
+        (r, ok)
+    }
+
+    /// Raise this value to the power e, given over 64-bit words in
+    /// little-endian order. The exponent is considered non-secret.
+    pub fn set_pow_pubexp(&mut self, e: &[u64]) {
+        let x = *self;
+        *self = Self::one(self.ctx());
+        for i in (0..(64 * e.len())).rev() {
+            self.set_square();
+            if (e[i >> 6] >> (i & 63)) & 1 != 0 {
+                self.set_mul(&x);
+            }
+        }
+    }
+
+    /// Return this value to the power e, given over 64-bit words in
+    /// little-endian order. The exponent is considered non-secret.
+    pub fn pow_pubexp(self, e: &[u64]) -> Self {
+        let mut r = self;
+        r.set_pow_pubexp(e);
+        r
+    }
+
+    /// Raise this value to the power `e`. Exponent `e` is encoded in
+    /// unsigned little-endian convention over exactly `ebitlen` bits.
+    pub fn set_pow(&mut self, e: &[u8], ebitlen: usize) {
+        let x = *self;
+        *self = Self::one(self.ctx());
+        for i in (0..ebitlen).rev() {
+            self.set_square();
+            let y = *self * x;
+            let ctl = (((e[i >> 3] >> (i & 7)) as u32) & 1).wrapping_neg();
+            self.set_cond(&y, ctl);
+        }
+    }
+
+    /// Return this value to the power `e` (as a new element). Exponent `e`
+    /// is encoded in unsigned little-endian convention over exactly
+    /// `ebitlen` bits.
+    pub fn pow(self, e: &[u8], ebitlen: usize) -> Self {
+        let mut r = self;
+        r.set_pow(e, ebitlen);
+        r
+    }
+
+    /// Encode this value into the first `2*ctx.encoded_length()` bytes of
+    /// `buf`, as the encoding of x0 followed by the encoding of x1.
+    pub fn encode_into(self, buf: &mut [u8]) {
+        let n = self.ctx().encoded_length;
+        self.x0.encode_into(&mut buf[..n]);
+        self.x1.encode_into(&mut buf[n..]);
+    }
+
+    /// Decode this value from bytes and return (val, `0xFFFFFFFF`) if the
+    /// input has length `2*ctx.encoded_length()` and decodes to canonical
+    /// values x0 and x1 in `[0, p-1]`, otherwise return (0, 0).
+    pub fn decode(ctx: &'a FpCtx<N>, buf: &[u8]) -> (Self, u32) {
+        let n = ctx.encoded_length;
+        if buf.len() != 2 * n {
+            return (Self::zero(ctx), 0);
+        }
+        let (x0, c0) = FpDyn::decode(ctx, &buf[..n]);
+        let (x1, c1) = FpDyn::decode(ctx, &buf[n..]);
+        let mut r = Self { x0, x1 };
+        let c = c0 & c1;
+        r.set_cond(&Self::zero(ctx), !c);
+        (r, c)
+    }
+
+    /// Decode the provided bytes into a field element. The source slice
+    /// can have arbitrary length; the first half of the bytes is decoded
+    /// into x0 and the latter half into x1, as for `FpDyn::decode_reduce`.
+    pub fn decode_reduce(ctx: &'a FpCtx<N>, buf: &[u8]) -> Self {
+        let n = buf.len() >> 1;
+        Self {
+            x0: FpDyn::decode_reduce(ctx, &buf[..n]),
+            x1: FpDyn::decode_reduce(ctx, &buf[n..]),
+        }
+    }
+
+    /// Return a new random field element (indistinguishable from uniform
+    /// generation).
+    pub fn rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(
+        ctx: &'a FpCtx<N>,
+        rng: &mut R,
+    ) -> Self {
+        let x0 = FpDyn::rand(ctx, rng);
+        let x1 = FpDyn::rand(ctx, rng);
+        Self { x0, x1 }
+    }
+
+    /// Get the "hash" of the value, i.e. bit 0 is bit 0 of x1, and bits
+    /// 1..63 are bits 0..62 of x0 (both in Montgomery representation).
+    pub fn hashcode(self) -> u64 {
+        (self.x0.hashcode() << 1) | (self.x1.hashcode() & 1)
+    }
+}
+
+impl<const N: usize> fmt::Display for FpDyn<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Write the canonical value in base-16, skipping leading zeros
+        let x = self.to_canonical();
+        f.write_str("0x")?;
+        let mut leading = true;
+        for i in (0..(16 * N)).rev() {
+            let nibble = (x[i >> 4] >> ((i & 15) << 2)) & 0x0F;
+            leading &= nibble == 0;
+            if !leading {
+                write!(f, "{:x}", nibble)?;
+            }
+        }
+        if leading {
+            f.write_str("0")?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Display for Fp2Dyn<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} + {}*i", self.x0, self.x1)
+    }
+}
+
+// Implement a binary operator and its assigning variant, for all
+// combinations of values and references, from the method set_$op.
+macro_rules! impl_dyn_binop {
+    ($typename:ident, $tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident, $set:ident) => {
+        impl<'a, const N: usize> $tr<$typename<'a, N>> for $typename<'a, N> {
+            type Output = $typename<'a, N>;
+
+            #[inline(always)]
+            fn $f(self, other: $typename<'a, N>) -> $typename<'a, N> {
+                let mut r = self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<'a, const N: usize> $tr<&$typename<'a, N>> for $typename<'a, N> {
+            type Output = $typename<'a, N>;
+
+            #[inline(always)]
+            fn $f(self, other: &$typename<'a, N>) -> $typename<'a, N> {
+                let mut r = self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<'a, const N: usize> $tr<$typename<'a, N>> for &$typename<'a, N> {
+            type Output = $typename<'a, N>;
+
+            #[inline(always)]
+            fn $f(self, other: $typename<'a, N>) -> $typename<'a, N> {
+                let mut r = *self;
+                r.$set(&other);
+                r
+            }
+        }
+
+        impl<'a, const N: usize> $tr<&$typename<'a, N>> for &$typename<'a, N> {
+            type Output = $typename<'a, N>;
+
+            #[inline(always)]
+            fn $f(self, other: &$typename<'a, N>) -> $typename<'a, N> {
+                let mut r = *self;
+                r.$set(other);
+                r
+            }
+        }
+
+        impl<'a, const N: usize> $tr_assign<$typename<'a, N>> for $typename<'a, N> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: $typename<'a, N>) {
+                self.$set(&other);
+            }
+        }
+
+        impl<'a, const N: usize> $tr_assign<&$typename<'a, N>> for $typename<'a, N> {
+            #[inline(always)]
+            fn $f_assign(&mut self, other: &$typename<'a, N>) {
+                self.$set(other);
+            }
+        }
+    };
+}
+
+// Division is multiplication by the inverse.
+macro_rules! impl_dyn_ops {
+    ($typename:ident) => {
+        impl<const N: usize> $typename<'_, N> {
+            #[inline]
+            fn set_div(&mut self, rhs: &Self) {
+                self.set_mul(&rhs.invert());
+            }
+        }
+
+        impl_dyn_binop!($typename, Add, add, AddAssign, add_assign, set_add);
+        impl_dyn_binop!($typename, Sub, sub, SubAssign, sub_assign, set_sub);
+        impl_dyn_binop!($typename, Mul, mul, MulAssign, mul_assign, set_mul);
+        impl_dyn_binop!($typename, Div, div, DivAssign, div_assign, set_div);
+
+        impl<'a, const N: usize> Neg for $typename<'a, N> {
+            type Output = $typename<'a, N>;
+
+            #[inline(always)]
+            fn neg(self) -> $typename<'a, N> {
+                let mut r = self;
+                r.set_neg();
+                r
+            }
+        }
+
+        impl<'a, const N: usize> Neg for &$typename<'a, N> {
+            type Output = $typename<'a, N>;
+
+            #[inline(always)]
+            fn neg(self) -> $typename<'a, N> {
+                let mut r = *self;
+                r.set_neg();
+                r
+            }
+        }
+    };
+}
+
+impl_dyn_ops!(FpDyn);
+impl_dyn_ops!(Fp2Dyn);
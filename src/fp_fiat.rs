@@ -0,0 +1,128 @@
+//! Finite fields Fp with p = 3 mod 4 whose Montgomery arithmetic is supplied
+//! externally, e.g. generated by [fiat-crypto](https://github.com/mit-plv/fiat-crypto)
+//! to be formally verified.
+//!
+//! The word-by-word Montgomery arithmetic of fiat-crypto over 64-bit words
+//! uses R = 2^(64*N) and fully reduced, saturated words, which is the very
+//! same representation as the generic type `Fp`. The supplied functions thus
+//! replace the limb arithmetic of `Fp` for additions, subtractions,
+//! negations, multiplications, squarings and encodings, while the rest
+//! (square roots, exponentiations, decoding, randomness, as well as the
+//! extension `Fp2`) is shared with the other backends. Inversions and
+//! Legendre symbols still use the binary GCD of the 64-bit backend.
+
+use crate::fp::{FiatFunctions, FieldParams, Fp};
+
+/// A macro to define the finite field Fp from the Montgomery arithmetic
+/// generated by fiat-crypto (with `word_by_word_montgomery` over 64-bit
+/// words), all functions are designed to run in constant time. Assumes that
+/// the characteristic is p = 3 mod 4.
+///
+/// This is the same as `define_fp_core`, with the arithmetic replaced as
+/// described in `FieldParams::FIAT`, and thus defines `<typename>Params` and
+/// `<typename>` as an alias of `fp::Fp`.
+///
+/// Macro expectations:
+/// - A typename for the finite field generated
+/// - An array of `N` words which represent the finite field characteristic
+///   in base 2^64. This MUST be the modulus the fiat-crypto code was
+///   generated for.
+/// - The fiat-crypto types for elements in and out of the Montgomery domain,
+///   i.e. the `..._montgomery_domain_field_element` and
+///   `..._non_montgomery_domain_field_element` tuple structs over `[u64; N]`.
+/// - The fiat-crypto functions `mul`, `square`, `add`, `sub` and
+///   `from_montgomery`.
+/// - Optionally, `check_modulus = false,` as for `define_fp_core`.
+#[macro_export]
+macro_rules! define_fp_from_fiat {
+    (
+        typename = $typename:ident,
+        modulus = $modulus:expr,
+        montgomery_domain = $mont:path,
+        non_montgomery_domain = $non_mont:path,
+        mul = $mul:path,
+        square = $square:path,
+        add = $add:path,
+        sub = $sub:path,
+        from_montgomery = $from_montgomery:path,
+        $(check_modulus = $check_modulus:tt,)?
+    ) => {
+        $crate::define_fp_core!(
+            typename = $typename,
+            modulus = $modulus,
+            fiat = $crate::fp::FiatFunctions {
+                mul: |d, a, b| {
+                    let mut r = $mont(*a);
+                    $mul(&mut r, &$mont(*a), &$mont(*b));
+                    *d = r.0;
+                },
+                square: |d, a| {
+                    let mut r = $mont(*a);
+                    $square(&mut r, &$mont(*a));
+                    *d = r.0;
+                },
+                add: |d, a, b| {
+                    let mut r = $mont(*a);
+                    $add(&mut r, &$mont(*a), &$mont(*b));
+                    *d = r.0;
+                },
+                sub: |d, a, b| {
+                    let mut r = $mont(*a);
+                    $sub(&mut r, &$mont(*a), &$mont(*b));
+                    *d = r.0;
+                },
+                from_montgomery: |d, a| {
+                    let mut r = $non_mont(*a);
+                    $from_montgomery(&mut r, &$mont(*a));
+                    *d = r.0;
+                },
+            },
+            $(check_modulus = $check_modulus,)?
+        );
+    };
+} // End of macro: define_fp_from_fiat
+
+impl<P: FieldParams<N>, const N: usize> Fp<P, N> {
+    // Add rhs to this value.
+    #[inline(always)]
+    pub(crate) fn set_add_fiat(&mut self, f: &FiatFunctions<N>, rhs: &Self) {
+        let a = self.0;
+        (f.add)(&mut self.0, &a, &rhs.0);
+    }
+
+    // Subtract rhs from this value.
+    #[inline(always)]
+    pub(crate) fn set_sub_fiat(&mut self, f: &FiatFunctions<N>, rhs: &Self) {
+        let a = self.0;
+        (f.sub)(&mut self.0, &a, &rhs.0);
+    }
+
+    // Negate this value, as 0 - x (fiat-crypto's opp is not required).
+    #[inline(always)]
+    pub(crate) fn set_neg_fiat(&mut self, f: &FiatFunctions<N>) {
+        let a = self.0;
+        (f.sub)(&mut self.0, &[0u64; N], &a);
+    }
+
+    // Multiply this value by rhs.
+    #[inline(always)]
+    pub(crate) fn set_mul_fiat(&mut self, f: &FiatFunctions<N>, rhs: &Self) {
+        let a = self.0;
+        (f.mul)(&mut self.0, &a, &rhs.0);
+    }
+
+    // Replace this value with its square.
+    #[inline(always)]
+    pub(crate) fn set_square_fiat(&mut self, f: &FiatFunctions<N>) {
+        let a = self.0;
+        (f.square)(&mut self.0, &a);
+    }
+
+    // Perform Montgomery reduction (division by R) on this value, which
+    // MUST be properly reduced.
+    #[inline(always)]
+    pub(crate) fn set_montyred_fiat(&mut self, f: &FiatFunctions<N>) {
+        let a = self.0;
+        (f.from_montgomery)(&mut self.0, &a);
+    }
+}
@@ -0,0 +1,332 @@
+//! Constant-time arithmetic for finite fields Fp with p = 3 mod 4 using
+//! Montgomery multiplication over 32-bit limbs.
+//!
+//! This backend is intended for embedded and 32-bit targets, where 64x64-bit
+//! products are not available natively. Only the limb-level arithmetic lives
+//! here: the generic type `Fp` selects it when its parameters use
+//! `Backend::W32`, and shares everything else (additions, roots,
+//! exponentiation, operators and trait implementations) with the 64-bit
+//! backend. Elements are stored as the same words in base 2^64, which are
+//! viewed as 32-bit limbs for the products, so that encodings (and hashcodes)
+//! are identical across backends.
+
+use crate::addchain::{self, ChainOp};
+use crate::fp::{FieldParams, Fp};
+use crate::utils32;
+
+/// A macro to define the finite field Fp using 32-bit limbs, all functions are
+/// designed to run in constant time. Assumes that the characteristic is
+/// p = 3 mod 4.
+///
+/// This is the same as `define_fp_core` with `backend = w32,`, which is also
+/// the default on targets with 32-bit pointers.
+///
+/// Macro expectations:
+/// - A typename for the finite field generated
+/// - An array of `N` words which represent the finite field characteristic
+///   in base 2^64
+/// - Optionally, the precomputed constants `r`, `r2`, `tdec`, `tfixdiv` and
+///   `sqrt_exp`, as for `define_fp_core`. The 32-bit backend does not use
+///   `tfixdiv`.
+/// - Optionally, the addition chains `sqrt_chain`, `fourth_root_chain`,
+///   `progenitor_chain`, `legendre_chain` and `inverse_chain`, as for
+///   `define_fp_core`.
+/// - Optionally, `check_modulus = false,` as for `define_fp_core`.
+#[macro_export]
+macro_rules! define_fp_core_w32 {
+    (
+        typename = $typename:ident,
+        modulus = $modulus:expr,
+        $(
+            r = $r:expr,
+            r2 = $r2:expr,
+            tdec = $tdec:expr,
+            tfixdiv = $tfixdiv:expr,
+            sqrt_exp = $sqrt_exp:expr,
+        )?
+        $(sqrt_chain = $sqrt_chain:expr,)?
+        $(fourth_root_chain = $fourth_root_chain:expr,)?
+        $(progenitor_chain = $progenitor_chain:expr,)?
+        $(legendre_chain = $legendre_chain:expr,)?
+        $(inverse_chain = $inverse_chain:expr,)?
+        $(check_modulus = $check_modulus:tt,)?
+    ) => {
+        $crate::define_fp_core!(
+            typename = $typename,
+            modulus = $modulus,
+            backend = w32,
+            $(r = $r, r2 = $r2, tdec = $tdec, tfixdiv = $tfixdiv, sqrt_exp = $sqrt_exp,)?
+            $(sqrt_chain = $sqrt_chain,)?
+            $(fourth_root_chain = $fourth_root_chain,)?
+            $(progenitor_chain = $progenitor_chain,)?
+            $(legendre_chain = $legendre_chain,)?
+            $(inverse_chain = $inverse_chain,)?
+            $(check_modulus = $check_modulus,)?
+        );
+    };
+} // End of macro: define_fp_core_w32
+
+impl<P: FieldParams<N>, const N: usize> Fp<P, N> {
+    // IMPLEMENTATION NOTES
+    // --------------------
+    //
+    // Each word in base 2^64 is viewed as two 32-bit limbs, for a total of
+    // LIMBS = 2*N limbs in little-endian order. R = 2^(32*LIMBS) = 2^(64*N)
+    // mod p is the same as for the 64-bit backend, so a field element x is
+    // represented by the very same integer x*R mod p.
+    //
+    // The 64-bit backend computes inversions and Legendre symbols with a
+    // binary GCD which relies on 64-bit words; here we instead use Fermat's
+    // little theorem and Euler's criterion, which are slower but only need
+    // multiplications.
+
+    // The modulus over 32-bit limbs
+    const MODULUS32: [[u32; 2]; N] = Self::to_limbs32(&Self::MODULUS);
+
+    // Constants used for internal arithmetic
+    const P0I32: u32 = Self::ninv32(Self::MODULUS32[0][0]);
+    const INV_EXP: [u64; N] = Self::const_inv_exp();
+    const LEGENDRE_EXP: [u64; N] = Self::const_legendre_exp();
+
+    // The chains for these exponents, when shorter than pow_pubexp().
+    const INV_CHAIN: Option<&'static [ChainOp]> = match P::CHAINS {
+        Some(c) => addchain::select(c.inverse, &Self::INV_EXP),
+        None => None,
+    };
+    const LEGENDRE_CHAIN: Option<&'static [ChainOp]> = match P::CHAINS {
+        Some(c) => addchain::select(c.legendre, &Self::LEGENDRE_EXP),
+        None => None,
+    };
+
+    // Split words into 32-bit limbs.
+    const fn to_limbs32(x: &[u64; N]) -> [[u32; 2]; N] {
+        let mut d = [[0u32; 2]; N];
+        let mut i = 0;
+        while i < N {
+            d[i] = [x[i] as u32, (x[i] >> 32) as u32];
+            i += 1;
+        }
+        d
+    }
+
+    // Gather 32-bit limbs into words.
+    #[inline(always)]
+    fn from_limbs32(x: &[[u32; 2]; N]) -> [u64; N] {
+        let mut d = [0u64; N];
+        for i in 0..N {
+            d[i] = (x[i][0] as u64) | ((x[i][1] as u64) << 32);
+        }
+        d
+    }
+
+    // Perform Montgomery reduction (division by R) on this value.
+    // Internal note: if self has proper contents (value less than p), then
+    // this necessarily yields a properly reduced value. If self is not
+    // properly reduced, then the output is in [0..p] inclusive.
+    #[inline]
+    pub(crate) fn set_montyred_w32(&mut self) {
+        let limbs = 2 * N;
+        let m = Self::MODULUS32;
+        let m = m.as_flattened();
+        let mut xx = Self::to_limbs32(&self.0);
+        let x = xx.as_flattened_mut();
+        for _ in 0..limbs {
+            let f = x[0].wrapping_mul(Self::P0I32);
+            let (_, mut cc) = utils32::umull_add(f, m[0], x[0]);
+            for i in 1..limbs {
+                let (d, hi) = utils32::umull_add2(f, m[i], x[i], cc);
+                x[i - 1] = d;
+                cc = hi;
+            }
+            x[limbs - 1] = cc;
+        }
+        self.0 = Self::from_limbs32(&xx);
+    }
+
+    /// Multiply this value by `rhs`.
+    #[inline]
+    pub(crate) fn set_mul_w32(&mut self, rhs: &Self) {
+        let limbs = 2 * N;
+        let m = Self::MODULUS32;
+        let m = m.as_flattened();
+        let (aa, bb) = (Self::to_limbs32(&self.0), Self::to_limbs32(&rhs.0));
+        let (a, b) = (aa.as_flattened(), bb.as_flattened());
+        let mut tt = [[0u32; 2]; N];
+        let t = tt.as_flattened_mut();
+
+        // combined muls + reduction
+        let mut cch = 0;
+        for &f in b {
+            let (lo, mut cc1) = utils32::umull_add(f, a[0], t[0]);
+            let g = lo.wrapping_mul(Self::P0I32);
+            let (_, mut cc2) = utils32::umull_add(g, m[0], lo);
+            for j in 1..limbs {
+                let (d, hi1) = utils32::umull_add2(f, a[j], t[j], cc1);
+                cc1 = hi1;
+                let (d, hi2) = utils32::umull_add2(g, m[j], d, cc2);
+                cc2 = hi2;
+                t[j - 1] = d;
+            }
+            let (d, ee) = utils32::addcarry_u32(cc1, cc2, cch);
+            t[limbs - 1] = d;
+            cch = ee;
+        }
+
+        // final reduction: subtract modulus if necessary
+        let mut cc = 0;
+        for i in 0..limbs {
+            let (d, ee) = utils32::subborrow_u32(t[i], m[i], cc);
+            t[i] = d;
+            cc = ee;
+        }
+        let mm = (cch as u32).wrapping_sub(cc as u32);
+        cc = 0;
+        for i in 0..limbs {
+            let (d, ee) = utils32::addcarry_u32(t[i], mm & m[i], cc);
+            t[i] = d;
+            cc = ee;
+        }
+        self.0 = Self::from_limbs32(&tt);
+    }
+
+    // Set this value to x/y. If y is zero, then this sets this value
+    // to zero, as y^(p-2) = 0 in that case.
+    pub(crate) fn set_div_w32(&mut self, y: &Self) {
+        let t = match Self::INV_CHAIN {
+            Some(ops) => y.pow_chain(ops),
+            None => y.pow_pubexp(&Self::INV_EXP),
+        };
+        self.set_mul_w32(&t);
+    }
+
+    // Legendre symbol on this value, see legendre().
+    pub(crate) fn legendre_w32(self) -> i32 {
+        // Euler's criterion: x^((p-1)/2) is 0, 1 or -1.
+        let t = match Self::LEGENDRE_CHAIN {
+            Some(ops) => self.pow_chain(ops),
+            None => self.pow_pubexp(&Self::LEGENDRE_EXP),
+        };
+        let r = t.equals(&Self::ONE) & 1;
+        let m = t.equals(&Self::MINUS_ONE) & 1;
+        (r as i32) - (m as i32)
+    }
+
+    /// Implements Algorithm 2 from Patrick Longa's
+    /// [ePrint 2022-367](https://eprint.iacr.org/2022/367) §3.
+    /// Computes a1 * b1 + a2 * b2 using an optimised method intended
+    /// for use in Fp2 multiplications
+    #[inline(always)]
+    pub(crate) fn sum_of_products_w32(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        let limbs = 2 * N;
+        let m = Self::MODULUS32;
+        let m = m.as_flattened();
+        let (a1, b1) = (Self::to_limbs32(&a1.0), Self::to_limbs32(&b1.0));
+        let (a2, b2) = (Self::to_limbs32(&a2.0), Self::to_limbs32(&b2.0));
+        let (a1, b1) = (a1.as_flattened(), b1.as_flattened());
+        let (a2, b2) = (a2.as_flattened(), b2.as_flattened());
+        let mut uu = [[0u32; 2]; N];
+        let u = uu.as_flattened_mut();
+
+        let mut cc1: u32;
+        let mut cc2: u32;
+        let mut cc3: u32;
+
+        let mut cch: u8 = 0;
+        let mut cch1: u8;
+        let mut cch2: u8;
+
+        for j in 0..limbs {
+            // u <- u + a1,j * b1 + a2,j * b2
+            (u[0], cc1) = utils32::umull_add(a1[j], b1[0], u[0]);
+            for k in 1..limbs {
+                (u[k], cc1) = utils32::umull_add2(a1[j], b1[k], u[k], cc1);
+            }
+            (u[0], cc2) = utils32::umull_add(a2[j], b2[0], u[0]);
+            for k in 1..limbs {
+                (u[k], cc2) = utils32::umull_add2(a2[j], b2[k], u[k], cc2);
+            }
+
+            // u <- (u + q * p) / 2^32 with q = u * p' mod 2^32
+            let q = u[0].wrapping_mul(Self::P0I32);
+            (_, cc3) = utils32::umull_add(q, m[0], u[0]);
+            for k in 1..limbs {
+                (u[k - 1], cc3) = utils32::umull_add2(q, m[k], u[k], cc3);
+            }
+
+            // Handle the carries cc1, cc2 and cc3, as well as the carry
+            // cch from the last iteration
+            (u[limbs - 1], cch1) = utils32::addcarry_u32(cc1, cc2, cch);
+            (u[limbs - 1], cch2) = utils32::addcarry_u32(u[limbs - 1], cc3, 0);
+            cch = cch1 + cch2;
+        }
+
+        // The result is in [0, 2p) if 2*(p - 1)^2 < p*R: subtract p
+        // and then conditionally add p back
+        let mut borrow: u8 = 0;
+        for i in 0..limbs {
+            (u[i], borrow) = utils32::subborrow_u32(u[i], m[i], borrow);
+        }
+        let mask = (cch as u32).wrapping_sub(borrow as u32);
+        let mut cc = 0;
+        for i in 0..limbs {
+            (u[i], cc) = utils32::addcarry_u32(u[i], mask & m[i], cc);
+        }
+
+        // When p is close to R, we need an additional conditional
+        // subtraction (see the 64-bit backend).
+        if Self::SUM_OF_PRODUCTS_ADDITIONAL_SUB {
+            borrow = 0;
+            for i in 0..limbs {
+                (u[i], borrow) = utils32::subborrow_u32(u[i], m[i], borrow);
+            }
+            let mask = (borrow as u32).wrapping_neg();
+            cc = 0;
+            for i in 0..limbs {
+                (u[i], cc) = utils32::addcarry_u32(u[i], mask & m[i], cc);
+            }
+        }
+
+        Self::new(Self::from_limbs32(&uu))
+    }
+
+    /*
+     * Support functions which compute constants at compile time.
+     */
+    // Return -1/x mod 2^32. It is assumed that x is odd.
+    const fn ninv32(x: u32) -> u32 {
+        let y = 2u32.wrapping_sub(x);
+        let y = y.wrapping_mul(2u32.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u32.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u32.wrapping_sub(y.wrapping_mul(x)));
+        let y = y.wrapping_mul(2u32.wrapping_sub(y.wrapping_mul(x)));
+        y.wrapping_neg()
+    }
+
+    // Compute p - 2, the exponent used for inversions.
+    const fn const_inv_exp() -> [u64; N] {
+        let mut d = Self::MODULUS;
+        let mut cc = 2u64;
+        let mut i = 0;
+        while i < N {
+            let (x, c) = d[i].overflowing_sub(cc);
+            d[i] = x;
+            cc = c as u64;
+            i += 1;
+        }
+        d
+    }
+
+    // Compute (p - 1)/2, the exponent used for Legendre symbols.
+    const fn const_legendre_exp() -> [u64; N] {
+        let mut d = [0u64; N];
+        let mut i = 0;
+        while i < N {
+            d[i] = Self::MODULUS[i] >> 1;
+            if i + 1 < N {
+                d[i] |= Self::MODULUS[i + 1] << 63;
+            }
+            i += 1;
+        }
+        d
+    }
+}
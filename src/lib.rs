@@ -7,15 +7,61 @@
 //! cryptographic research, meaning the current functionality is tailored for
 //! a particular set of problems.
 
-// We include these so we can have things like
-// fn encode(self) -> [u8; Self::ENCODED_LENGTH];
-// defined within the Fq trait
-#![allow(incomplete_features)]
-#![feature(generic_const_exprs)]
+// With the `nightly` feature, we include these so that the `FqArrays`
+// trait can have things like
+// fn encode_array(self) -> [u8; Self::ENCODED_LENGTH];
+// Otherwise, the crate builds on stable Rust.
+#![cfg_attr(feature = "nightly", allow(incomplete_features))]
+#![cfg_attr(feature = "nightly", feature(generic_const_exprs))]
 #![recursion_limit = "256"]
+// The arithmetic is fully no_std and allocation-free. The `alloc` feature
+// (enabled by `std`) adds the methods which return vectors.
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub extern crate alloc;
+
+// Expand to the given items only when the `alloc` feature of this crate is
+// enabled. Macros generating types use this, as a `#[cfg]` in their output
+// would refer to the features of the calling crate.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_alloc {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_alloc {
+    ($($item:item)*) => {};
+}
+
+pub mod addchain;
+pub mod c_api;
+pub mod fp;
+pub mod fp2;
 pub mod fp2_gen;
+pub mod fp_dyn;
+pub mod fp_fiat;
 pub mod fp_gen;
+pub mod fp_gen32;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod test_macros;
 pub mod traits;
+pub mod utils32;
 pub mod utils64;
+
+// Used by the macros to name the parameters types.
+#[doc(hidden)]
+pub use paste;
+
+// Used by define_fp2_python.
+#[cfg(feature = "python")]
+#[doc(hidden)]
+pub use pyo3;
@@ -0,0 +1,299 @@
+//! Python bindings for generated fields, through [PyO3](https://pyo3.rs), to
+//! call the exact arithmetic of this crate from Python (e.g. from Sage or
+//! Jupyter).
+//!
+//! The macro `define_fp2_python` defines a Python class for a GF(p^2) type.
+//! This module holds the conversion layer between Python integers and field
+//! elements which the generated classes rely on: integers cross the boundary
+//! as their sign and magnitude, the latter in unsigned little-endian
+//! convention, which matches the encodings of this crate. Unlike the rest of
+//! the crate, the bindings are not constant-time.
+
+use crate::traits::Fq;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyInt};
+use std::vec::Vec;
+
+/// A macro to define a Python class wrapping a GF(p^2) type, such as one
+/// defined with `define_fp2_from_modulus`. The class is a Rust tuple struct
+/// over the field type, to be added to a `#[pymodule]`.
+///
+/// With `x = K(x0, x1)` for the class `K` and Python integers `x0` and `x1`
+/// (`x1` defaults to zero) representing `x0 + i*x1`, the class provides:
+///
+/// - the operators `+`, `-`, `*` and `/` with elements and integers, unary
+///   `-`, `==`, `hash()` and `x**e` for any integer `e` (negative exponents
+///   invert first);
+/// - `x.x0` and `x.x1`, the components as integers in `[0, p)`;
+/// - `x.sqrt()` and `x.invert()`, returning `None` if the square root does
+///   not exist, or raising `ZeroDivisionError` on zero;
+/// - `x.legendre()`, which is 0, 1 or -1;
+/// - `x.encode()` and `K.decode(buf)`, which raises `ValueError` if `buf` is
+///   not a canonical encoding;
+/// - `g.solve_dlp_2e(x, e)`, returning an integer `v` such that `x = g^v` if
+///   `g` has order 2^e exactly, and `None` otherwise.
+///
+/// The generated code refers to PyO3 through this crate, as `::fp2::pyo3`,
+/// so that the calling crate does not need to depend on the same version of
+/// PyO3 (but cannot rename this crate).
+///
+/// Macro expectations:
+/// - A GF(p^2) type over a base field of this crate.
+/// - The name of the class, which is also its name in Python.
+#[macro_export]
+macro_rules! define_fp2_python {
+    (
+        typename = $Fp2:ty,
+        pyclass = $Class:ident,
+    ) => {
+        #[$crate::pyo3::pyclass(crate = "::fp2::pyo3", frozen, from_py_object)]
+        #[derive(Clone, Copy)]
+        pub struct $Class(pub $Fp2);
+
+        impl $Class {
+            const HALF: usize = <$Fp2>::ENCODED_LENGTH / 2;
+
+            fn operand(x: $crate::python::Operand<Self>) -> $Fp2 {
+                match x {
+                    $crate::python::Operand::Elem(x) => x.0,
+                    $crate::python::Operand::Int(m, neg) => {
+                        <$Fp2>::new(&$crate::python::fq_from_int_bytes(&m, neg), &0u64.into())
+                    }
+                }
+            }
+
+            fn nonzero(x: $Fp2) -> $crate::pyo3::PyResult<$Fp2> {
+                if x.is_zero() != 0 {
+                    return Err($crate::pyo3::exceptions::PyZeroDivisionError::new_err(
+                        "division by zero",
+                    ));
+                }
+                Ok(x)
+            }
+        }
+
+        #[$crate::pyo3::pymethods]
+        #[pyo3(crate = "::fp2::pyo3")]
+        impl $Class {
+            #[new]
+            #[pyo3(signature = (x0, x1 = None))]
+            fn py_new(
+                x0: &$crate::pyo3::Bound<'_, $crate::pyo3::types::PyInt>,
+                x1: Option<&$crate::pyo3::Bound<'_, $crate::pyo3::types::PyInt>>,
+            ) -> $crate::pyo3::PyResult<Self> {
+                let (m0, n0) = $crate::python::int_to_bytes(x0)?;
+                let x0 = $crate::python::fq_from_int_bytes(&m0, n0);
+                let x1 = match x1 {
+                    Some(x1) => {
+                        let (m1, n1) = $crate::python::int_to_bytes(x1)?;
+                        $crate::python::fq_from_int_bytes(&m1, n1)
+                    }
+                    None => 0u64.into(),
+                };
+                Ok(Self(<$Fp2>::new(&x0, &x1)))
+            }
+
+            #[staticmethod]
+            fn decode(buf: &[u8]) -> $crate::pyo3::PyResult<Self> {
+                let (x, ok) = <$Fp2>::decode(buf);
+                if ok == 0 {
+                    return Err($crate::pyo3::exceptions::PyValueError::new_err(
+                        "invalid encoding",
+                    ));
+                }
+                Ok(Self(x))
+            }
+
+            fn encode<'py>(
+                &self,
+                py: $crate::pyo3::Python<'py>,
+            ) -> $crate::pyo3::Bound<'py, $crate::pyo3::types::PyBytes> {
+                $crate::pyo3::types::PyBytes::new(py, &self.0.encode())
+            }
+
+            #[getter]
+            fn x0<'py>(
+                &self,
+                py: $crate::pyo3::Python<'py>,
+            ) -> $crate::pyo3::PyResult<$crate::pyo3::Bound<'py, $crate::pyo3::types::PyInt>> {
+                $crate::python::int_from_bytes(py, &self.0.encode()[..Self::HALF])
+            }
+
+            #[getter]
+            fn x1<'py>(
+                &self,
+                py: $crate::pyo3::Python<'py>,
+            ) -> $crate::pyo3::PyResult<$crate::pyo3::Bound<'py, $crate::pyo3::types::PyInt>> {
+                $crate::python::int_from_bytes(py, &self.0.encode()[Self::HALF..])
+            }
+
+            fn is_zero(&self) -> bool {
+                self.0.is_zero() != 0
+            }
+
+            fn legendre(&self) -> i32 {
+                self.0.legendre()
+            }
+
+            fn sqrt(&self) -> Option<Self> {
+                let (r, ok) = self.0.sqrt();
+                (ok != 0).then_some(Self(r))
+            }
+
+            fn invert(&self) -> $crate::pyo3::PyResult<Self> {
+                Ok(Self(Self::nonzero(self.0)?.invert()))
+            }
+
+            fn solve_dlp_2e<'py>(
+                &self,
+                py: $crate::pyo3::Python<'py>,
+                x: &Self,
+                e: usize,
+            ) -> $crate::pyo3::PyResult<
+                Option<$crate::pyo3::Bound<'py, $crate::pyo3::types::PyInt>>,
+            > {
+                // The order of g divides p + 1 and p - 1.
+                if e == 0 || e > <$Fp2>::CHAR_BIT_LENGTH {
+                    return Ok(None);
+                }
+                let (v, ok) = self.0.solve_dlp_2e(&x.0, e, None);
+                if ok == 0 {
+                    return Ok(None);
+                }
+                $crate::python::int_from_bytes(py, &v).map(Some)
+            }
+
+            fn __add__(&self, rhs: $crate::python::Operand<Self>) -> Self {
+                Self(self.0 + Self::operand(rhs))
+            }
+
+            fn __radd__(&self, lhs: $crate::python::Operand<Self>) -> Self {
+                Self(Self::operand(lhs) + self.0)
+            }
+
+            fn __sub__(&self, rhs: $crate::python::Operand<Self>) -> Self {
+                Self(self.0 - Self::operand(rhs))
+            }
+
+            fn __rsub__(&self, lhs: $crate::python::Operand<Self>) -> Self {
+                Self(Self::operand(lhs) - self.0)
+            }
+
+            fn __mul__(&self, rhs: $crate::python::Operand<Self>) -> Self {
+                Self(self.0 * Self::operand(rhs))
+            }
+
+            fn __rmul__(&self, lhs: $crate::python::Operand<Self>) -> Self {
+                Self(Self::operand(lhs) * self.0)
+            }
+
+            fn __truediv__(
+                &self,
+                rhs: $crate::python::Operand<Self>,
+            ) -> $crate::pyo3::PyResult<Self> {
+                Ok(Self(self.0 / Self::nonzero(Self::operand(rhs))?))
+            }
+
+            fn __rtruediv__(
+                &self,
+                lhs: $crate::python::Operand<Self>,
+            ) -> $crate::pyo3::PyResult<Self> {
+                Ok(Self(Self::operand(lhs) / Self::nonzero(self.0)?))
+            }
+
+            fn __neg__(&self) -> Self {
+                Self(-self.0)
+            }
+
+            fn __pow__(
+                &self,
+                e: &$crate::pyo3::Bound<'_, $crate::pyo3::types::PyInt>,
+                modulo: Option<&$crate::pyo3::Bound<'_, $crate::pyo3::PyAny>>,
+            ) -> $crate::pyo3::PyResult<Self> {
+                if modulo.is_some_and(|m| !m.is_none()) {
+                    return Err($crate::pyo3::exceptions::PyTypeError::new_err(
+                        "pow() 3rd argument not allowed",
+                    ));
+                }
+                let (m, neg) = $crate::python::int_to_bytes(e)?;
+                let x = if neg { Self::nonzero(self.0)?.invert() } else { self.0 };
+                Ok(Self(x.pow(&m, 8 * m.len())))
+            }
+
+            fn __eq__(&self, rhs: $crate::python::Operand<Self>) -> bool {
+                self.0.equals(&Self::operand(rhs)) != 0
+            }
+
+            fn __hash__(&self) -> u64 {
+                self.0.hashcode()
+            }
+
+            fn __bool__(&self) -> bool {
+                self.0.is_zero() == 0
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}", self.0)
+            }
+        }
+    };
+} // End of macro: define_fp2_python
+
+/// The operand of an arithmetic operator of a class `C` defined with
+/// `define_fp2_python`: either an element of the class, or an integer given
+/// as its magnitude in unsigned little-endian convention and its sign.
+pub enum Operand<C> {
+    Elem(C),
+    Int(Vec<u8>, bool),
+}
+
+impl<'a, 'py, C: FromPyObjectOwned<'py>> FromPyObject<'a, 'py> for Operand<C> {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(x) = obj.extract::<C>() {
+            return Ok(Operand::Elem(x));
+        }
+        match obj.cast::<PyInt>() {
+            Ok(x) => {
+                let (m, neg) = int_to_bytes(&x)?;
+                Ok(Operand::Int(m, neg))
+            }
+            Err(_) => Err(PyTypeError::new_err(
+                "expected a field element or an integer",
+            )),
+        }
+    }
+}
+
+/// Return the magnitude of a Python integer, in unsigned little-endian
+/// convention over the minimal number of bytes, and whether it is negative.
+pub fn int_to_bytes(x: &Bound<'_, PyInt>) -> PyResult<(Vec<u8>, bool)> {
+    let neg = x.lt(0)?;
+    let m = if neg { x.neg()? } else { x.clone().into_any() };
+    let n = m
+        .call_method0("bit_length")?
+        .extract::<usize>()?
+        .div_ceil(8);
+    let b = m.call_method1("to_bytes", (n, "little"))?;
+    Ok((b.cast::<PyBytes>()?.as_bytes().to_vec(), neg))
+}
+
+/// Return the non-negative Python integer whose magnitude is given in
+/// unsigned little-endian convention.
+pub fn int_from_bytes<'py>(py: Python<'py>, m: &[u8]) -> PyResult<Bound<'py, PyInt>> {
+    let x = py
+        .get_type::<PyInt>()
+        .call_method1("from_bytes", (PyBytes::new(py, m), "little"))?;
+    Ok(x.cast_into::<PyInt>()?)
+}
+
+/// Return the element of the base field GF(p) for an integer given as its
+/// magnitude `m`, with any length in unsigned little-endian convention, and
+/// its sign. The integer is reduced modulo p.
+pub fn fq_from_int_bytes<F: Fq>(m: &[u8], neg: bool) -> F {
+    let mut x = F::decode_reduce(m);
+    x.set_cond_neg((neg as u32).wrapping_neg());
+    x
+}
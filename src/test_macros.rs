@@ -92,7 +92,7 @@ macro_rules! define_fp_tests {
             assert_eq!(a.equals(&b), u32::MAX, "decode round-trip value mismatch");
 
             // Zero is a valid encoding and must round-trip to the zero element.
-            let (zero, ok) = <$Fp>::decode(&vec![0u8; <$Fp>::ENCODED_LENGTH]);
+            let (zero, ok) = <$Fp>::decode(&[0u8; <$Fp>::ENCODED_LENGTH]);
             assert_eq!(ok, u32::MAX, "decode of zero encoding should succeed");
             assert_eq!(
                 zero.is_zero(),
@@ -110,7 +110,7 @@ macro_rules! define_fp_tests {
             );
 
             // The modulus itself encodes a value >= p and must fail.
-            let mut p_bytes = vec![0u8; <$Fp>::ENCODED_LENGTH];
+            let mut p_bytes = [0u8; <$Fp>::ENCODED_LENGTH];
             for i in 0..<$Fp>::N {
                 let word_bytes = <$Fp>::MODULUS[i].to_le_bytes();
                 let start = i * 8;
@@ -126,6 +126,178 @@ macro_rules! define_fp_tests {
             );
         }
 
+        /// Montgomery and canonical limbs: `x*R mod p` and `x`, round-trips
+        /// and rejection of values >= p.
+        #[test]
+        fn fp_test_limbs() {
+            let zp = fp_modulus();
+            let zr = ::num_bigint::BigInt::from(1) << (64 * <$Fp>::N);
+            let to_bigint = |w: &[u64]| {
+                let bytes: Vec<u8> = w.iter().flat_map(|x| x.to_le_bytes()).collect();
+                ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &bytes)
+            };
+
+            for i in 0..100 {
+                let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                let za = ::num_bigint::BigInt::from_bytes_le(
+                    ::num_bigint::Sign::Plus,
+                    a.encode().as_ref(),
+                );
+
+                let c = a.to_canonical_limbs();
+                assert_eq!(to_bigint(&c), za, "iter {i}: canonical limbs mismatch");
+                let (b, ok) = <$Fp>::from_canonical_limbs(&c);
+                assert_eq!(ok, u32::MAX, "iter {i}: from_canonical_limbs failed");
+                assert_eq!(a.equals(&b), u32::MAX, "iter {i}: canonical round-trip");
+
+                let m = a.to_montgomery_limbs();
+                assert_eq!(
+                    to_bigint(&m),
+                    (&za * &zr) % &zp,
+                    "iter {i}: Montgomery limbs mismatch"
+                );
+                let (b, ok) = <$Fp>::from_montgomery_limbs(&m);
+                assert_eq!(ok, u32::MAX, "iter {i}: from_montgomery_limbs failed");
+                assert_eq!(a.equals(&b), u32::MAX, "iter {i}: Montgomery round-trip");
+                assert_eq!(
+                    a.equals(&<$Fp>::new(m)),
+                    u32::MAX,
+                    "iter {i}: new() mismatch"
+                );
+            }
+
+            // The modulus itself must be rejected by both.
+            let (zero, ok) = <$Fp>::from_canonical_limbs(&<$Fp>::MODULUS);
+            assert_eq!(ok, 0x00000000, "from_canonical_limbs(p) should fail");
+            assert_eq!(zero.is_zero(), u32::MAX, "failure should return zero");
+            let (zero, ok) = <$Fp>::from_montgomery_limbs(&<$Fp>::MODULUS);
+            assert_eq!(ok, 0x00000000, "from_montgomery_limbs(p) should fail");
+            assert_eq!(zero.is_zero(), u32::MAX, "failure should return zero");
+        }
+
+        /// `PartialEq`, `Hash` and `Ord`: consistent with `equals` and with
+        /// the canonical integers.
+        #[test]
+        fn fp_test_eq_hash_ord() {
+            use ::std::hash::{BuildHasher as _, RandomState};
+
+            let hasher = RandomState::new();
+            let mut v = Vec::new();
+            for i in 0..100 {
+                let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                let b = <$Fp>::decode_reduce(&fp_test_vector(i + 1));
+                let za = ::num_bigint::BigInt::from_bytes_le(
+                    ::num_bigint::Sign::Plus,
+                    a.encode().as_ref(),
+                );
+                let zb = ::num_bigint::BigInt::from_bytes_le(
+                    ::num_bigint::Sign::Plus,
+                    b.encode().as_ref(),
+                );
+                let c = a + <$Fp>::ZERO;
+                assert!(a == c, "iter {i}: a should equal itself");
+                assert_eq!(a == b, a.equals(&b) != 0, "iter {i}: == mismatch");
+                assert_eq!(a.cmp(&b), za.cmp(&zb), "iter {i}: order mismatch");
+                assert_eq!(a.cmp(&c), ::core::cmp::Ordering::Equal);
+                assert_eq!(hasher.hash_one(a), hasher.hash_one(c), "iter {i}: hash");
+                v.push(a);
+                v.push(c);
+            }
+
+            // Sorting and deduplication
+            assert!(<$Fp>::ZERO < <$Fp>::ONE && <$Fp>::ONE < <$Fp>::MINUS_ONE);
+            v.sort();
+            v.dedup();
+            assert_eq!(v.len(), 100);
+            assert!(v.windows(2).all(|w| w[0] < w[1]));
+            let set: ::std::collections::HashSet<$Fp> = v.iter().copied().collect();
+            assert_eq!(set.len(), 100);
+        }
+
+        /// Formatting traits: canonical values in bases 16 and 10, padded
+        /// as the same integers.
+        #[test]
+        fn fp_test_formatting() {
+            assert_eq!(format!("{}", <$Fp>::ZERO), "0x0");
+            assert_eq!(format!("{:x} {:#X}", <$Fp>::ZERO, <$Fp>::ONE), "0 0x1");
+            assert_eq!(format!("{}", <$Fp>::ZERO.decimal()), "0");
+            for i in 0..100 {
+                let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                let za = ::num_bigint::BigInt::from_bytes_le(
+                    ::num_bigint::Sign::Plus,
+                    a.encode().as_ref(),
+                );
+                let hex = za.to_str_radix(16);
+                assert_eq!(format!("{}", a), format!("0x{hex}"), "iter {i}: Display");
+                assert_eq!(format!("{:x}", a), hex, "iter {i}: LowerHex");
+                assert_eq!(
+                    format!("{:#x}", a),
+                    format!("0x{hex}"),
+                    "iter {i}: LowerHex"
+                );
+                assert_eq!(format!("{:X}", a), hex.to_uppercase(), "iter {i}: UpperHex");
+                assert_eq!(
+                    format!("{}", a.decimal()),
+                    za.to_string(),
+                    "iter {i}: decimal"
+                );
+                assert_eq!(format!("{:^90}", a), format!("{:^#90x}", za), "iter {i}");
+                assert_eq!(format!("{:#066x}", a), format!("{:#066x}", za), "iter {i}");
+                assert_eq!(format!("{:*<70X}", a), format!("{:*<70X}", za), "iter {i}");
+                assert_eq!(format!("{:3x}", a), format!("{:3x}", za), "iter {i}");
+                assert_eq!(
+                    format!("{:>100}", a.decimal()),
+                    format!("{:>100}", za),
+                    "iter {i}: decimal"
+                );
+                assert_eq!(
+                    format!("{:0100}", a.decimal()),
+                    format!("{:0100}", za),
+                    "iter {i}: decimal"
+                );
+                let d = format!("{:?}", a);
+                assert!(
+                    d == format!("Fp(0x{hex})") || d == "Fp(<redacted>)",
+                    "iter {i}: Debug"
+                );
+            }
+        }
+
+        /// Operators with integers, and sums and products over iterators.
+        #[test]
+        fn fp_test_int_ops_and_iter() {
+            let mut v = Vec::new();
+            for i in 0..100 {
+                let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                let k = (i as i32).wrapping_mul(0x2C2E_7A15);
+                let fk = <$Fp>::from(k);
+                assert!(a + k == a + fk && k + a == a + fk, "iter {i}: add i32");
+                assert!(a - k == a - fk && k - &a == fk - a, "iter {i}: sub i32");
+                assert!(a * k == a * fk && k * &a == a * fk, "iter {i}: mul i32");
+                for u in [i as u64, 1 << 31, u64::MAX - (i as u64)] {
+                    let fu = <$Fp>::from(u);
+                    assert!(&a + u == a + fu && u + a == a + fu, "iter {i}: add u64");
+                    assert!(a - u == a - fu && u - a == fu - a, "iter {i}: sub u64");
+                    assert!(a * u == a * fu && u * a == a * fu, "iter {i}: mul u64");
+                }
+                let mut c = a;
+                c += 3;
+                c -= 5u64;
+                c *= -2;
+                c *= 7u64;
+                assert!(c == (a - <$Fp>::TWO) * <$Fp>::from(-14), "iter {i}: assign");
+                v.push(a);
+            }
+
+            let sum = v.iter().fold(<$Fp>::ZERO, |acc, x| acc + x);
+            let prod = v.iter().fold(<$Fp>::ONE, |acc, x| acc * x);
+            assert!(v.iter().sum::<$Fp>() == sum && v.iter().copied().sum::<$Fp>() == sum);
+            assert!(v.iter().product::<$Fp>() == prod);
+            assert!(v.iter().copied().product::<$Fp>() == prod);
+            assert!(::core::iter::empty::<$Fp>().sum::<$Fp>() == <$Fp>::ZERO);
+            assert!(::core::iter::empty::<$Fp>().product::<$Fp>() == <$Fp>::ONE);
+        }
+
         /// Addition: `(a + b) mod p`.
         #[test]
         fn fp_test_add() {
@@ -540,7 +712,7 @@ macro_rules! define_fp_tests {
         #[test]
         fn fp_test_pow_fermat() {
             // Compute p-1 as a little-endian byte array.
-            let mut exp_bytes = vec![0u8; <$Fp>::N * 8];
+            let mut exp_bytes = [0u8; <$Fp>::N * 8];
             let mut borrow = 1u64;
             for i in 0..<$Fp>::N {
                 let (d, b) = <$Fp>::MODULUS[i].overflowing_sub(borrow);
@@ -814,6 +986,168 @@ macro_rules! define_fp_tests {
             assert_eq!(c.is_zero(), u32::MAX, "sqrt(0) should return zero");
         }
 
+        /// Progenitor: `z = a^((p-3)/4)` gives the Legendre symbol as
+        /// `a*z^2`, and a square root of `a` or `-a` as `a*z`.
+        #[test]
+        fn fp_test_progenitor() {
+            for i in 0..30 {
+                let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                let z = a.progenitor();
+                let ls = <$Fp>::from(a.legendre());
+                assert_eq!((a * z.square()).equals(&ls), u32::MAX, "iter {i}: a*z^2");
+                let t = (a * z).square();
+                let b = <$Fp>::select(&a, &(-a), (a.legendre() >> 1) as u32);
+                assert_eq!(t.equals(&b), u32::MAX, "iter {i}: (a*z)^2");
+                // (a^2)^((p-3)/4) = a^((p-3)/2) = legendre(a)/a
+                let b = a.square().progenitor();
+                assert_eq!((a * b).equals(&ls), u32::MAX, "iter {i}: square");
+            }
+            assert_eq!(<$Fp>::ZERO.progenitor().is_zero(), u32::MAX);
+        }
+
+        /// `sqrt_ratio(u, v)` is the square root of `u/v`, or of `-u/v` if
+        /// `u/v` is not a square; `invsqrt(x)` is the square root of `1/x`.
+        #[test]
+        fn fp_test_sqrt_ratio() {
+            let mut uv = vec![(<$Fp>::ZERO, <$Fp>::ONE), (<$Fp>::ZERO, <$Fp>::ZERO)];
+            for i in 0..30 {
+                let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                let b = <$Fp>::decode_reduce(&fp_test_vector(i + 100));
+                uv.extend([(a, b), (a * a * b, b), (-(a * a * b), b), (a, <$Fp>::ZERO)]);
+            }
+            for (i, (u, v)) in uv.into_iter().enumerate() {
+                let (y, r) = <$Fp>::sqrt_ratio(&u, &v);
+                if v.is_zero() != 0 {
+                    assert_eq!(r, 0, "input {i}: v = 0");
+                    assert_eq!(y.is_zero(), u32::MAX, "input {i}: v = 0");
+                    continue;
+                }
+                let x = u / v;
+                let (s, rs) = x.sqrt();
+                assert_eq!(r, rs, "input {i}: success flag");
+                let s = <$Fp>::select(&(-x).sqrt().0, &s, rs);
+                assert_eq!(y.equals(&s), u32::MAX, "input {i}: root");
+                assert_eq!(
+                    (y.square() * v).equals(&<$Fp>::select(&(-u), &u, r)),
+                    u32::MAX,
+                    "input {i}: root^2"
+                );
+
+                let (z, rz) = v.invsqrt();
+                assert_eq!(rz, v.sqrt().1, "input {i}: invsqrt flag");
+                let s = <$Fp>::select(&<$Fp>::ZERO, &v.invert().sqrt().0, rz);
+                assert_eq!(z.equals(&s), u32::MAX, "input {i}: invsqrt");
+            }
+            assert_eq!(<$Fp>::ZERO.invsqrt().1, 0);
+            assert_eq!(<$Fp>::ZERO.invsqrt().0.is_zero(), u32::MAX);
+        }
+
+        /// Power residue symbols match their definition on integers, and
+        /// `is_nth_power()` matches `is_square()` and `fourth_root()`.
+        #[test]
+        fn fp_test_power_residue() {
+            fn check<const K: u64>() {
+                use ::num_bigint::BigInt;
+
+                let zp = fp_modulus();
+                let pm1 = &zp - 1;
+                let (mut g, mut h) = (K, u64::try_from(&pm1 % K).unwrap());
+                while h != 0 {
+                    (g, h) = (h, g % h);
+                }
+                let e = &pm1 / g;
+                let mut xx = vec![<$Fp>::ZERO, <$Fp>::ONE, <$Fp>::MINUS_ONE];
+                for i in 0..20 {
+                    let a = <$Fp>::decode_reduce(&fp_test_vector(i));
+                    xx.extend([a, a.pow_u64_vartime(K)]);
+                }
+                for (i, x) in xx.into_iter().enumerate() {
+                    let zx = BigInt::from_bytes_le(::num_bigint::Sign::Plus, &x.encode());
+                    let zs = zx.modpow(&e, &zp);
+                    let s = x.power_residue_symbol::<K>();
+                    let s = BigInt::from_bytes_le(::num_bigint::Sign::Plus, &s.encode());
+                    assert_eq!(s, zs, "K = {K}, input {i}: symbol");
+                    let is = zs == BigInt::from(1) || zx == BigInt::from(0);
+                    let r = x.is_nth_power::<K>();
+                    assert_eq!(r, (is as u32).wrapping_neg(), "K = {K}, input {i}");
+                    if K == 2 {
+                        assert_eq!(r, x.is_square(), "input {i}: is_square");
+                    }
+                    if K == 4 {
+                        assert_eq!(r, x.fourth_root().1, "input {i}: fourth_root");
+                    }
+                }
+            }
+
+            check::<1>();
+            check::<2>();
+            check::<3>();
+            check::<4>();
+            check::<5>();
+            check::<6>();
+            check::<7>();
+            check::<8>();
+            check::<12>();
+        }
+
+        /// Sign predicates match the canonical integers, and each sign
+        /// convention selects the root whose sign is 0.
+        #[test]
+        fn fp_test_sign_conventions() {
+            use $crate::traits::SignConvention;
+
+            let zp = fp_modulus();
+            let half = (&zp - 1) / 2;
+            let mask = |b: bool| (b as u32).wrapping_neg();
+            let mut xx = vec![<$Fp>::ZERO, <$Fp>::ONE, <$Fp>::MINUS_ONE, <$Fp>::TWO];
+            xx.extend([
+                <$Fp>::MINUS_ONE.half(),
+                <$Fp>::MINUS_ONE.half() + <$Fp>::ONE,
+            ]);
+            for i in 0..30 {
+                xx.push(<$Fp>::decode_reduce(&fp_test_vector(i)));
+            }
+            for (i, x) in xx.into_iter().enumerate() {
+                let zx = ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &x.encode());
+                let odd = mask(zx.bit(0));
+                assert_eq!(x.is_odd(), odd, "input {i}: is_odd");
+                assert_eq!(x.sgn0(), odd, "input {i}: sgn0");
+                let largest = mask(zx > half);
+                assert_eq!(
+                    x.is_lexicographically_largest(),
+                    largest,
+                    "input {i}: largest"
+                );
+
+                let (c, _) = x.square().sqrt();
+                assert_eq!(
+                    x.square().sqrt_signed(SignConvention::Sgn0).0.equals(&c),
+                    u32::MAX
+                );
+                for sc in [SignConvention::Sgn0, SignConvention::Positive] {
+                    let mut y = x;
+                    y.set_canonical_sign(sc);
+                    assert_eq!(y.sign(sc), 0, "input {i}: {sc:?}");
+                    assert_eq!(
+                        y.equals(&x) | y.equals(&(-x)),
+                        u32::MAX,
+                        "input {i}: {sc:?}"
+                    );
+                    let (y2, r) = x.square().sqrt_signed(sc);
+                    assert_eq!(r, u32::MAX, "input {i}: {sc:?}");
+                    assert_eq!(y2.equals(&y), u32::MAX, "input {i}: {sc:?} sqrt");
+                    let (y4, r) = x.square().square().fourth_root_signed(sc);
+                    assert_eq!(r, u32::MAX, "input {i}: {sc:?}");
+                    assert_eq!(
+                        y4.square().equals(&x.square()),
+                        u32::MAX,
+                        "input {i}: {sc:?}"
+                    );
+                    assert_eq!(y4.sign(sc), 0, "input {i}: {sc:?} fourth root");
+                }
+            }
+        }
+
         /// Fourth roots: `fourth_root(a^4)` succeeds; `sqrt(-(a^4))` fails; result LSB is zero.
         #[test]
         fn fp_test_fourth_root() {
@@ -961,9 +1295,9 @@ macro_rules! define_fp2_tests {
                 let a = <$Fp2>::decode_reduce(&va);
                 let (zc0, zc1) = fp2_decode_components(&a.encode());
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 assert_eq!(zc0, &za0 % &zp, "iter {i}: x0 encode/decode_reduce failed");
                 assert_eq!(zc1, &za1 % &zp, "iter {i}: x1 encode/decode_reduce failed");
             }
@@ -987,13 +1321,13 @@ macro_rules! define_fp2_tests {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i));
                 let b = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i + 1));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let zb0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x0().encode());
                 let zb1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x1().encode());
                 let (zc0, zc1) = fp2_decode_components(&(a + b).encode());
                 assert_eq!(zc0, (&za0 + &zb0) % &zp, "iter {i}: add x0 failed");
                 assert_eq!(zc1, (&za1 + &zb1) % &zp, "iter {i}: add x1 failed");
@@ -1018,13 +1352,13 @@ macro_rules! define_fp2_tests {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i));
                 let b = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i + 1));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let zb0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x0().encode());
                 let zb1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x1().encode());
                 let (zc0, zc1) = fp2_decode_components(&(a - b).encode());
                 assert_eq!(zc0, (&zp + &za0 - &zb0) % &zp, "iter {i}: sub x0 failed");
                 assert_eq!(zc1, (&zp + &za1 - &zb1) % &zp, "iter {i}: sub x1 failed");
@@ -1051,9 +1385,9 @@ macro_rules! define_fp2_tests {
             for i in 0..100 {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let c = -a;
                 let (zc0, zc1) = fp2_decode_components(&c.encode());
                 assert_eq!(zc0, (&zp - &za0) % &zp, "iter {i}: neg x0 failed");
@@ -1076,13 +1410,13 @@ macro_rules! define_fp2_tests {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i));
                 let b = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i + 1));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let zb0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x0().encode());
                 let zb1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &b.x1().encode());
                 let zd0 = (&zp + ((&za0 * &zb0) % &zp) - ((&za1 * &zb1) % &zp)) % &zp;
                 let zd1 = ((&za0 * &zb1) + (&za1 * &zb0)) % &zp;
 
@@ -1111,9 +1445,9 @@ macro_rules! define_fp2_tests {
             for i in 0..100 {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let zd0 = (&zp + ((&za0 * &za0) % &zp) - ((&za1 * &za1) % &zp)) % &zp;
                 let zd1 = ((&za0 * &za1) + (&za1 * &za0)) % &zp;
                 let (zc0, zc1) = fp2_decode_components(&a.square().encode());
@@ -1162,9 +1496,9 @@ macro_rules! define_fp2_tests {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i));
                 let b = <$Fp2>::decode_reduce(&fp2_test_vector(2 * i + 1));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let (zc0, zc1) = fp2_decode_components(&(a / b * b).encode());
                 assert_eq!(zc0, za0, "iter {i}: division round-trip x0 failed");
                 assert_eq!(zc1, za1, "iter {i}: division round-trip x1 failed");
@@ -1195,9 +1529,9 @@ macro_rules! define_fp2_tests {
             for i in 0..100 {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 let c = a.conjugate();
                 let (zc0, zc1) = fp2_decode_components(&c.encode());
                 assert_eq!(zc0, za0, "iter {i}: conjugate changed x0");
@@ -1356,7 +1690,7 @@ macro_rules! define_fp2_tests {
                 c.set_x0_small(k);
                 let (zc0, zc1) = fp2_decode_components(&c.encode());
                 let za1 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x1().encode());
                 assert_eq!(zc0, zk, "iter {i}: set_x0_small x0 wrong");
                 assert_eq!(zc1, za1, "iter {i}: set_x0_small changed x1");
 
@@ -1365,7 +1699,7 @@ macro_rules! define_fp2_tests {
                 c.set_x1_small(k);
                 let (zc0, zc1) = fp2_decode_components(&c.encode());
                 let za0 =
-                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0.encode());
+                    ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &a.x0().encode());
                 assert_eq!(zc0, za0, "iter {i}: set_x1_small changed x0");
                 assert_eq!(zc1, zk, "iter {i}: set_x1_small x1 wrong");
             }
@@ -1374,10 +1708,10 @@ macro_rules! define_fp2_tests {
             let a = <$Fp2>::decode_reduce(&fp2_test_vector(0));
             let mut c = a;
             c.set_x0_small(0);
-            assert_eq!(c.x0.is_zero(), u32::MAX, "set_x0_small(0) should zero x0");
+            assert_eq!(c.x0().is_zero(), u32::MAX, "set_x0_small(0) should zero x0");
             let mut c = a;
             c.set_x1_small(0);
-            assert_eq!(c.x1.is_zero(), u32::MAX, "set_x1_small(0) should zero x1");
+            assert_eq!(c.x1().is_zero(), u32::MAX, "set_x1_small(0) should zero x1");
         }
 
         /// Legendre symbol and `is_square`.
@@ -1430,15 +1764,14 @@ macro_rules! define_fp2_tests {
             // -(x0^2) embedded as a real element is not.
             for i in 0..100 {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
-                if a.x0.is_zero() == 0 {
-                    let mut f = <$Fp2>::ZERO;
-                    f.x0 = a.x0.square();
+                if a.x0().is_zero() == 0 {
+                    let mut f = <$Fp2>::new(&a.x0().square(), &<$Fp2>::ZERO.x1());
                     assert_eq!(
                         f.is_square_base_field(),
                         u32::MAX,
                         "iter {i}: is_square_base_field(x0^2) should be true"
                     );
-                    f.x0.set_neg();
+                    f.set_neg();
                     assert_eq!(
                         f.is_square_base_field(),
                         0,
@@ -1499,9 +1832,8 @@ macro_rules! define_fp2_tests {
             // Fp2 because -1 = i^2 is a square in Fp2.
             for i in 0..100 {
                 let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
-                if a.x0.is_zero() == 0 {
-                    let mut f = <$Fp2>::ZERO;
-                    f.x0 = a.x0;
+                if a.x0().is_zero() == 0 {
+                    let f = <$Fp2>::new(&a.x0(), &<$Fp2>::ZERO.x1());
 
                     let (c, r) = f.sqrt();
                     assert_eq!(r, u32::MAX, "iter {i}: sqrt of real element failed");
@@ -1518,6 +1850,85 @@ macro_rules! define_fp2_tests {
             }
         }
 
+        /// Sign predicates match the canonical integers (with x0-then-x1
+        /// priority for `sgn0` and x1-then-x0 for the lexicographic order),
+        /// and each sign convention selects the root whose sign is 0.
+        #[test]
+        fn fp2_test_sign_conventions() {
+            use $crate::traits::SignConvention;
+
+            let zp = fp2_modulus();
+            let half = (&zp - 1) / 2;
+            let mask = |b: bool| (b as u32).wrapping_neg();
+            let mut xx = vec![<$Fp2>::ZERO, <$Fp2>::ONE, <$Fp2>::MINUS_ONE, <$Fp2>::ZETA];
+            for i in 0..100 {
+                let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
+                let (x0, x1) = (<$Fp2>::from(a.x0()), <$Fp2>::from(a.x1()));
+                xx.extend([a, x0, -x0, x1 * <$Fp2>::ZETA, -x1 * <$Fp2>::ZETA]);
+            }
+            for (i, x) in xx.into_iter().enumerate() {
+                let (z0, z1) = fp2_decode_components(&x.encode()[..]);
+                let zero = ::num_bigint::BigInt::from(0);
+                let sgn0 = z0.bit(0) || (z0 == zero && z1.bit(0));
+                assert_eq!(x.sgn0(), mask(sgn0), "input {i}: sgn0");
+                let largest = z1 > half || (z1 == zero && z0 > half);
+                assert_eq!(
+                    x.is_lexicographically_largest(),
+                    mask(largest),
+                    "input {i}: largest"
+                );
+
+                let (c, _) = x.square().sqrt();
+                assert_eq!(
+                    x.square().sqrt_signed(SignConvention::Sgn0).0.equals(&c),
+                    u32::MAX
+                );
+                for sc in [SignConvention::Sgn0, SignConvention::Positive] {
+                    let mut y = x;
+                    y.set_canonical_sign(sc);
+                    assert_eq!(y.sign(sc), 0, "input {i}: {sc:?}");
+                    assert_eq!(
+                        y.equals(&x) | y.equals(&(-x)),
+                        u32::MAX,
+                        "input {i}: {sc:?}"
+                    );
+                    let (y2, r) = x.square().sqrt_signed(sc);
+                    assert_eq!(r, u32::MAX, "input {i}: {sc:?}");
+                    assert_eq!(y2.equals(&y), u32::MAX, "input {i}: {sc:?} sqrt");
+                    let (y4, r) = x.square().square().fourth_root_signed(sc);
+                    assert_eq!(r, u32::MAX, "input {i}: {sc:?}");
+                    assert_eq!(
+                        y4.n_square(2).equals(&x.n_square(2)),
+                        u32::MAX,
+                        "input {i}: {sc:?}"
+                    );
+                    assert_eq!(y4.sign(sc), 0, "input {i}: {sc:?} fourth root");
+                }
+            }
+        }
+
+        /// `invsqrt(x)` is the square root of `1/x`, and fails on zero.
+        #[test]
+        fn fp2_test_invsqrt() {
+            let nqr = fp2_nqr();
+            let mut xx = vec![<$Fp2>::ZERO, <$Fp2>::ONE, <$Fp2>::MINUS_ONE, <$Fp2>::ZETA];
+            for i in 0..100 {
+                let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
+                let (x0, x1) = (<$Fp2>::from(a.x0()), <$Fp2>::from(a.x1()));
+                xx.extend([a, a * a, nqr * a * a, x0, -x0, x1 * <$Fp2>::ZETA]);
+            }
+            for (i, x) in xx.into_iter().enumerate() {
+                let (y, r) = x.invsqrt();
+                let (c, rc) = x.invert().sqrt();
+                assert_eq!(r, rc & !x.is_zero(), "input {i}: success flag");
+                let c = <$Fp2>::select(&<$Fp2>::ZERO, &c, r);
+                assert_eq!(y.equals(&c), u32::MAX, "input {i}: root");
+                let mut z = x;
+                assert_eq!(z.set_invsqrt(), r, "input {i}: set_invsqrt");
+                assert_eq!(z.equals(&y), u32::MAX, "input {i}: set_invsqrt");
+            }
+        }
+
         /// Fourth root: success and failure cases.
         #[test]
         fn fp2_test_fourth_root() {
@@ -1661,14 +2072,152 @@ macro_rules! define_fp2_tests {
             );
         }
 
+        /// `PartialEq`, `Hash` and `Ord`: consistent with `equals` and with
+        /// the canonical integers x1, then x0.
+        #[test]
+        fn fp2_test_eq_hash_ord() {
+            use ::std::hash::{BuildHasher as _, RandomState};
+
+            let hasher = RandomState::new();
+            let mut v = Vec::new();
+            for i in 0..100 {
+                let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
+                let b = <$Fp2>::decode_reduce(&fp2_test_vector(i + 1));
+                let (za0, za1) = fp2_decode_components(a.encode().as_ref());
+                let (zb0, zb1) = fp2_decode_components(b.encode().as_ref());
+                let c = a + <$Fp2>::ZERO;
+                assert!(a == c, "iter {i}: a should equal itself");
+                assert_eq!(a == b, a.equals(&b) != 0, "iter {i}: == mismatch");
+                assert_eq!(
+                    a.cmp(&b),
+                    (za1, za0).cmp(&(zb1, zb0)),
+                    "iter {i}: order mismatch"
+                );
+                assert_eq!(hasher.hash_one(a), hasher.hash_one(c), "iter {i}: hash");
+                v.push(a);
+                v.push(c);
+            }
+
+            // The imaginary part comes first.
+            assert!(<$Fp2>::MINUS_ONE < <$Fp2>::ZETA);
+            assert!(<$Fp2>::ZETA < <$Fp2>::ZETA + <$Fp2>::ONE);
+            v.sort();
+            v.dedup();
+            assert_eq!(v.len(), 100);
+            assert!(v.windows(2).all(|w| w[0] < w[1]));
+            let set: ::std::collections::HashSet<$Fp2> = v.iter().copied().collect();
+            assert_eq!(set.len(), 100);
+        }
+
+        /// Operators with elements of the base field and with integers, and
+        /// sums and products over iterators.
+        #[test]
+        fn fp2_test_mixed_ops_and_iter() {
+            let mut v = Vec::new();
+            for i in 0..100 {
+                let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
+                let k = <$Fp2>::decode_reduce(&fp2_test_vector(i + 1)).x1();
+                let ek = <$Fp2>::from(k);
+                assert!(
+                    ek.x0().equals(&k) != 0 && ek.x1().is_zero() != 0,
+                    "iter {i}"
+                );
+                assert!(a + k == a + ek && &a + &k == a + ek, "iter {i}: add base");
+                assert!(a - k == a - ek && &a - k == a - ek, "iter {i}: sub base");
+                assert!(a * k == a * ek && a * &k == a * ek, "iter {i}: mul base");
+                let mut c = a;
+                c += k;
+                c *= &k;
+                c -= k;
+                assert!(c == (a + ek) * ek - ek, "iter {i}: assign base");
+
+                let n = (i as i32).wrapping_mul(-0x2C2E_7A15);
+                let en = <$Fp2>::from(n);
+                assert!(a + n == a + en && n + a == a + en, "iter {i}: add i32");
+                assert!(a - n == a - en && n - &a == en - a, "iter {i}: sub i32");
+                assert!(a * n == a * en && n * &a == a * en, "iter {i}: mul i32");
+                for u in [i as u64, 1 << 31, u64::MAX - (i as u64)] {
+                    let eu = <$Fp2>::from(u);
+                    assert!(&a + u == a + eu && u + a == a + eu, "iter {i}: add u64");
+                    assert!(a - u == a - eu && u - a == eu - a, "iter {i}: sub u64");
+                    assert!(a * u == a * eu && u * a == a * eu, "iter {i}: mul u64");
+                }
+                let mut c = a;
+                c += 3;
+                c -= 5u64;
+                c *= -2;
+                c *= 7u64;
+                assert!(
+                    c == (a - <$Fp2>::TWO) * <$Fp2>::from(-14),
+                    "iter {i}: assign"
+                );
+                v.push(a);
+            }
+
+            let sum = v.iter().fold(<$Fp2>::ZERO, |acc, x| acc + x);
+            let prod = v.iter().fold(<$Fp2>::ONE, |acc, x| acc * x);
+            assert!(v.iter().sum::<$Fp2>() == sum && v.iter().copied().sum::<$Fp2>() == sum);
+            assert!(v.iter().product::<$Fp2>() == prod);
+            assert!(v.iter().copied().product::<$Fp2>() == prod);
+            assert!(::core::iter::empty::<$Fp2>().sum::<$Fp2>() == <$Fp2>::ZERO);
+            assert!(::core::iter::empty::<$Fp2>().product::<$Fp2>() == <$Fp2>::ONE);
+        }
+
+        /// Formatting traits: "x0 + x1*i" with canonical values in bases 16
+        /// and 10, the width and flags applying to each component.
+        #[test]
+        fn fp2_test_formatting() {
+            assert_eq!(format!("{}", <$Fp2>::ZETA), "0x0 + 0x1*i");
+            assert_eq!(format!("{}", <$Fp2>::ZETA.decimal()), "0 + 1*i");
+            for i in 0..100 {
+                let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
+                let (z0, z1) = fp2_decode_components(a.encode().as_ref());
+                let (h0, h1) = (z0.to_str_radix(16), z1.to_str_radix(16));
+                assert_eq!(format!("{}", a), format!("0x{h0} + 0x{h1}*i"), "iter {i}");
+                assert_eq!(format!("{:x}", a), format!("{h0} + {h1}*i"), "iter {i}");
+                assert_eq!(
+                    format!("{:#x}", a),
+                    format!("0x{h0} + 0x{h1}*i"),
+                    "iter {i}"
+                );
+                assert_eq!(
+                    format!("{:X}", a),
+                    format!("{} + {}*i", h0.to_uppercase(), h1.to_uppercase()),
+                    "iter {i}"
+                );
+                assert_eq!(
+                    format!("{}", a.decimal()),
+                    format!("{z0} + {z1}*i"),
+                    "iter {i}"
+                );
+                assert_eq!(
+                    format!("{:#070x}", a),
+                    format!("{z0:#070x} + {z1:#070x}*i"),
+                    "iter {i}"
+                );
+                assert_eq!(
+                    format!("{:_>80}", a.decimal()),
+                    format!("{z0:_>80} + {z1:_>80}*i"),
+                    "iter {i}"
+                );
+                let d = format!("{:?}", a);
+                assert!(
+                    d == format!("Fp2({a})") || d == "Fp2(<redacted>)",
+                    "iter {i}: Debug"
+                );
+            }
+        }
+
         #[test]
         fn test_fp2_xi_decomposition() {
             let y = <$Fp2>::from_u32_pair(123, 345);
             let (y0, y1) = y.xi();
-            assert_eq!(y.x0.equals(&y0), u32::MAX);
-            assert_eq!(y.x1.equals(&y1), u32::MAX);
-            assert_eq!(y.x0.equals(&y.x0()), u32::MAX);
-            assert_eq!(y.x1.equals(&y.x1()), u32::MAX);
+            assert_eq!(y.x0().equals(&y0), u32::MAX);
+            assert_eq!(y.x1().equals(&y1), u32::MAX);
+            let (enc, enc0, enc1) = (y.encode(), y0.encode(), y1.encode());
+            let len = enc0.as_ref().len();
+            assert_eq!(&enc.as_ref()[..len], enc0.as_ref());
+            assert_eq!(&enc.as_ref()[len..], enc1.as_ref());
         }
 
         /// Predefined constants: arithmetic identities.
@@ -1688,6 +2237,37 @@ macro_rules! define_fp2_tests {
             assert_ne!((minus_one * minus_one).equals(&one), 0, "(-1)*(-1) == 1");
         }
 
+        /// `pow_pubexp_limbs`: exponents wider than the base field, and
+        /// the group order p^2 - 1.
+        #[test]
+        fn fp2_test_pow_pubexp_limbs() {
+            for i in 0..30 {
+                let a = <$Fp2>::decode_reduce(&fp2_test_vector(i));
+                let v = fp2_test_vector(i + 1000);
+                for n in [0, 1, 2 * N - 1, 2 * N + 1] {
+                    let e: Vec<u64> = (0..n)
+                        .map(|j| u64::from_le_bytes(v[8 * j..8 * j + 8].try_into().unwrap()))
+                        .collect();
+                    let c = a.pow_pubexp_limbs(&e);
+                    let d = a.pow_limbs(&e, 64 * n);
+                    assert_eq!(c.equals(&d), u32::MAX, "iter {i}: {n}-limb exponent");
+                }
+                for e in [1u64, 2, 3, 0x1F, 0x20, 0x8000_0001] {
+                    let c = a.pow_pubexp_limbs(&[e, 0, 0]);
+                    assert_eq!(c.equals(&a.pow_u64_vartime(e)), u32::MAX, "iter {i}: e={e}");
+                }
+
+                // a^(p^2 - 1) = 1 for non-zero a.
+                let zp = fp2_modulus();
+                let (_, order) = (&zp * &zp - 1u32).to_u64_digits();
+                let c = a.pow_pubexp_limbs(&order);
+                assert_eq!(c.equals(&<$Fp2>::ONE), u32::MAX, "iter {i}: a^(p^2-1)");
+            }
+            let z = <$Fp2>::decode_reduce(&fp2_zero_vector());
+            assert_eq!(z.pow_pubexp_limbs(&[]).equals(&<$Fp2>::ONE), u32::MAX);
+            assert_eq!(z.pow_pubexp_limbs(&[0, 3]).is_zero(), u32::MAX);
+        }
+
         #[test]
         fn test_fp2_trait_static_methods() {
             use fp2::traits::Fp2;
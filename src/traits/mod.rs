@@ -2,11 +2,34 @@
 //!
 //! - Fq: defines arithmetic and constant time operations for a finite field GF(q)
 //! - FqExp: specialised trait for computing modular exponentiation
+//! - FqArrays: with the `nightly` feature, encodings and exponents as arrays sized by the associated constants
 //! - FqRnd: specialised trait for computing random elements in the field
 //! - FqRoots: specialised trait for computing roots in the field. Note `Fq` expects `set_sqrt()` and `sqrt` due to the commonality of their usage.
 //! - Fp2: a supertrait of Fq for the finite field GF(p^2) with modulus x^2 + 1
+//! - Fp2BaseField: what the generic type `fp2::Fp2` needs from its base field GF(p)
+use core::fmt::Display;
 use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
-use std::fmt::Display;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::fp::ByteArray;
+
+/// How to choose between the roots `y` and `-y` of an element: the chosen
+/// root is the one whose sign is 0 for the convention.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SignConvention {
+    /// `sgn0(y) = 0` as in RFC 9380: the least significant bit of `y` (as
+    /// an integer in `[0..p-1]`) is zero, or in GF(p^2), that of the "real
+    /// part", or of the "imaginary part" if the real part is zero. This is
+    /// the convention of `sqrt()` and `fourth_root()`.
+    Sgn0,
+    /// `y` is not lexicographically largest, i.e. `y` is at most (p-1)/2,
+    /// or in GF(p^2), the "imaginary part" is at most (p-1)/2, or the "real
+    /// part" if the imaginary part is zero (as in the Zcash serialization
+    /// of BLS12-381 points).
+    Positive,
+}
 
 /// Trait for Finite Field Arithmetic for the field GF(q). These are the core arithmetic and
 /// constant time operations which are expected to be implemented for all fields.
@@ -34,6 +57,13 @@ pub trait Fq:
     // TODO: i dont like including this, maybe refactor this away.
     const N: usize;
 
+    /// The encoded representation of the finite field element, which is
+    /// `[u8; Self::ENCODED_LENGTH]`.
+    type Encoding: Copy + AsRef<[u8]> + AsMut<[u8]>;
+
+    /// An array of `Self::N` words, which is `[u64; Self::N]`.
+    type Words: Copy + AsRef<[u64]> + AsMut<[u64]> + for<'a> TryFrom<&'a [u64]>;
+
     /// Predefined constant element representing the value 0.
     const ZERO: Self;
 
@@ -130,6 +160,54 @@ pub trait Fq:
     /// is not a quadratic residue, then this returns (zero, `0x00000000`).
     fn sqrt(self) -> (Self, u32);
 
+    /// Return `0xFFFFFFFF` if the sign of this value is 1 for `sgn0` of
+    /// RFC 9380, or `0x00000000` otherwise (see `SignConvention::Sgn0`).
+    /// The default implementation is for prime fields and returns the
+    /// least significant bit of the encoding.
+    fn sgn0(self) -> u32 {
+        ((self.encode().as_ref()[0] & 1) as u32).wrapping_neg()
+    }
+
+    /// Return `0xFFFFFFFF` if this value is lexicographically largest, or
+    /// `0x00000000` otherwise (see `SignConvention::Positive`). The default
+    /// implementation is for prime fields: x > (p-1)/2 exactly when 2*x,
+    /// reduced modulo p, is odd.
+    fn is_lexicographically_largest(self) -> u32 {
+        self.mul2().sgn0()
+    }
+
+    /// Return `0xFFFFFFFF` if the sign of this value is 1 for the
+    /// convention `sc`, or `0x00000000` otherwise.
+    fn sign(self, sc: SignConvention) -> u32 {
+        match sc {
+            SignConvention::Sgn0 => self.sgn0(),
+            SignConvention::Positive => self.is_lexicographically_largest(),
+        }
+    }
+
+    /// Negate this value if its sign is 1 for the convention `sc`, so that
+    /// it becomes the root of its square chosen by `sc`.
+    fn set_canonical_sign(&mut self, sc: SignConvention) {
+        let ctl = self.sign(sc);
+        self.set_cond_neg(ctl);
+    }
+
+    /// Set this value to its square root, as `set_sqrt()`, but choosing
+    /// the root with the sign convention `sc`.
+    fn set_sqrt_signed(&mut self, sc: SignConvention) -> u32 {
+        let r = self.set_sqrt();
+        self.set_canonical_sign(sc);
+        r
+    }
+
+    /// Compute the square root of this value, as `sqrt()`, but choosing
+    /// the root with the sign convention `sc`.
+    fn sqrt_signed(self, sc: SignConvention) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_sqrt_signed(sc);
+        (x, r)
+    }
+
     /// Legendre symbol on this value. Return value is:
     /// -  0   if this value is zero
     /// - +1   if this value is a non-zero quadratic residue
@@ -171,7 +249,7 @@ pub trait Fq:
 
     /// Encode this value into bytes. Encoding uses little-endian, has
     /// a fixed size (for a given field), and is canonical.
-    fn encode(self) -> [u8; Self::ENCODED_LENGTH];
+    fn encode(self) -> Self::Encoding;
 
     /// Decode the provided bytes into a field element. Returned values
     /// are the element and `0xFFFFFFFF` on success, or the zero element and
@@ -209,6 +287,24 @@ pub trait FqRoots: Fq {
     /// is zero (when normalized to an integer in `[0..p-1]`). If this value
     /// is not some element to the power of four, then this returns (zero, `0x00000000`).
     fn fourth_root(self) -> (Self, u32);
+
+    /// Set this value to its fourth root, as `set_fourth_root()`, but
+    /// choosing between the roots `y` and `-y` with the sign convention
+    /// `sc`.
+    fn set_fourth_root_signed(&mut self, sc: SignConvention) -> u32 {
+        let r = self.set_fourth_root();
+        self.set_canonical_sign(sc);
+        r
+    }
+
+    /// Compute the fourth root of this value, as `fourth_root()`, but
+    /// choosing between the roots `y` and `-y` with the sign convention
+    /// `sc`.
+    fn fourth_root_signed(self, sc: SignConvention) -> (Self, u32) {
+        let mut x = self;
+        let r = x.set_fourth_root_signed(sc);
+        (x, r)
+    }
 }
 
 /// Traits for computing exponentiations of finite field elements
@@ -230,7 +326,7 @@ pub trait FqExp: Fq {
 
     /// Raise this value to the provided exponent. The exponent is non-zero
     /// and is public. The exponent is encoded over N 64-bit limbs.
-    fn set_pow_pubexp(&mut self, e: &[u64; Self::N]);
+    fn set_pow_pubexp(&mut self, e: &Self::Words);
 
     /// Raise this value to the power `e`. The exponent is considered
     /// non-secret.
@@ -258,7 +354,50 @@ pub trait FqExp: Fq {
 
     /// Return this value to the provided exponent. The exponent is non-zero
     /// and is public. The exponent is encoded over N 64-bit limbs.
-    fn pow_pubexp(self, e: &[u64; Self::N]) -> Self;
+    fn pow_pubexp(self, e: &Self::Words) -> Self;
+}
+
+/// Methods of `Fq` and `FqExp` taking and returning arrays sized by the
+/// associated constants (`generic_const_exprs`, nightly only). These are
+/// implemented for all fields on top of `Fq::Encoding` and `Fq::Words`,
+/// which are the same arrays: a generic implementation such as `fp::Fp`
+/// cannot name the lengths in its own methods.
+#[cfg(feature = "nightly")]
+pub trait FqArrays: FqExp {
+    /// Encode this value into bytes, as `encode()`.
+    fn encode_array(self) -> [u8; Self::ENCODED_LENGTH];
+
+    /// Raise this value to the provided exponent, as `set_pow_pubexp()`.
+    /// The exponent is non-zero and is public. The exponent is encoded over
+    /// N 64-bit limbs.
+    fn set_pow_pubexp_array(&mut self, e: &[u64; Self::N]);
+
+    /// Return this value to the provided exponent, as `pow_pubexp()`. The
+    /// exponent is non-zero and is public. The exponent is encoded over N
+    /// 64-bit limbs.
+    fn pow_pubexp_array(self, e: &[u64; Self::N]) -> Self;
+}
+
+#[cfg(feature = "nightly")]
+impl<T: FqExp> FqArrays for T {
+    fn encode_array(self) -> [u8; Self::ENCODED_LENGTH] {
+        let mut d = [0u8; Self::ENCODED_LENGTH];
+        d.copy_from_slice(self.encode().as_ref());
+        d
+    }
+
+    fn set_pow_pubexp_array(&mut self, e: &[u64; Self::N]) {
+        match Self::Words::try_from(&e[..]) {
+            Ok(w) => self.set_pow_pubexp(&w),
+            Err(_) => unreachable!("Fq::Words has Fq::N words"),
+        }
+    }
+
+    fn pow_pubexp_array(self, e: &[u64; Self::N]) -> Self {
+        let mut x = self;
+        x.set_pow_pubexp_array(e);
+        x
+    }
 }
 
 /// Traits for obtaining random elements in a finite field
@@ -334,6 +473,7 @@ pub trait Fp2: Fq + FqExp + FqRoots + FqRnd {
     ///
     /// Note that the first value (`gpp[0]`) is `g` itself, and the last one must
     /// be `-1` (otherwise, `g` does not have order exactly 2^e).
+    #[cfg(feature = "alloc")]
     fn precompute_dlp_tables(self, n: usize) -> (Vec<usize>, Vec<Self>, u32);
 
     /// Find integer `v` (modulo 2^e) such that `x = self^v`. If self
@@ -345,10 +485,69 @@ pub trait Fp2: Fq + FqExp + FqRoots + FqRnd {
     ///
     /// Optionally include precomputed values from the method precompute_dlp_tables
     /// otherwise these are computed at runtime.
+    #[cfg(feature = "alloc")]
     fn solve_dlp_2e(
         self,
         x: &Self,
         e: usize,
         precomputed_tables: Option<(&Vec<usize>, &Vec<Self>)>,
     ) -> (Vec<u8>, u32);
+
+    /// Same as `precompute_dlp_tables`, but without allocation: the tables
+    /// are returned as arrays of `T` entries, and `T` MUST be at least
+    /// `fp2_gen::dlp_table_capacity(n)`. Unused entries are padded with
+    /// `usize::MAX` and zero, respectively.
+    fn precompute_dlp_tables_fixed<const T: usize>(self, n: usize) -> ([usize; T], [Self; T], u32);
+
+    /// Same as `solve_dlp_2e`, but without allocation: the tables are as
+    /// output by `precompute_dlp_tables_fixed`, and `v` is returned over `L`
+    /// bytes, where `L` MUST be at least `(e + 7) >> 3`.
+    fn solve_dlp_2e_fixed<const T: usize, const L: usize>(
+        self,
+        x: &Self,
+        e: usize,
+        precomputed_tables: Option<(&[usize; T], &[Self; T])>,
+    ) -> ([u8; L], u32);
+}
+
+/// Trait for the base field GF(p), with p = 3 mod 4, of the extension field
+/// `fp2::Fp2`. This is all the extension uses from its base field, so any
+/// implementation of GF(p) arithmetic (such as `fp::Fp`) can be extended with
+/// `define_fp2_from_type`.
+pub trait Fp2BaseField: Fq + FqExp + FqRoots + FqRnd {
+    /// The bit length of the characteristic p.
+    const BIT_LENGTH: usize;
+
+    /// The encoded representation of an element of GF(p^2), which MUST be
+    /// `[u8; 2 * Self::ENCODED_LENGTH]`.
+    type Fp2Encoding: ByteArray;
+
+    /// If `true`, multiplications in GF(p^2) use the schoolbook method
+    /// rather than `sum_of_products()` and `difference_of_products()`,
+    /// which is faster when these need an additional subtraction (or are
+    /// not optimised).
+    const SUM_OF_PRODUCTS_ADDITIONAL_SUB: bool = true;
+
+    /// Return `a1 * b1 + a2 * b2`.
+    fn sum_of_products(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        *a1 * *b1 + *a2 * *b2
+    }
+
+    /// Return `a1 * b1 - a2 * b2`.
+    fn difference_of_products(a1: &Self, b1: &Self, a2: &Self, b2: &Self) -> Self {
+        *a1 * *b1 - *a2 * *b2
+    }
+
+    /// Return this value to the power (p-3)/4, see `fp::Fp::progenitor()`.
+    /// The default implementation uses `pow()` with the exponent computed
+    /// from the encoding of -1.
+    fn progenitor(self) -> Self {
+        // p = 3 mod 4, thus (p-3)/4 = (p-1) >> 2.
+        let mut e = Self::MINUS_ONE.encode();
+        let e = e.as_mut();
+        for i in 0..e.len() {
+            e[i] = (e[i] >> 2) | e.get(i + 1).map_or(0, |&x| x << 6);
+        }
+        self.pow(e, Self::BIT_LENGTH)
+    }
 }
@@ -0,0 +1,49 @@
+//! Utility functions for performing arithmetic with `u32` words, for use in
+//! the 32-bit backend. All products are computed over `u64`, which every
+//! 32-bit target supports natively.
+
+// Add with carry; carry is 0 or 1.
+// (x, y, c_in) -> x + y + c_in mod 2^32, c_out
+#[inline(always)]
+pub const fn addcarry_u32(x: u32, y: u32, c: u8) -> (u32, u8) {
+    let z = (x as u64).wrapping_add(y as u64).wrapping_add(c as u64);
+    (z as u32, (z >> 32) as u8)
+}
+
+// Subtract with borrow; borrow is 0 or 1.
+// (x, y, c_in) -> x - y - c_in mod 2^32, c_out
+#[inline(always)]
+pub const fn subborrow_u32(x: u32, y: u32, c: u8) -> (u32, u8) {
+    let z = (x as u64).wrapping_sub(y as u64).wrapping_sub(c as u64);
+    (z as u32, (z >> 63) as u8)
+}
+
+// Compute x*y over 64 bits, returned as two 32-bit words (lo, hi)
+#[inline(always)]
+pub const fn umull(x: u32, y: u32) -> (u32, u32) {
+    let z = (x as u64) * (y as u64);
+    (z as u32, (z >> 32) as u32)
+}
+
+// Compute x*y+z over 64 bits, returned as two 32-bit words (lo, hi)
+#[inline(always)]
+pub const fn umull_add(x: u32, y: u32, z: u32) -> (u32, u32) {
+    let t = ((x as u64) * (y as u64)).wrapping_add(z as u64);
+    (t as u32, (t >> 32) as u32)
+}
+
+// Compute x*y+z1+z2 over 64 bits, returned as two 32-bit words (lo, hi)
+#[inline(always)]
+pub const fn umull_add2(x: u32, y: u32, z1: u32, z2: u32) -> (u32, u32) {
+    let t = ((x as u64) * (y as u64))
+        .wrapping_add(z1 as u64)
+        .wrapping_add(z2 as u64);
+    (t as u32, (t >> 32) as u32)
+}
+
+// Return 0xFFFFFFFF if x >= 0x80000000, 0 otherwise (i.e. take the sign
+// bit of the signed interpretation, and expand it to 32 bits).
+#[inline(always)]
+pub const fn sgnw(x: u32) -> u32 {
+    ((x as i32) >> 31) as u32
+}
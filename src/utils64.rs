@@ -156,3 +156,481 @@ pub const fn lzcnt(x: u64) -> u32 {
 
     s as u32
 }
+
+// Number of limbs below which mul_karatsuba() stops recursing and uses
+// schoolbook multiplication on the remaining operands.
+pub const KARATSUBA_LEAF_LIMBS: usize = 24;
+
+// Default number of limbs from which define_fp_core!() computes products
+// with mul_karatsuba() followed by a separate Montgomery reduction, rather
+// than with the interleaved Montgomery multiplication. Value obtained
+// from the benchmarks in benches/fp2_bench.rs on x86_64; it can be
+// overridden per field with the `karatsuba_threshold` macro parameter.
+pub const KARATSUBA_THRESHOLD: usize = 48;
+
+// Schoolbook product of a and b over plain integers. The output d MUST
+// have length a.len() + b.len().
+#[inline]
+pub fn mul_schoolbook(d: &mut [u64], a: &[u64], b: &[u64]) {
+    let m = b.len();
+    d.fill(0);
+    for (i, &f) in a.iter().enumerate() {
+        let di = &mut d[i..(i + m + 1)];
+        let mut cc = 0u64;
+        for (w, &y) in di[..m].iter_mut().zip(b) {
+            let (lo, hi) = umull_add2(f, y, *w, cc);
+            *w = lo;
+            cc = hi;
+        }
+        di[m] = cc;
+    }
+}
+
+// Number of scratch limbs needed by mul_karatsuba() for operands of n limbs.
+pub const fn karatsuba_scratch_len(n: usize) -> usize {
+    let mut n = n;
+    let mut s = 0;
+    while n >= KARATSUBA_LEAF_LIMBS {
+        let m = n - (n >> 1);
+        s += 4 * m + 1;
+        n = m;
+    }
+    s
+}
+
+// Karatsuba product of a and b over plain integers. Both operands MUST
+// have the same length n; the output d MUST have length 2*n, and the
+// scratch space t at least karatsuba_scratch_len(n) limbs. The recursion
+// only depends on n, and carries are handled with masks, so this is
+// constant-time.
+pub fn mul_karatsuba(d: &mut [u64], a: &[u64], b: &[u64], t: &mut [u64]) {
+    let n = a.len();
+    if n < KARATSUBA_LEAF_LIMBS {
+        mul_schoolbook(d, a, b);
+        return;
+    }
+
+    // Split a = a0 + a1*2^(64*h) and b = b0 + b1*2^(64*h), with a0 and b0
+    // over h limbs, and a1 and b1 over m >= h limbs.
+    let h = n >> 1;
+    let m = n - h;
+    let (a0, a1) = a.split_at(h);
+    let (b0, b1) = b.split_at(h);
+    let (sa, t) = t.split_at_mut(m);
+    let (sb, t) = t.split_at_mut(m);
+    let (z1, t) = t.split_at_mut(2 * m + 1);
+
+    // z0 = a0*b0 and z2 = a1*b1 go directly into the output.
+    {
+        let (d0, d2) = d.split_at_mut(2 * h);
+        mul_karatsuba(d0, a0, b0, t);
+        mul_karatsuba(d2, a1, b1, t);
+    }
+
+    // sa = a0 + a1 and sb = b0 + b1, with carries ca and cb.
+    let mut ca = 0;
+    let mut cb = 0;
+    for i in 0..m {
+        let x = if i < h { a0[i] } else { 0 };
+        let y = if i < h { b0[i] } else { 0 };
+        (sa[i], ca) = addcarry_u64(a1[i], x, ca);
+        (sb[i], cb) = addcarry_u64(b1[i], y, cb);
+    }
+
+    // z1 = (sa + ca*2^(64*m))*(sb + cb*2^(64*m))
+    mul_karatsuba(&mut z1[..2 * m], sa, sb, t);
+    let ma = (ca as u64).wrapping_neg();
+    let mb = (cb as u64).wrapping_neg();
+    let mut cc1 = 0;
+    let mut cc2 = 0;
+    for i in 0..m {
+        (z1[m + i], cc1) = addcarry_u64(z1[m + i], ma & sb[i], cc1);
+        (z1[m + i], cc2) = addcarry_u64(z1[m + i], mb & sa[i], cc2);
+    }
+    z1[2 * m] = (cc1 as u64) + (cc2 as u64) + (ma & mb & 1);
+
+    // z1 <- z1 - z0 - z2 = a0*b1 + a1*b0
+    let mut cc1 = 0;
+    let mut cc2 = 0;
+    for i in 0..(2 * m + 1) {
+        let x = if i < 2 * h { d[i] } else { 0 };
+        let y = if i < 2 * m { d[2 * h + i] } else { 0 };
+        (z1[i], cc1) = subborrow_u64(z1[i], x, cc1);
+        (z1[i], cc2) = subborrow_u64(z1[i], y, cc2);
+    }
+
+    // d <- d + z1*2^(64*h); the full product fits in 2*n limbs so the
+    // final carry is always zero.
+    let mut cc = 0;
+    for i in h..(2 * n) {
+        let x = if i - h < 2 * m + 1 { z1[i - h] } else { 0 };
+        (d[i], cc) = addcarry_u64(d[i], x, cc);
+    }
+}
+
+// Maximum number of words of a modulus given as a string to define_fp_core!().
+pub const MODULUS_STR_MAX_WORDS: usize = 128;
+
+// Parse an unsigned integer, given as a decimal string or as a hexadecimal
+// string with a "0x" prefix, into N little-endian words. Underscores are
+// ignored. Panics (i.e. fails the compilation, when used in a const context)
+// if the string is malformed or if the value does not fit in N words.
+pub const fn parse_words<const N: usize>(s: &str) -> [u64; N] {
+    let b = s.as_bytes();
+    let (radix, start) = if b.len() > 2 && b[0] == b'0' && (b[1] == b'x' || b[1] == b'X') {
+        (16u128, 2)
+    } else {
+        (10u128, 0)
+    };
+
+    let mut d = [0u64; N];
+    let mut empty = true;
+    let mut i = start;
+    while i < b.len() {
+        let c = b[i];
+        i += 1;
+        let v = match c {
+            b'_' => continue,
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' if radix == 16 => c - b'a' + 10,
+            b'A'..=b'F' if radix == 16 => c - b'A' + 10,
+            _ => panic!("invalid digit in modulus string"),
+        };
+        empty = false;
+
+        // d <- d*radix + v
+        let mut cc = v as u64;
+        let mut j = 0;
+        while j < N {
+            let z = (d[j] as u128) * radix + (cc as u128);
+            d[j] = z as u64;
+            cc = (z >> 64) as u64;
+            j += 1;
+        }
+        assert!(cc == 0, "modulus string is too large");
+    }
+    assert!(!empty, "empty modulus string");
+    d
+}
+
+// Return the number of words of the integer given as a string (see
+// parse_words()), ignoring leading zero words.
+pub const fn parse_words_len(s: &str) -> usize {
+    let d = parse_words::<MODULUS_STR_MAX_WORDS>(s);
+    let mut n = MODULUS_STR_MAX_WORDS;
+    while n > 1 && d[n - 1] == 0 {
+        n -= 1;
+    }
+    n
+}
+
+// Return -1/x mod 2^64, for an odd x.
+pub(crate) const fn ninv64(x: u64) -> u64 {
+    let mut y = 2u64.wrapping_sub(x);
+    let mut k = 0;
+    while k < 5 {
+        y = y.wrapping_mul(2u64.wrapping_sub(y.wrapping_mul(x)));
+        k += 1;
+    }
+    y.wrapping_neg()
+}
+
+// Montgomery multiplication a*b/2^(64*N) mod p over plain arrays, for use
+// in const contexts. p0i MUST be -1/p mod 2^64, and a, b MUST be less than p.
+pub(crate) const fn const_mmul<const N: usize>(
+    a: &[u64; N],
+    b: &[u64; N],
+    p: &[u64; N],
+    p0i: u64,
+) -> [u64; N] {
+    let mut d = [0u64; N];
+    let mut dh = 0u64;
+    let mut j = 0;
+    while j < N {
+        let bj = b[j] as u128;
+        let f = a[0].wrapping_mul(b[j]).wrapping_add(d[0]).wrapping_mul(p0i) as u128;
+        let mut cc1 = 0u128;
+        let mut cc2 = 0u128;
+        let mut i = 0;
+        while i < N {
+            let z = (a[i] as u128) * bj + (d[i] as u128) + cc1;
+            cc1 = z >> 64;
+            let z = (p[i] as u128) * f + ((z as u64) as u128) + cc2;
+            cc2 = z >> 64;
+            if i > 0 {
+                d[i - 1] = z as u64;
+            }
+            i += 1;
+        }
+        let z = (dh as u128) + cc1 + cc2;
+        d[N - 1] = z as u64;
+        dh = (z >> 64) as u64;
+        j += 1;
+    }
+
+    // The result is less than 2*p: subtract p if it is not less than p.
+    let mut r = [0u64; N];
+    let mut cc = 0u64;
+    let mut i = 0;
+    while i < N {
+        let z = (d[i] as u128)
+            .wrapping_sub(p[i] as u128)
+            .wrapping_sub(cc as u128);
+        r[i] = z as u64;
+        cc = ((z >> 64) as u64) & 1;
+        i += 1;
+    }
+    if dh == 0 && cc == 1 { d } else { r }
+}
+
+// Largest modulus bit length for which the compile-time checks run
+// `is_probable_prime`: const evaluation is slow, and a 1024-bit modulus
+// already takes about half a minute. Larger moduli skip the primality test,
+// and only get the other checks (odd, 3 mod 4, non-zero top word).
+pub const PRIMALITY_CHECK_MAX_BITS: usize = 1024;
+
+// Miller-Rabin bases of is_probable_prime(), the first twelve primes. They are
+// split in three groups, which define_fp_core tests in separate constants:
+// each constant evaluation has its own step budget.
+pub const PRIMALITY_BASES: [[u64; 4]; 3] = [[2, 3, 5, 7], [11, 13, 17, 19], [23, 29, 31, 37]];
+
+// Return 2*x mod p, for x < p (compile-time).
+pub(crate) const fn const_double_mod<const N: usize>(x: &[u64; N], p: &[u64; N]) -> [u64; N] {
+    let mut t = [0u64; N];
+    let mut hi = 0u64;
+    let mut i = 0;
+    while i < N {
+        t[i] = (x[i] << 1) | hi;
+        hi = x[i] >> 63;
+        i += 1;
+    }
+    // subtract p if t >= p (t < 2*p)
+    let mut r = [0u64; N];
+    let mut cc = 0u64;
+    let mut i = 0;
+    while i < N {
+        let z = (t[i] as u128)
+            .wrapping_sub(p[i] as u128)
+            .wrapping_sub(cc as u128);
+        r[i] = z as u64;
+        cc = ((z >> 64) as u64) & 1;
+        i += 1;
+    }
+    if hi == 0 && cc == 1 { t } else { r }
+}
+
+// Return 2^n mod p (compile-time), by doubling from 2^(bitlen-1) which is
+// less than p. The top word of p MUST be non-zero.
+pub(crate) const fn const_pow2_mod<const N: usize>(p: &[u64; N], n: usize) -> [u64; N] {
+    let bl = 64 * N - (p[N - 1].leading_zeros() as usize);
+    let mut d = [0u64; N];
+    if n < bl {
+        d[n >> 6] = 1u64 << (n & 63);
+        return d;
+    }
+    d[(bl - 1) >> 6] = 1u64 << ((bl - 1) & 63);
+    let mut k = bl - 1;
+    while k < n {
+        d = const_double_mod(&d, p);
+        k += 1;
+    }
+    d
+}
+
+// Compile-time primality test of a modulus, for `FieldParams`: None if p
+// is larger than PRIMALITY_CHECK_MAX_BITS bits, otherwise whether p is odd
+// and passes Miller-Rabin tests with the given bases (false if its top word
+// is zero).
+pub const fn check_prime<const N: usize>(p: &[u64; N], bases: &[u64]) -> Option<bool> {
+    if N == 0 || p[N - 1] == 0 || p[0] & 1 == 0 {
+        return Some(false);
+    }
+    if 64 * N - (p[N - 1].leading_zeros() as usize) > PRIMALITY_CHECK_MAX_BITS {
+        return None;
+    }
+    Some(miller_rabin(p, bases))
+}
+
+// Return true if p passes Miller-Rabin tests with the first twelve primes
+// (2 to 37) as bases. This is deterministic for p < 2^81 (no composite passes
+// all of them); larger composites pass with negligible probability unless
+// they are deliberately crafted against these bases.
+// For use in const contexts: p MUST be odd and its top word MUST be non-zero.
+pub const fn is_probable_prime<const N: usize>(p: &[u64; N]) -> bool {
+    miller_rabin(p, PRIMALITY_BASES.as_flattened())
+}
+
+// Return true if p passes Miller-Rabin tests with the given bases, which
+// MUST be less than p. p MUST be odd and its top word MUST be non-zero.
+pub const fn miller_rabin<const N: usize>(p: &[u64; N], bases: &[u64]) -> bool {
+    let p0i = ninv64(p[0]);
+
+    // Montgomery representation of 1, i.e. R = 2^(64*N) mod p; then R^2 mod
+    // p, by doubling 64*N more times.
+    let one = const_pow2_mod(p, 64 * N);
+    let mut r2 = one;
+    let mut k = 0;
+    while k < 64 * N {
+        r2 = const_double_mod(&r2, p);
+        k += 1;
+    }
+
+    // p - 1 in Montgomery representation is p - one
+    let mut minus_one = [0u64; N];
+    let mut cc = 0u64;
+    let mut i = 0;
+    while i < N {
+        let z = (p[i] as u128)
+            .wrapping_sub(one[i] as u128)
+            .wrapping_sub(cc as u128);
+        minus_one[i] = z as u64;
+        cc = ((z >> 64) as u64) & 1;
+        i += 1;
+    }
+
+    // p - 1 = d*2^s with d odd
+    let mut d = *p;
+    d[0] -= 1;
+    let mut s = 0;
+    while d[0] & 1 == 0 {
+        let mut i = 0;
+        while i < N - 1 {
+            d[i] = (d[i] >> 1) | (d[i + 1] << 63);
+            i += 1;
+        }
+        d[N - 1] >>= 1;
+        s += 1;
+    }
+    let dl = 64 * N - (d[N - 1].leading_zeros() as usize);
+
+    let mut b = 0;
+    while b < bases.len() {
+        // Work with the Montgomery representation a*R mod p of the base,
+        // obtained from the plain integer a with a Montgomery multiplication
+        // by R^2 mod p.
+        let mut a = [0u64; N];
+        a[0] = bases[b];
+        let a = const_mmul(&a, &r2, p, p0i);
+
+        // x = a^d, with a fixed 4-bit window (const evaluation is slow).
+        let mut aa = [one; 16];
+        let mut j = 1;
+        while j < 16 {
+            aa[j] = const_mmul(&aa[j - 1], &a, p, p0i);
+            j += 1;
+        }
+        let mut x = one;
+        let mut i = (dl + 3) & !3;
+        while i > 0 {
+            i -= 4;
+            let mut k = 0;
+            while k < 4 {
+                x = const_mmul(&x, &x, p, p0i);
+                k += 1;
+            }
+            let w = ((d[i >> 6] >> (i & 63)) & 15) as usize;
+            if w != 0 {
+                x = const_mmul(&x, &aa[w], p, p0i);
+            }
+        }
+
+        let mut ok = words_eq(&x, &one) || words_eq(&x, &minus_one);
+        let mut k = 1;
+        while !ok && k < s {
+            x = const_mmul(&x, &x, p, p0i);
+            ok = words_eq(&x, &minus_one);
+            k += 1;
+        }
+        if !ok {
+            return false;
+        }
+        b += 1;
+    }
+    true
+}
+
+// Return x mod d, for a non-zero d; x is in little-endian 64-bit limbs.
+pub const fn mod_small(x: &[u64], d: u64) -> u64 {
+    let mut m = 0u64;
+    let mut i = x.len();
+    while i > 0 {
+        i -= 1;
+        m = ((((m as u128) << 64) | (x[i] as u128)) % (d as u128)) as u64;
+    }
+    m
+}
+
+// Divide x (in little-endian 64-bit limbs) in place by a non-zero d, and
+// return the remainder.
+pub const fn div_small(x: &mut [u64], d: u64) -> u64 {
+    let mut m = 0u64;
+    let mut i = x.len();
+    while i > 0 {
+        i -= 1;
+        let z = ((m as u128) << 64) | (x[i] as u128);
+        x[i] = (z / (d as u128)) as u64;
+        m = (z % (d as u128)) as u64;
+    }
+    m
+}
+
+// Return gcd(a, b).
+pub const fn gcd_u64(a: u64, b: u64) -> u64 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Return the Jacobi symbol (x/n) (1, -1, or 0 if x and n are not coprime).
+// For use in const contexts: n MUST be odd.
+pub const fn jacobi_u64<const N: usize>(x: u64, n: &[u64; N]) -> i32 {
+    if x == 0 {
+        return 0;
+    }
+
+    // (2/n) = -1 if n = 3 or 5 mod 8
+    let mut t = 1;
+    let mut a = x;
+    while a & 1 == 0 {
+        a >>= 1;
+        if (n[0] & 7) == 3 || (n[0] & 7) == 5 {
+            t = -t;
+        }
+    }
+
+    // Quadratic reciprocity for odd a, then (n/a) = ((n mod a)/a).
+    if (a & 3) == 3 && (n[0] & 3) == 3 {
+        t = -t;
+    }
+    let m = mod_small(n, a);
+
+    // Both operands now fit in a word.
+    let (mut a, mut b) = (m, a);
+    while a != 0 {
+        while a & 1 == 0 {
+            a >>= 1;
+            if (b & 7) == 3 || (b & 7) == 5 {
+                t = -t;
+            }
+        }
+        (a, b) = (b, a);
+        if (a & 3) == 3 && (b & 3) == 3 {
+            t = -t;
+        }
+        a %= b;
+    }
+    if b == 1 { t } else { 0 }
+}
+
+const fn words_eq<const N: usize>(a: &[u64; N], b: &[u64; N]) -> bool {
+    let mut i = 0;
+    while i < N {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
@@ -0,0 +1,33 @@
+/* C API of a finite field GF(p^2), generated by fp2::define_fp2_c_api.
+ * Elements are passed as their canonical encodings over
+ * fp127_encoded_length() bytes. Functions returning
+ * uint32_t return 0xFFFFFFFF on success, or 0 on failure, in which
+ * case the output is set to zero. */
+#ifndef FP127_H
+#define FP127_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+size_t fp127_encoded_length(void);
+size_t fp127_limbs(void);
+uint32_t fp127_add(uint8_t *out, const uint8_t *a, const uint8_t *b);
+uint32_t fp127_sub(uint8_t *out, const uint8_t *a, const uint8_t *b);
+uint32_t fp127_mul(uint8_t *out, const uint8_t *a, const uint8_t *b);
+uint32_t fp127_sqr(uint8_t *out, const uint8_t *a);
+uint32_t fp127_inv(uint8_t *out, const uint8_t *a);
+uint32_t fp127_sqrt(uint8_t *out, const uint8_t *a);
+uint32_t fp127_pow(uint8_t *out, const uint8_t *a, const uint8_t *e, size_t elen);
+uint32_t fp127_encode(uint8_t *out, const uint64_t *limbs);
+uint32_t fp127_decode(uint64_t *limbs, const uint8_t *a);
+uint32_t fp127_solve_dlp_2e(uint8_t *v, const uint8_t *g, const uint8_t *x, size_t e);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,104 @@
+/* C driver for tests/test_c_api.rs: checks the functions exported by
+ * define_fp2_c_api for GF(p^2) with p = 2^127 - 1, from C. Returns 0 on
+ * success, or the number of the first failed check. */
+#include <string.h>
+
+#include "fp127.h"
+
+#define LEN 32
+
+/* x0 + i*x1 with small x0 and x1 */
+static void
+set_small(uint8_t *a, uint8_t x0, uint8_t x1)
+{
+	memset(a, 0, LEN);
+	a[0] = x0;
+	a[LEN / 2] = x1;
+}
+
+/* -x0 + i*x1 with small x0 and x1 */
+static void
+set_neg_small(uint8_t *a, uint8_t x0, uint8_t x1)
+{
+	memset(a, 0xFF, LEN / 2);
+	a[0] = 0xFF - x0;
+	a[LEN / 2 - 1] = 0x7F;
+	memset(a + LEN / 2, 0, LEN / 2);
+	a[LEN / 2] = x1;
+}
+
+int
+fp127_c_api_run(void)
+{
+	uint8_t zero[LEN], one[LEN], minus_one[LEN], i[LEN], minus_i[LEN];
+	uint8_t t[LEN], u[LEN], v[1];
+	uint64_t limbs[4];
+	uint8_t four = 4;
+
+	if (fp127_encoded_length() != LEN || fp127_limbs() != 2) {
+		return 1;
+	}
+	set_small(zero, 0, 0);
+	set_small(one, 1, 0);
+	set_neg_small(minus_one, 1, 0);
+	set_small(i, 0, 1);
+	set_small(minus_i, 0, 0);
+	if (fp127_sub(minus_i, zero, i) != 0xFFFFFFFF) {
+		return 2;
+	}
+
+	/* i^2 = -1, and i^4 = 1 */
+	if (fp127_mul(t, i, i) != 0xFFFFFFFF || memcmp(t, minus_one, LEN) != 0) {
+		return 3;
+	}
+	if (fp127_sqr(t, i) != 0xFFFFFFFF || memcmp(t, minus_one, LEN) != 0) {
+		return 4;
+	}
+	if (fp127_pow(t, i, &four, 1) != 0xFFFFFFFF || memcmp(t, one, LEN) != 0) {
+		return 5;
+	}
+
+	/* 1/i = -i, and i + (-i) = 0 */
+	if (fp127_inv(t, i) != 0xFFFFFFFF || memcmp(t, minus_i, LEN) != 0) {
+		return 6;
+	}
+	if (fp127_add(t, i, minus_i) != 0xFFFFFFFF || memcmp(t, zero, LEN) != 0) {
+		return 7;
+	}
+
+	/* sqrt(-1)^2 = -1 */
+	if (fp127_sqrt(t, minus_one) != 0xFFFFFFFF) {
+		return 8;
+	}
+	if (fp127_sqr(u, t) != 0xFFFFFFFF || memcmp(u, minus_one, LEN) != 0) {
+		return 9;
+	}
+
+	/* R = 2^128 = 2 mod p, so that 1 + 2*i is represented by 2 and 4 */
+	set_small(t, 1, 2);
+	if (fp127_decode(limbs, t) != 0xFFFFFFFF) {
+		return 10;
+	}
+	if (limbs[0] != 2 || limbs[1] != 0 || limbs[2] != 4 || limbs[3] != 0) {
+		return 11;
+	}
+	if (fp127_encode(u, limbs) != 0xFFFFFFFF || memcmp(u, t, LEN) != 0) {
+		return 12;
+	}
+
+	/* i has order 2^2, and i^2 = -1 */
+	if (fp127_solve_dlp_2e(v, i, minus_one, 2) != 0xFFFFFFFF || v[0] != 2) {
+		return 13;
+	}
+
+	/* p is not a canonical encoding, and the output is cleared */
+	memset(t, 0xFF, LEN / 2);
+	t[LEN / 2 - 1] = 0x7F;
+	memset(t + LEN / 2, 0, LEN / 2);
+	memset(u, 0xAA, LEN);
+	if (fp127_add(u, t, one) != 0 || memcmp(u, zero, LEN) != 0) {
+		return 14;
+	}
+
+	return 0;
+}
@@ -0,0 +1,193 @@
+// Addition chains for the fixed exponents of a field, derived at compile
+// time by define_fp_core or given as parameters.
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    use fp2::addchain::{self, ChainOp};
+
+    mod fp127_chain_tests {
+        use fp2::addchain::ChainOp::{self, Square};
+
+        // Field modulus
+        const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+
+        // (p+1)/4 = 2^125, given as two runs of squarings; the other chains
+        // are derived.
+        const SQRT_CHAIN: [ChainOp; 2] = [Square(0, 0, 120), Square(0, 0, 5)];
+
+        fp2::define_fp_core!(
+            typename = Fp127,
+            modulus = MODULUS,
+            backend = w32,
+            sqrt_chain = &SQRT_CHAIN,
+        );
+        fp2::define_fp_tests!(Fp127);
+
+        #[test]
+        fn check_chains() {
+            use fp2::fp::FieldParams;
+
+            let c = <Fp127Params as FieldParams<2>>::CHAINS.unwrap();
+            assert_eq!(c.sqrt, &SQRT_CHAIN);
+            // (p+1)/8 = 2^124
+            assert_eq!(c.fourth_root, &[Square(0, 0, 124)]);
+            // (p-1)/2 = 2^126 - 1 and p - 2 = 2^127 - 3
+            let e = fp2::addchain::chain_exponent::<2>(c.legendre);
+            assert_eq!(e, Some([u64::MAX, u64::MAX >> 2]));
+            let e = fp2::addchain::chain_exponent::<2>(c.inverse);
+            assert_eq!(e, Some([u64::MAX - 2, u64::MAX >> 1]));
+        }
+    }
+
+    // p = 5*2^248 - 1
+    const MODULUS251: [u64; 4] = [
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x04FFFFFFFFFFFFFF,
+    ];
+
+    fp2::define_fp_core!(typename = Fp251, modulus = MODULUS251,);
+
+    fn test_exponent(i: u64) -> [u64; 4] {
+        use ::sha2::Digest as _;
+        let buf = ::sha2::Sha256::digest(i.to_le_bytes());
+        core::array::from_fn(|j| u64::from_le_bytes(buf[8 * j..8 * j + 8].try_into().unwrap()))
+    }
+
+    fn derive_vec(e: &[u64]) -> Vec<ChainOp> {
+        let mut ops = vec![ChainOp::Square(0, 0, 0); addchain::derived_len(e)];
+        assert_eq!(addchain::derive_into(e, &mut ops), ops.len());
+        ops
+    }
+
+    #[test]
+    fn test_derived_chains() {
+        let x = Fp251::from(7u64).invert();
+        for i in 0..50 {
+            let mut e = test_exponent(i);
+            // Sparse and short exponents as well
+            match i % 5 {
+                1 => e = [1, 0, 0, 0],
+                2 => e[1..].fill(0),
+                3 => e = [e[0] & e[1] & e[2], 0, 0, e[3] & 0xFF00000000000000],
+                _ => {}
+            }
+            let ops = derive_vec(&e);
+            assert_eq!(addchain::chain_exponent::<4>(&ops), Some(e));
+            assert_eq!(x.pow_chain(&ops).equals(&x.pow_pubexp(&e)), u32::MAX);
+        }
+    }
+
+    #[test]
+    fn test_derived_chains_are_short() {
+        // A 4-bit fixed window uses 14 multiplications and squarings for
+        // the table, then one multiplication per non-zero chunk.
+        for e in [
+            addchain::sqrt_exponent(&MODULUS251),
+            addchain::legendre_exponent(&MODULUS251),
+            addchain::inverse_exponent(&MODULUS251),
+            test_exponent(1000),
+        ] {
+            let ops = derive_vec(&e);
+            let muls = ops
+                .iter()
+                .filter(|op| matches!(op, ChainOp::Mul(..)))
+                .count();
+            let chunks = e
+                .iter()
+                .flat_map(|w| (0..16).map(move |j| (w >> (4 * j)) & 15));
+            let window_muls = 7 + chunks.filter(|&c| c != 0).count() - 1;
+            assert!(muls < window_muls, "{muls} >= {window_muls}");
+        }
+    }
+
+    #[test]
+    fn test_temps_used() {
+        // Small windows only use the accumulator, the odd powers and x^2.
+        for e in [[49, 0, 0, 0], addchain::sqrt_exponent(&MODULUS251)] {
+            let ops = derive_vec(&e);
+            assert!(addchain::temps_used(&ops) <= 8, "{e:?}");
+        }
+        // At most 16 odd powers, for 5-bit windows.
+        for i in 0..20 {
+            let ops = derive_vec(&test_exponent(i));
+            assert!(addchain::temps_used(&ops) <= 18);
+        }
+        assert_eq!(addchain::temps_used(&[]), 1);
+        assert_eq!(addchain::temps_used(&[ChainOp::Mul(0, 3, 9)]), 10);
+    }
+
+    #[test]
+    fn test_select() {
+        use ChainOp::Mul;
+
+        // 4-bit window: 14 for the table, then 4 squarings per chunk and a
+        // multiplication per non-zero chunk.
+        assert_eq!(addchain::pow_pubexp_cost(&[1, 0]), 14);
+        assert_eq!(addchain::pow_pubexp_cost(&[0x10, 0]), 18);
+        assert_eq!(addchain::pow_pubexp_cost(&[0x15, 0]), 19);
+        assert_eq!(addchain::pow_pubexp_cost(&[0, 1]), 14 + 16 * 4);
+
+        // x^16 and x^21 with one multiplication per step.
+        static X16: [ChainOp; 15] = [Mul(0, 0, 1); 15];
+        static X21: [ChainOp; 20] = [Mul(0, 0, 1); 20];
+        assert_eq!(addchain::chain_cost(&X21), 20);
+        assert_eq!(addchain::select(&X16, &[16]), Some(&X16[..]));
+        assert_eq!(addchain::select(&X21, &[21]), None);
+
+        // The derived chain for (p-1)/2 beats the 4-bit window.
+        let e = addchain::legendre_exponent(&MODULUS251);
+        let ops = derive_vec(&e);
+        assert!(addchain::chain_cost(&ops) < addchain::pow_pubexp_cost(&e));
+    }
+
+    #[test]
+    fn test_chain_exponent() {
+        use ChainOp::{Mul, Square};
+
+        // x^3, then x^(3*2^4 + 1) = x^49 with temporaries
+        let ops = [Square(1, 0, 1), Mul(0, 0, 1), Square(2, 0, 4), Mul(0, 2, 3)];
+        assert_eq!(addchain::chain_exponent::<1>(&ops), Some([49]));
+        // Out of range temporary, or overflow
+        assert_eq!(addchain::chain_exponent::<1>(&[Mul(0, 0, 32)]), None);
+        assert_eq!(addchain::chain_exponent::<1>(&[Square(0, 0, 64)]), None);
+        assert_eq!(
+            addchain::chain_exponent::<2>(&[Square(0, 0, 64)]),
+            Some([0, 1])
+        );
+
+        let x = Fp251::from(3u64);
+        assert_eq!(x.pow_chain(&ops).equals(&x.pow_u64_vartime(49)), u32::MAX);
+        assert_eq!(x.pow_chain(&[]).equals(&x), u32::MAX);
+
+        // All temporaries start with x, whatever the number used.
+        for k in [1, 3, 4, 5, 8, 9, 16, 17, 31] {
+            let ops = [Mul(0, 0, k), Square(k, k, 1), Mul(0, k, 0)];
+            assert_eq!(
+                x.pow_chain(&ops).equals(&x.pow_u64_vartime(4)),
+                u32::MAX,
+                "k = {k}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_chain_out_of_range() {
+        let _ = Fp251::from(3u64).pow_chain(&[ChainOp::Mul(0, 0, 32)]);
+    }
+
+    #[test]
+    fn test_fixed_exponents() {
+        // p = 2^127 - 1
+        let m = [u64::MAX, u64::MAX >> 1];
+        assert_eq!(addchain::sqrt_exponent(&m), [0, 1 << 61]);
+        assert_eq!(addchain::fourth_root_exponent(&m), [0, 1 << 60]);
+        assert_eq!(addchain::legendre_exponent(&m), [u64::MAX, u64::MAX >> 2]);
+        assert_eq!(
+            addchain::inverse_exponent(&m),
+            [u64::MAX - 2, u64::MAX >> 1]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+// Export of GF(p^2) through a C ABI. The generated header is checked against
+// tests/c/fp127.h, and the exported functions are called both from Rust and,
+// with the c-api-test feature, from the C driver tests/c/test_c_api.c which is
+// compiled by build.rs.
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    // Field modulus
+    const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+
+    fp2::define_fp2_from_modulus!(typename = Fp127Ext, base_typename = Fp, modulus = MODULUS,);
+    fp2::define_fp2_c_api!(typename = Fp127Ext, prefix = fp127,);
+
+    const L: usize = Fp127Ext::ENCODED_LENGTH;
+
+    #[test]
+    fn test_c_header() {
+        assert_eq!(FP127_H, include_str!("c/fp127.h"));
+    }
+
+    #[test]
+    fn test_c_api_from_rust() {
+        use ::sha2::Digest as _;
+
+        assert_eq!(fp127_encoded_length(), 32);
+        assert_eq!(fp127_limbs(), 2);
+
+        let mut sh = ::sha2::Sha512::new();
+        for i in 0..20u64 {
+            sh.update(i.to_le_bytes());
+            let buf = sh.finalize_reset();
+            let a = Fp127Ext::decode_reduce(&buf[..32]);
+            let b = Fp127Ext::decode_reduce(&buf[32..]);
+            let (ea, eb) = (a.encode(), b.encode());
+            let mut out = [0u8; L];
+            unsafe {
+                assert_eq!(
+                    fp127_add(out.as_mut_ptr(), ea.as_ptr(), eb.as_ptr()),
+                    u32::MAX
+                );
+                assert_eq!(out, (a + b).encode());
+                assert_eq!(
+                    fp127_sub(out.as_mut_ptr(), ea.as_ptr(), eb.as_ptr()),
+                    u32::MAX
+                );
+                assert_eq!(out, (a - b).encode());
+                assert_eq!(
+                    fp127_mul(out.as_mut_ptr(), ea.as_ptr(), eb.as_ptr()),
+                    u32::MAX
+                );
+                assert_eq!(out, (a * b).encode());
+                assert_eq!(fp127_sqr(out.as_mut_ptr(), ea.as_ptr()), u32::MAX);
+                assert_eq!(out, a.square().encode());
+                assert_eq!(fp127_inv(out.as_mut_ptr(), ea.as_ptr()), u32::MAX);
+                assert_eq!(out, a.invert().encode());
+
+                let e = &buf[..16];
+                assert_eq!(
+                    fp127_pow(out.as_mut_ptr(), ea.as_ptr(), e.as_ptr(), 16),
+                    u32::MAX
+                );
+                assert_eq!(out, a.pow(e, 128).encode());
+
+                let sa = a.square().encode();
+                assert_eq!(fp127_sqrt(out.as_mut_ptr(), sa.as_ptr()), u32::MAX);
+                assert_eq!(Fp127Ext::decode(&out).0.square().encode(), sa);
+
+                let mut limbs = [0u64; 4];
+                assert_eq!(fp127_decode(limbs.as_mut_ptr(), ea.as_ptr()), u32::MAX);
+                let (x0, x1) = a.to_montgomery_limbs();
+                assert_eq!(limbs, [x0[0], x0[1], x1[0], x1[1]]);
+                assert_eq!(fp127_encode(out.as_mut_ptr(), limbs.as_ptr()), u32::MAX);
+                assert_eq!(out, ea);
+            }
+        }
+
+        // Non-canonical inputs and non-squares fail and clear the output.
+        let p = [0xFFu8; L];
+        let one = Fp127Ext::ONE.encode();
+        let mut out = [0xAAu8; L];
+        unsafe {
+            assert_eq!(fp127_add(out.as_mut_ptr(), p.as_ptr(), one.as_ptr()), 0);
+            assert_eq!(out, [0u8; L]);
+            out.fill(0xAA);
+            let ns = Fp127Ext::from(3u64).invert().encode();
+            let (_, ok) = Fp127Ext::decode(&ns).0.sqrt();
+            assert_eq!(fp127_sqrt(out.as_mut_ptr(), ns.as_ptr()), ok);
+            let limbs = [u64::MAX; 4];
+            assert_eq!(fp127_encode(out.as_mut_ptr(), limbs.as_ptr()), 0);
+        }
+
+        // i has order 4, and i = (-1)^(1/2)
+        let g = Fp127Ext::ZETA.encode();
+        let x = Fp127Ext::MINUS_ONE.encode();
+        let mut v = [0u8; 1];
+        unsafe {
+            assert_eq!(
+                fp127_solve_dlp_2e(v.as_mut_ptr(), g.as_ptr(), x.as_ptr(), 2),
+                u32::MAX
+            );
+            assert_eq!(v, [2]);
+            assert_eq!(
+                fp127_solve_dlp_2e(v.as_mut_ptr(), g.as_ptr(), x.as_ptr(), 0),
+                0
+            );
+        }
+    }
+
+    #[cfg(feature = "c-api-test")]
+    #[link(name = "fp2_c_api_test", kind = "static")]
+    unsafe extern "C" {
+        fn fp127_c_api_run() -> i32;
+    }
+
+    #[cfg(feature = "c-api-test")]
+    #[test]
+    fn test_c_api_from_c() {
+        assert_eq!(unsafe { fp127_c_api_run() }, 0);
+    }
+}
@@ -81,4 +81,62 @@ mod dlp_tests {
             assert!(z.equals(&y) == u32::MAX);
         }
     }
+
+    #[test]
+    fn test_dlp_n_fixed() {
+        // x is a precomputed element of order 2^248
+        let x = X;
+        let mut y = FpExt::ONE;
+
+        const T: usize = fp2::fp2_gen::dlp_table_capacity(248);
+        let (dlp_table, ele_table, check) = x.precompute_dlp_tables_fixed::<T>(248);
+        assert!(check == u32::MAX);
+
+        // The fixed-capacity tables match the allocated ones, up to padding
+        let (dlp_vec, ele_vec, _) = x.precompute_dlp_tables(248);
+        assert!(dlp_table[..dlp_vec.len()] == dlp_vec[..]);
+        assert!(dlp_table[dlp_vec.len()..].iter().all(|&j| j == usize::MAX));
+        for (a, b) in ele_table.iter().zip(ele_vec.iter()) {
+            assert!(a.equals(b) == u32::MAX);
+        }
+
+        for _ in 0..25 {
+            compute_challenge(&x, &mut y);
+
+            let (exp, check) =
+                x.solve_dlp_2e_fixed::<T, 31>(&y, 248, Some((&dlp_table, &ele_table)));
+            let z = x.pow(&exp, 248);
+            assert!(check == u32::MAX);
+            assert!(z.equals(&y) == u32::MAX);
+
+            let (exp2, check) = x.solve_dlp_2e_fixed::<T, 32>(&y, 248, None);
+            assert!(check == u32::MAX);
+            assert!(exp2[..31] == exp && exp2[31] == 0);
+        }
+    }
+
+    #[test]
+    fn test_dlp_root_of_unity() {
+        // p = 5*2^248 - 1, so the 2-power subgroup of GF(p^2)* has order 2^249
+        const { assert!(FpExt::TWO_ADICITY == 249) };
+        let g = FpExt::TWO_ADIC_GENERATOR;
+        let (exp, check) = g.solve_dlp_2e(&X.square(), 249, None);
+        assert!(check == u32::MAX);
+        assert!(g.pow(&exp, 249).equals(&X.square()) == u32::MAX);
+
+        // X is a power of the primitive 2^248-th root of unity
+        let r = FpExt::root_of_unity(248);
+        let (exp, check) = r.solve_dlp_2e(&X, 248, None);
+        assert!(check == u32::MAX);
+        assert!(r.pow(&exp, 248).equals(&X) == u32::MAX);
+    }
+
+    #[test]
+    fn test_dlp_n_fixed_wrong_order() {
+        // x^2 has order 2^247, not 2^248
+        let x = X.square();
+        const T: usize = fp2::fp2_gen::dlp_table_capacity(248);
+        let (_, check) = x.solve_dlp_2e_fixed::<T, 31>(&X, 248, None);
+        assert!(check == 0);
+    }
 }
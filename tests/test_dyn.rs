@@ -0,0 +1,215 @@
+// Compare the runtime-modulus types FpDyn and Fp2Dyn against the types
+// generated by the macros for the same modulus.
+macro_rules! define_dyn_tests {
+    ($Fp:ty, $FpExt:ty, $modulus:expr) => {
+        use ::sha2::Digest as _;
+        use fp2::fp_dyn::{Fp2Dyn, FpCtx, FpDyn};
+        use fp2::traits::SignConvention;
+
+        const N: usize = <$Fp>::N;
+
+        fn test_vector(i: usize) -> Vec<u8> {
+            let len = (<$Fp>::ENCODED_LENGTH + 64) & !31usize;
+            let mut buf = vec![0u8; len];
+            let mut sh = ::sha2::Sha256::new();
+            for j in 0..(len >> 5) {
+                sh.update([i as u64, j as u64].map(u64::to_le_bytes).concat());
+                buf[(32 * j)..(32 * j + 32)].copy_from_slice(&sh.finalize_reset());
+            }
+            buf
+        }
+
+        fn enc(x: FpDyn<'_, N>) -> Vec<u8> {
+            let mut buf = vec![0u8; x.ctx().encoded_length()];
+            x.encode_into(&mut buf);
+            buf
+        }
+
+        fn enc2(x: Fp2Dyn<'_, N>) -> Vec<u8> {
+            let mut buf = vec![0u8; 2 * x.ctx().encoded_length()];
+            x.encode_into(&mut buf);
+            buf
+        }
+
+        #[test]
+        fn test_ctx() {
+            let ctx = FpCtx::new(&$modulus).unwrap();
+            assert_eq!(ctx.modulus(), &$modulus);
+            assert_eq!(ctx.bit_length(), <$Fp>::BIT_LENGTH);
+            assert_eq!(ctx.encoded_length(), <$Fp>::ENCODED_LENGTH);
+            assert_eq!(enc(FpDyn::one(&ctx)), <$Fp>::ONE.encode().as_ref());
+            assert_eq!(
+                enc(FpDyn::minus_one(&ctx)),
+                <$Fp>::MINUS_ONE.encode().as_ref()
+            );
+            assert_eq!(
+                enc(FpDyn::from_i64(&ctx, -7)),
+                <$Fp>::from(-7i64).encode().as_ref()
+            );
+            assert_eq!(
+                enc(FpDyn::from_u64(&ctx, u64::MAX)),
+                <$Fp>::from(u64::MAX).encode().as_ref()
+            );
+        }
+
+        #[test]
+        fn test_fp_dyn() {
+            let ctx = FpCtx::new(&$modulus).unwrap();
+            for i in 0..30 {
+                let (va, vb) = (test_vector(2 * i), test_vector(2 * i + 1));
+                let (a, b) = (<$Fp>::decode_reduce(&va), <$Fp>::decode_reduce(&vb));
+                let ad = FpDyn::decode_reduce(&ctx, &va);
+                let bd = FpDyn::decode_reduce(&ctx, &vb);
+                assert_eq!(enc(ad), a.encode().as_ref());
+                assert_eq!(enc(bd), b.encode().as_ref());
+
+                assert_eq!(enc(ad + bd), (a + b).encode().as_ref());
+                assert_eq!(enc(ad - bd), (a - b).encode().as_ref());
+                assert_eq!(enc(-ad), (-a).encode().as_ref());
+                assert_eq!(enc(ad * bd), (a * b).encode().as_ref());
+                assert_eq!(enc(ad / bd), (a / b).encode().as_ref());
+                assert_eq!(enc(ad.square()), a.square().encode().as_ref());
+                assert_eq!(enc(ad.half()), a.half().encode().as_ref());
+                assert_eq!(enc(ad.mul_small(-5)), a.mul_small(-5).encode().as_ref());
+                assert_eq!(enc(ad.invert()), a.invert().encode().as_ref());
+                assert_eq!(ad.legendre(), a.legendre());
+                assert_eq!(ad.is_square(), a.is_square());
+                let (s, ok) = a.sqrt();
+                let (sd, okd) = ad.sqrt();
+                assert_eq!(okd, ok);
+                assert_eq!(enc(sd), s.encode().as_ref());
+                assert_eq!(ad.is_odd(), a.is_odd());
+                assert_eq!(ad.sgn0(), a.sgn0());
+                assert_eq!(
+                    ad.is_lexicographically_largest(),
+                    a.is_lexicographically_largest()
+                );
+                let (s, _) = a.square().sqrt_signed(SignConvention::Positive);
+                let (sd, _) = ad.square().sqrt_signed(SignConvention::Positive);
+                assert_eq!(enc(sd), s.encode().as_ref());
+                assert_eq!(ad.hashcode(), a.hashcode());
+                assert_eq!(format!("{}", ad), format!("{}", a));
+
+                let e = &vb[..16];
+                assert_eq!(enc(ad.pow(e, 128)), a.pow(e, 128).encode().as_ref());
+                assert_eq!(
+                    enc(ad.pow_pubexp(&[3, 5])),
+                    (a * a * a * (a.square().square() * a).n_square(64))
+                        .encode()
+                        .as_ref()
+                );
+
+                let (cd, ok) = FpDyn::decode(&ctx, &a.encode().as_ref());
+                assert_eq!(ok, u32::MAX);
+                assert_eq!(cd.equals(&ad), u32::MAX);
+            }
+
+            // Non-canonical and wrong-length encodings are rejected.
+            let mut buf = vec![0xFFu8; <$Fp>::ENCODED_LENGTH];
+            assert_eq!(FpDyn::decode(&ctx, &buf).1, 0);
+            buf.push(0);
+            assert_eq!(FpDyn::decode(&ctx, &buf).1, 0);
+            assert_eq!(FpDyn::zero(&ctx).invert().is_zero(), u32::MAX);
+            assert_eq!(FpDyn::zero(&ctx).legendre(), 0);
+        }
+
+        #[test]
+        fn test_fp2_dyn() {
+            let ctx = FpCtx::new(&$modulus).unwrap();
+            for i in 0..30 {
+                let (va, vb) = (test_vector(2 * i), test_vector(2 * i + 1));
+                let a = <$FpExt>::decode_reduce(&va);
+                let b = <$FpExt>::decode_reduce(&vb);
+                let ad = Fp2Dyn::decode_reduce(&ctx, &va);
+                let bd = Fp2Dyn::decode_reduce(&ctx, &vb);
+                assert_eq!(enc2(ad), a.encode().as_ref());
+                assert_eq!(enc2(bd), b.encode().as_ref());
+
+                assert_eq!(enc2(ad + bd), (a + b).encode().as_ref());
+                assert_eq!(enc2(ad - bd), (a - b).encode().as_ref());
+                assert_eq!(enc2(ad * bd), (a * b).encode().as_ref());
+                assert_eq!(enc2(ad / bd), (a / b).encode().as_ref());
+                assert_eq!(enc2(ad.square()), a.square().encode().as_ref());
+                assert_eq!(enc2(ad.conjugate()), a.conjugate().encode().as_ref());
+                assert_eq!(ad.legendre(), a.legendre());
+                assert_eq!(ad.hashcode(), a.hashcode());
+                let (s, ok) = a.sqrt();
+                let (sd, okd) = ad.sqrt();
+                assert_eq!(okd, ok);
+                assert_eq!(enc2(sd), s.encode().as_ref());
+                assert_eq!(ad.sgn0(), a.sgn0());
+                assert_eq!(
+                    ad.is_lexicographically_largest(),
+                    a.is_lexicographically_largest()
+                );
+                let (s, _) = a.square().sqrt_signed(SignConvention::Positive);
+                let (sd, _) = ad.square().sqrt_signed(SignConvention::Positive);
+                assert_eq!(enc2(sd), s.encode().as_ref());
+
+                // Square roots of elements of the base field, and of their
+                // opposites.
+                let x = Fp2Dyn::new(&ad.x0(), &FpDyn::zero(&ctx));
+                let y = <$FpExt>::new(&a.x0(), &<$Fp>::ZERO);
+                for (x, y) in [(x, y), (-x, -y)] {
+                    let (s, ok) = y.sqrt();
+                    let (sd, okd) = x.sqrt();
+                    assert_eq!(okd, ok);
+                    assert_eq!(enc2(sd), s.encode().as_ref());
+                    assert_eq!(x.is_square_base_field(), y.is_square_base_field());
+                }
+
+                let (cd, ok) = Fp2Dyn::decode(&ctx, &a.encode().as_ref());
+                assert_eq!(ok, u32::MAX);
+                assert_eq!(cd.equals(&ad), u32::MAX);
+            }
+            assert_eq!(
+                (Fp2Dyn::zeta(&ctx).square() + Fp2Dyn::one(&ctx)).is_zero(),
+                u32::MAX
+            );
+        }
+    };
+}
+
+mod dyn_ugly_tests {
+    const MODULUS: [u64; 2] = [0x5A0E852097C48043, 0x7EA2A3A646684E9D];
+    fp2::define_fp2_from_modulus!(typename = FpExt, base_typename = Fp, modulus = MODULUS,);
+
+    define_dyn_tests!(Fp, FpExt, MODULUS);
+}
+
+mod dyn_fp95_tests {
+    // The top word of this modulus is small.
+    const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFDB, 0x000000007FFFFFFF];
+    fp2::define_fp2_from_modulus!(typename = FpExt, base_typename = Fp, modulus = MODULUS,);
+
+    define_dyn_tests!(Fp, FpExt, MODULUS);
+}
+
+mod dyn_fp251_tests {
+    const MODULUS: [u64; 4] = [
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x04FFFFFFFFFFFFFF,
+    ];
+    fp2::define_fp2_from_modulus!(typename = FpExt, base_typename = Fp, modulus = MODULUS,);
+
+    define_dyn_tests!(Fp, FpExt, MODULUS);
+}
+
+#[test]
+fn test_ctx_rejects() {
+    use fp2::fp_dyn::FpCtx;
+
+    // p = 1 mod 4
+    assert!(FpCtx::new(&[45u64, 0x8000000000000000]).is_none());
+    // composite
+    assert!(FpCtx::new(&[0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFB]).is_none());
+    // zero top word
+    assert!(FpCtx::new(&[0xFFFFFFFFFFFFFFC5, 0]).is_none());
+    // single-word primes p = 3 mod 4, as rejected by define_fp_core
+    assert!(FpCtx::new(&[0x1FFFFFFFFFFFFFFF]).is_none());
+    assert!(FpCtx::new(&[7u64]).is_none());
+    assert!(FpCtx::<0>::new(&[]).is_none());
+    assert!(FpCtx::new(&[0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF]).is_some());
+}
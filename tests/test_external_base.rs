@@ -0,0 +1,242 @@
+// An external implementation of GF(p), extended to GF(p^2) with
+// define_fp2_from_type through the Fp2BaseField trait only. The base field
+// wraps a field generated by define_fp_core and only forwards the trait
+// methods, so that the extension cannot rely on anything else, and keeps the
+// default sum of products of the trait.
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    use core::fmt;
+    use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+    use fp2::traits::{Fp2BaseField, Fq, FqExp, FqRnd, FqRoots};
+
+    // Field modulus
+    const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+
+    fp2::define_fp_core!(typename = Fp127, modulus = MODULUS,);
+
+    #[derive(Clone, Copy, Debug)]
+    pub struct Base(Fp127);
+
+    macro_rules! impl_binop {
+        ($tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident) => {
+            impl $tr for Base {
+                type Output = Base;
+
+                fn $f(self, other: Base) -> Base {
+                    Base(self.0.$f(other.0))
+                }
+            }
+
+            impl $tr_assign for Base {
+                fn $f_assign(&mut self, other: Base) {
+                    self.0.$f_assign(other.0)
+                }
+            }
+        };
+    }
+
+    impl_binop!(Add, add, AddAssign, add_assign);
+    impl_binop!(Sub, sub, SubAssign, sub_assign);
+    impl_binop!(Mul, mul, MulAssign, mul_assign);
+    impl_binop!(Div, div, DivAssign, div_assign);
+
+    impl Neg for Base {
+        type Output = Base;
+
+        fn neg(self) -> Base {
+            Base(-self.0)
+        }
+    }
+
+    impl fmt::Display for Base {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    macro_rules! impl_from {
+        ($($t:ty),*) => {
+            $(
+                impl From<$t> for Base {
+                    fn from(x: $t) -> Base {
+                        Base(Fp127::from(x))
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_from!(u32, u64, i32, i64);
+
+    // Forward methods to the wrapped field, as trait methods.
+    macro_rules! forward {
+        ($tr:ident: $(fn $f:ident(&mut self $(, $a:ident: $t:ty)*) $(-> $r:ty)?;)*) => {
+            $(
+                fn $f(&mut self $(, $a: $t)*) $(-> $r)? {
+                    <Fp127 as $tr>::$f(&mut self.0 $(, $a)*)
+                }
+            )*
+        };
+        ($tr:ident: $(fn $f:ident(self $(, $a:ident: $t:ty)*) -> $r:ty;)*) => {
+            $(
+                fn $f(self $(, $a: $t)*) -> $r {
+                    Base(<Fp127 as $tr>::$f(self.0 $(, $a)*))
+                }
+            )*
+        };
+    }
+
+    impl Fq for Base {
+        const ENCODED_LENGTH: usize = <Fp127 as Fq>::ENCODED_LENGTH;
+        const N: usize = <Fp127 as Fq>::N;
+        type Encoding = <Fp127 as Fq>::Encoding;
+        type Words = <Fp127 as Fq>::Words;
+        const ZERO: Self = Base(Fp127::ZERO);
+        const ONE: Self = Base(Fp127::ONE);
+        const TWO: Self = Base(Fp127::TWO);
+        const THREE: Self = Base(Fp127::THREE);
+        const FOUR: Self = Base(Fp127::FOUR);
+        const MINUS_ONE: Self = Base(Fp127::MINUS_ONE);
+
+        forward!(Fq:
+            fn set_neg(&mut self);
+            fn set_half(&mut self);
+            fn set_mul2(&mut self);
+            fn set_mul3(&mut self);
+            fn set_mul4(&mut self);
+            fn set_mul8(&mut self);
+            fn set_mul_small(&mut self, k: i32);
+            fn set_square(&mut self);
+            fn set_n_square(&mut self, n: u32);
+            fn set_invert(&mut self);
+            fn set_sqrt(&mut self) -> u32;
+            fn set_cond_neg(&mut self, ctl: u32);
+        );
+        forward!(Fq:
+            fn half(self) -> Self;
+            fn mul2(self) -> Self;
+            fn mul3(self) -> Self;
+            fn mul4(self) -> Self;
+            fn mul8(self) -> Self;
+            fn mul_small(self, k: i32) -> Self;
+            fn square(self) -> Self;
+            fn n_square(self, n: u32) -> Self;
+            fn invert(self) -> Self;
+        );
+
+        fn is_zero(self) -> u32 {
+            self.0.is_zero()
+        }
+        fn equals(self, rhs: &Self) -> u32 {
+            self.0.equals(&rhs.0)
+        }
+        fn sqrt(self) -> (Self, u32) {
+            let (r, ok) = self.0.sqrt();
+            (Base(r), ok)
+        }
+        fn legendre(self) -> i32 {
+            self.0.legendre()
+        }
+        fn is_square(self) -> u32 {
+            self.0.is_square()
+        }
+        fn batch_invert(xx: &mut [Self]) {
+            for x in xx.iter_mut() {
+                x.set_invert();
+            }
+        }
+        fn set_select(&mut self, a: &Self, b: &Self, ctl: u32) {
+            self.0.set_select(&a.0, &b.0, ctl)
+        }
+        fn set_cond(&mut self, rhs: &Self, ctl: u32) {
+            self.0.set_cond(&rhs.0, ctl)
+        }
+        fn select(a: &Self, b: &Self, ctl: u32) -> Self {
+            Base(Fp127::select(&a.0, &b.0, ctl))
+        }
+        fn cond_swap(a: &mut Self, b: &mut Self, ctl: u32) {
+            Fp127::cond_swap(&mut a.0, &mut b.0, ctl)
+        }
+        fn encode(self) -> Self::Encoding {
+            self.0.encode()
+        }
+        fn decode(buf: &[u8]) -> (Self, u32) {
+            let (r, ok) = Fp127::decode(buf);
+            (Base(r), ok)
+        }
+        fn decode_reduce(buf: &[u8]) -> Self {
+            Base(Fp127::decode_reduce(buf))
+        }
+        fn hashcode(self) -> u64 {
+            self.0.hashcode()
+        }
+    }
+
+    impl FqExp for Base {
+        forward!(FqExp:
+            fn set_pow(&mut self, e: &[u8], ebitlen: usize);
+            fn set_pow_ext(&mut self, e: &[u8], eoff: usize, ebitlen: usize);
+            fn set_pow_u64(&mut self, e: u64, ebitlen: usize);
+            fn set_pow_pubexp(&mut self, e: &Self::Words);
+            fn set_pow_u64_vartime(&mut self, e: u64);
+        );
+        forward!(FqExp:
+            fn pow(self, e: &[u8], ebitlen: usize) -> Self;
+            fn pow_ext(self, e: &[u8], eoff: usize, ebitlen: usize) -> Self;
+            fn pow_u64(self, e: u64, ebitlen: usize) -> Self;
+            fn pow_u64_vartime(self, e: u64) -> Self;
+            fn pow_pubexp(self, e: &Self::Words) -> Self;
+        );
+    }
+
+    impl FqRoots for Base {
+        fn set_fourth_root(&mut self) -> u32 {
+            self.0.set_fourth_root()
+        }
+        fn fourth_root(self) -> (Self, u32) {
+            let (r, ok) = self.0.fourth_root();
+            (Base(r), ok)
+        }
+    }
+
+    impl FqRnd for Base {
+        fn set_rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(&mut self, rng: &mut R) {
+            self.0.set_rand(rng)
+        }
+        fn rand<R: ::rand_core::CryptoRng + ::rand_core::RngCore>(rng: &mut R) -> Self {
+            Base(Fp127::rand(rng))
+        }
+    }
+
+    impl Fp2BaseField for Base {
+        const BIT_LENGTH: usize = Fp127::BIT_LENGTH;
+        type Fp2Encoding = [u8; 2 * Fp127::ENCODED_LENGTH];
+    }
+
+    fp2::define_fp2_from_type!(typename = BaseExt, base_field = Base,);
+    fp2::define_fp2_tests!(BaseExt, MODULUS, 2);
+
+    // Same extension over the generated field, to compare against.
+    fp2::define_fp2_from_type!(typename = Fp127Ext, base_field = Fp127,);
+
+    #[test]
+    fn test_external_base_matches() {
+        use ::sha2::Digest as _;
+
+        let mut sh = ::sha2::Sha512::new();
+        for i in 0..100u64 {
+            sh.update(i.to_le_bytes());
+            let buf = sh.finalize_reset();
+            let a = Fp127Ext::decode_reduce(&buf[..32]);
+            let b = Fp127Ext::decode_reduce(&buf[32..]);
+            let xa = BaseExt::decode_reduce(&buf[..32]);
+            let xb = BaseExt::decode_reduce(&buf[32..]);
+            assert_eq!(xa.encode(), a.encode());
+            assert_eq!((xa * xb).encode(), (a * b).encode());
+            assert_eq!(xa.mul_sum_of_products(&xb).encode(), (a * b).encode());
+            assert_eq!((xa / xb).encode(), (a / b).encode());
+            assert_eq!(xa.square().sqrt().0.encode(), a.square().sqrt().0.encode());
+        }
+    }
+}
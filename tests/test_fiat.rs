@@ -0,0 +1,164 @@
+// Fields defined with define_fp_from_fiat. The functions below follow the
+// interface of the Rust code generated by fiat-crypto with
+//   word_by_word_montgomery --lang Rust p127 64 '2^127 - 1'
+// (the generated code itself is not a dependency of this crate), and count
+// their calls to check that they are actually used.
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+#[allow(non_camel_case_types)]
+mod p127_64 {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    const M: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+
+    #[derive(Clone, Copy)]
+    pub struct fiat_p127_montgomery_domain_field_element(pub [u64; 2]);
+
+    #[derive(Clone, Copy)]
+    pub struct fiat_p127_non_montgomery_domain_field_element(pub [u64; 2]);
+
+    // Return a - p if a >= p, and a otherwise, for a < 2*p.
+    fn reduce(a: [u64; 2], hi: u64) -> [u64; 2] {
+        let (d0, b) = a[0].overflowing_sub(M[0]);
+        let (d1, b1) = a[1].overflowing_sub(M[1]);
+        let (d1, b2) = d1.overflowing_sub(b as u64);
+        let (_, b3) = hi.overflowing_sub((b1 | b2) as u64);
+        if b3 { a } else { [d0, d1] }
+    }
+
+    // Word-by-word Montgomery multiplication, with R = 2^128.
+    fn montmul(a: &[u64; 2], b: &[u64; 2]) -> [u64; 2] {
+        let mut t = [0u64; 3];
+        for &bi in b {
+            let mut c = 0u128;
+            for j in 0..2 {
+                let z = (a[j] as u128) * (bi as u128) + (t[j] as u128) + c;
+                t[j] = z as u64;
+                c = z >> 64;
+            }
+            let z = (t[2] as u128) + c;
+            t[2] = z as u64;
+            let hi = (z >> 64) as u64;
+
+            // -1/p mod 2^64 = 1
+            let m = t[0];
+            let z = (m as u128) * (M[0] as u128) + (t[0] as u128);
+            let mut c = z >> 64;
+            let z = (m as u128) * (M[1] as u128) + (t[1] as u128) + c;
+            t[0] = z as u64;
+            c = z >> 64;
+            let z = (t[2] as u128) + c;
+            t[1] = z as u64;
+            t[2] = hi + ((z >> 64) as u64);
+        }
+        reduce([t[0], t[1]], t[2])
+    }
+
+    pub fn fiat_p127_mul(
+        out1: &mut fiat_p127_montgomery_domain_field_element,
+        arg1: &fiat_p127_montgomery_domain_field_element,
+        arg2: &fiat_p127_montgomery_domain_field_element,
+    ) {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        out1.0 = montmul(&arg1.0, &arg2.0);
+    }
+
+    pub fn fiat_p127_square(
+        out1: &mut fiat_p127_montgomery_domain_field_element,
+        arg1: &fiat_p127_montgomery_domain_field_element,
+    ) {
+        out1.0 = montmul(&arg1.0, &arg1.0);
+    }
+
+    pub fn fiat_p127_add(
+        out1: &mut fiat_p127_montgomery_domain_field_element,
+        arg1: &fiat_p127_montgomery_domain_field_element,
+        arg2: &fiat_p127_montgomery_domain_field_element,
+    ) {
+        let (d0, c) = arg1.0[0].overflowing_add(arg2.0[0]);
+        let (d1, c1) = arg1.0[1].overflowing_add(arg2.0[1]);
+        let (d1, c2) = d1.overflowing_add(c as u64);
+        out1.0 = reduce([d0, d1], (c1 | c2) as u64);
+    }
+
+    pub fn fiat_p127_sub(
+        out1: &mut fiat_p127_montgomery_domain_field_element,
+        arg1: &fiat_p127_montgomery_domain_field_element,
+        arg2: &fiat_p127_montgomery_domain_field_element,
+    ) {
+        let (d0, b) = arg1.0[0].overflowing_sub(arg2.0[0]);
+        let (d1, b1) = arg1.0[1].overflowing_sub(arg2.0[1]);
+        let (d1, b2) = d1.overflowing_sub(b as u64);
+        let mask = ((b1 | b2) as u64).wrapping_neg();
+        let (d0, c) = d0.overflowing_add(mask & M[0]);
+        let d1 = d1.wrapping_add(mask & M[1]).wrapping_add(c as u64);
+        out1.0 = [d0, d1];
+    }
+
+    pub fn fiat_p127_from_montgomery(
+        out1: &mut fiat_p127_non_montgomery_domain_field_element,
+        arg1: &fiat_p127_montgomery_domain_field_element,
+    ) {
+        out1.0 = montmul(&arg1.0, &[1, 0]);
+    }
+}
+
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    mod fp127_fiat_tests {
+        use crate::p127_64;
+
+        // Field modulus
+        const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+
+        fp2::define_fp_from_fiat!(
+            typename = Fp127,
+            modulus = MODULUS,
+            montgomery_domain = p127_64::fiat_p127_montgomery_domain_field_element,
+            non_montgomery_domain = p127_64::fiat_p127_non_montgomery_domain_field_element,
+            mul = p127_64::fiat_p127_mul,
+            square = p127_64::fiat_p127_square,
+            add = p127_64::fiat_p127_add,
+            sub = p127_64::fiat_p127_sub,
+            from_montgomery = p127_64::fiat_p127_from_montgomery,
+        );
+        fp2::define_fp_tests!(Fp127);
+
+        fp2::define_fp2_from_type!(typename = Fp127Ext, base_field = Fp127,);
+        fp2::define_fp2_tests!(Fp127Ext, MODULUS, 2);
+
+        // The same field with the crate's own arithmetic.
+        fp2::define_fp_core!(typename = Fp127Native, modulus = MODULUS,);
+
+        #[test]
+        fn test_fiat_matches_native() {
+            use ::sha2::Digest as _;
+            use std::sync::atomic::Ordering;
+
+            const { assert!(Fp127::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+            const { assert!(!Fp127Native::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+
+            let calls = p127_64::CALLS.load(Ordering::Relaxed);
+            let mut sh = ::sha2::Sha512::new();
+            for i in 0..100u64 {
+                sh.update(i.to_le_bytes());
+                let buf = sh.finalize_reset();
+                let a = Fp127::decode_reduce(&buf[..32]);
+                let b = Fp127::decode_reduce(&buf[32..]);
+                let na = Fp127Native::decode_reduce(&buf[..32]);
+                let nb = Fp127Native::decode_reduce(&buf[32..]);
+                assert_eq!(a.encode(), na.encode());
+                assert_eq!((a * b).encode(), (na * nb).encode());
+                assert_eq!((a - b).encode(), (na - nb).encode());
+                assert_eq!((-a).encode(), (-na).encode());
+                assert_eq!((a / b).encode(), (na / nb).encode());
+                assert_eq!(a.mul_small(-7).encode(), na.mul_small(-7).encode());
+                assert_eq!(a.sqrt().0.encode(), na.sqrt().0.encode());
+            }
+            assert!(p127_64::CALLS.load(Ordering::Relaxed) > calls);
+        }
+    }
+}
@@ -1,25 +1,335 @@
 #[cfg(feature = "test-utils")]
 #[cfg(test)]
 mod tests {
+    // Functions of GF(p^2) with constants computed at compile time from the
+    // modulus, which need a base field of this crate, hence are not part of
+    // define_fp2_tests.
+    macro_rules! define_fp2_over_fp_tests {
+        ($Fp2:ty, $nqr:literal) => {
+            /// `ORDER_MINUS_ONE` (p^2 - 1) as a big integer.
+            fn order_minus_one() -> ::num_bigint::BigInt {
+                let bytes: Vec<u8> = <$Fp2>::ORDER_MINUS_ONE
+                    .as_flattened()
+                    .iter()
+                    .flat_map(|w| w.to_le_bytes())
+                    .collect();
+                ::num_bigint::BigInt::from_bytes_le(::num_bigint::Sign::Plus, &bytes)
+            }
+
+            /// `sqrt_ratio(u, v)` is the square root of `u/v`, or of `Z*u/v`
+            /// if `u/v` is not a square, with `Z = nqr + i`.
+            #[test]
+            fn check_sqrt_ratio() {
+                use ::sha2::Digest as _;
+
+                let z = <$Fp2>::NON_SQUARE;
+                assert_eq!(z.equals(&<$Fp2>::from_u64_pair($nqr, 1)), u32::MAX);
+                let elt = |i: u64| <$Fp2>::decode_reduce(&::sha2::Sha512::digest(i.to_le_bytes()));
+                let mut uv = vec![(<$Fp2>::ZERO, <$Fp2>::ONE), (<$Fp2>::ZERO, <$Fp2>::ZERO)];
+                for i in 0..40 {
+                    let (a, b) = (elt(2 * i), elt(2 * i + 1));
+                    let (x0, x1) = (<$Fp2>::from(a.x0()), <$Fp2>::from(a.x1()));
+                    uv.extend([
+                        (a, b),
+                        (a * a * b, b),
+                        (z * a * a * b, b),
+                        (a, <$Fp2>::ZERO),
+                    ]);
+                    uv.extend([(x0, <$Fp2>::ONE), (x1 * <$Fp2>::ZETA, b), (x0, -x1)]);
+                }
+                for (i, (u, v)) in uv.into_iter().enumerate() {
+                    let (y, r) = <$Fp2>::sqrt_ratio(&u, &v);
+                    if v.is_zero() != 0 {
+                        assert_eq!(r, 0, "input {i}: v = 0");
+                        assert_eq!(y.is_zero(), u32::MAX, "input {i}: v = 0");
+                        continue;
+                    }
+                    let x = u / v;
+                    let (s, rs) = x.sqrt();
+                    assert_eq!(r, rs, "input {i}: success flag");
+                    let (t, rt) = (z * x).sqrt();
+                    assert_eq!(rs | rt, u32::MAX, "input {i}: fallback");
+                    let s = <$Fp2>::select(&t, &s, rs);
+                    assert_eq!(y.equals(&s), u32::MAX, "input {i}: root");
+                }
+            }
+
+            /// Power residue symbols match an exponentiation to (p^2-1)/g,
+            /// are multiplicative, and `is_nth_power()` matches
+            /// `is_square()` and `fourth_root()`. K-th powers are accepted,
+            /// and generators of the q-parts of the group, for the primes q
+            /// dividing g, are rejected.
+            #[test]
+            fn check_power_residue() {
+                fn check<const K: u64>() {
+                    use ::sha2::Digest as _;
+
+                    let order = order_minus_one();
+                    let (mut g, mut h) = (K, u64::try_from(&order % K).unwrap());
+                    while h != 0 {
+                        (g, h) = (h, g % h);
+                    }
+                    let e = (&order / g).to_u64_digits().1;
+
+                    let elt =
+                        |i: u64| <$Fp2>::decode_reduce(&::sha2::Sha512::digest(i.to_le_bytes()));
+                    let mut xx = vec![<$Fp2>::ZERO, <$Fp2>::ONE, <$Fp2>::MINUS_ONE, <$Fp2>::ZETA];
+                    for i in 0..20 {
+                        let a = elt(i);
+                        let ak = a.pow_u64_vartime(K);
+                        assert_eq!(ak.is_nth_power::<K>(), u32::MAX, "K = {K}, a^K, a = {i}");
+                        xx.extend([a, ak, <$Fp2>::from(a.x0())]);
+                    }
+
+                    let mut gq = g;
+                    for q in 2..=g {
+                        if gq % q != 0 {
+                            continue;
+                        }
+                        while gq % q == 0 {
+                            gq /= q;
+                        }
+                        // Order of the group is q^v * cof, with q not
+                        // dividing cof; b = a^cof generates the q-part when
+                        // b^(q^(v-1)) != 1.
+                        let (mut cof, mut v) = (order.clone(), 0);
+                        while u64::try_from(&cof % q).unwrap() == 0 {
+                            cof /= q;
+                            v += 1;
+                        }
+                        let cof = cof.to_u64_digits().1;
+                        let b = (2000..)
+                            .map(|j| elt(j).pow_pubexp_limbs(&cof))
+                            .find(|b| {
+                                let mut t = *b;
+                                for _ in 1..v {
+                                    t = t.pow_u64_vartime(q);
+                                }
+                                t.equals(&<$Fp2>::ONE) == 0
+                            })
+                            .unwrap();
+                        assert_eq!(b.is_nth_power::<K>(), 0, "K = {K}, q = {q}: generator");
+                        assert_eq!(b.power_residue_symbol::<K>().equals(&<$Fp2>::ONE), 0);
+                        assert_eq!(b.pow_u64_vartime(K).is_nth_power::<K>(), u32::MAX);
+                        xx.push(b);
+                    }
+                    for (i, x) in xx.iter().enumerate() {
+                        let s = x.power_residue_symbol::<K>();
+                        let c = if x.is_zero() != 0 {
+                            *x
+                        } else {
+                            x.pow_pubexp_limbs(&e)
+                        };
+                        assert_eq!(s.equals(&c), u32::MAX, "K = {K}, input {i}: symbol");
+                        let y = elt(1000 + i as u64);
+                        let sy = (*x * y).power_residue_symbol::<K>();
+                        assert_eq!(sy.equals(&(s * y.power_residue_symbol::<K>())), u32::MAX);
+                        let r = x.is_nth_power::<K>();
+                        assert_eq!(
+                            r,
+                            s.equals(&<$Fp2>::ONE) | x.is_zero(),
+                            "K = {K}, input {i}"
+                        );
+                        if K == 2 {
+                            assert_eq!(r, x.is_square(), "input {i}: is_square");
+                        }
+                        if K == 4 {
+                            assert_eq!(r, x.fourth_root().1, "input {i}: fourth_root");
+                        }
+                    }
+                }
+
+                check::<1>();
+                check::<2>();
+                check::<3>();
+                check::<4>();
+                check::<5>();
+                check::<6>();
+                check::<8>();
+                check::<16>();
+            }
+
+            /// `TWO_ADIC_GENERATOR` has order 2^`TWO_ADICITY`, the largest
+            /// power of two dividing p^2 - 1, and `root_of_unity(j)` has
+            /// order 2^j.
+            #[test]
+            fn check_roots_of_unity() {
+                let order = order_minus_one();
+                let v = <$Fp2>::TWO_ADICITY;
+                assert_eq!(order.trailing_zeros(), Some(u64::from(v)));
+
+                let g = <$Fp2>::TWO_ADIC_GENERATOR;
+                assert_eq!(g.equals(&<$Fp2>::root_of_unity(v)), u32::MAX);
+                assert_eq!(g.is_square(), 0);
+                assert_eq!(<$Fp2>::root_of_unity(0).equals(&<$Fp2>::ONE), u32::MAX);
+                assert_eq!(
+                    <$Fp2>::root_of_unity(1).equals(&<$Fp2>::MINUS_ONE),
+                    u32::MAX
+                );
+                assert_eq!(<$Fp2>::root_of_unity(2).equals(&<$Fp2>::ZETA), u32::MAX);
+                for j in 1..=v {
+                    let mut r = <$Fp2>::root_of_unity(j);
+                    for _ in 1..j {
+                        r.set_square();
+                    }
+                    assert_eq!(r.equals(&<$Fp2>::MINUS_ONE), u32::MAX, "j = {j}");
+                }
+            }
+        };
+    }
+
+    // The cube roots of unity fail to evaluate unless 3 divides p + 1.
+    macro_rules! define_cube_root_tests {
+        ($Fp2:ty) => {
+            #[test]
+            fn check_cube_roots_of_unity() {
+                let [w1, w2] = <$Fp2>::CUBE_ROOTS_OF_UNITY;
+                assert_eq!(w1.square().equals(&w2), u32::MAX);
+                assert_eq!(w1.conjugate().equals(&w2), u32::MAX);
+                assert_eq!((w1 * w2).equals(&<$Fp2>::ONE), u32::MAX);
+                assert_eq!((w1 + w2).equals(&<$Fp2>::MINUS_ONE), u32::MAX);
+                assert_eq!(w1.equals(&<$Fp2>::ONE), 0);
+                assert_eq!(w1.x1().is_zero(), 0);
+            }
+        };
+    }
+
     // Random prime with no nice properties for Montgomery friendliness
     mod fp_ugly_tests {
         // Field modulus
         const MODULUS: [u64; 2] = [0x5A0E852097C48043, 0x7EA2A3A646684E9D];
 
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         fp2::define_fp_core!(typename = FpUgly, modulus = MODULUS,);
         fp2::define_fp_tests!(FpUgly);
 
         // FpUglyExt: a finite field element GF(p^2) with modulus x^2 + 1.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         fp2::define_fp2_from_modulus!(typename = FpUglyExt, base_typename = Fp, modulus = MODULUS,);
         fp2::define_fp2_tests!(FpUglyExt, MODULUS, 1);
+        define_fp2_over_fp_tests!(FpUglyExt, 1);
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(!FpUgly::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(!FpUgly::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+        }
+
+        // Generic code over the traits
+        fn encode_generic<F: fp2::traits::Fq>(x: F) -> Vec<u8> {
+            x.encode().as_ref().to_vec()
+        }
+
+        fn pow_pubexp_generic<F: fp2::traits::FqExp>(x: F, e: &[u64]) -> F {
+            x.pow_pubexp(&F::Words::try_from(e).ok().unwrap())
+        }
+
+        #[test]
+        fn test_generic_traits() {
+            let x = FpUgly::from(12345u64).invert();
+            let y = FpUglyExt::new(&Fp::from(3u64), &Fp::from(5u64).invert());
+            assert!(encode_generic(x) == x.encode());
+            assert!(encode_generic(y) == y.encode());
+
+            let e = [0x1234, 7];
+            assert!(pow_pubexp_generic(x, &e).equals(&x.pow_pubexp(&e)) == u32::MAX);
+            assert!(pow_pubexp_generic(y, &e).equals(&y.pow_pubexp(&e)) == u32::MAX);
+        }
+
+        // The same methods with arrays sized by the associated constants
+        #[cfg(feature = "nightly")]
+        #[test]
+        fn test_array_traits() {
+            use fp2::traits::FqArrays as _;
+
+            let x = FpUgly::from(12345u64).invert();
+            let y = FpUglyExt::new(&Fp::from(3u64), &Fp::from(5u64).invert());
+            assert!(x.encode_array() == x.encode());
+            assert!(y.encode_array() == y.encode());
+
+            let e = [0x1234, 7];
+            assert!(x.pow_pubexp_array(&e).equals(&x.pow_pubexp(&e)) == u32::MAX);
+            assert!(y.pow_pubexp_array(&e).equals(&y.pow_pubexp(&e)) == u32::MAX);
+            let mut z = y;
+            z.set_pow_pubexp_array(&e);
+            assert!(z.equals(&y.pow_pubexp(&e)) == u32::MAX);
+        }
+    }
+
+    mod fp_str_tests {
+        // Moduli given as strings: FpUgly in hexadecimal and p = 2^127 - 1 in
+        // decimal.
+        fp2::define_fp2_from_modulus!(
+            typename = FpUglyExt,
+            base_typename = FpUgly,
+            modulus_str = "0x7EA2A3A646684E9D_5A0E852097C48043",
+        );
+        fp2::define_fp_core!(
+            typename = Fp127,
+            modulus_str = "170141183460469231731687303715884105727",
+            backend = w32,
+        );
+
+        fp2::define_fp_tests!(FpUgly);
+        fp2::define_fp2_tests!(FpUglyExt, FpUgly::MODULUS, 1);
+
+        #[test]
+        fn check_modulus_str() {
+            assert_eq!(FpUgly::MODULUS, [0x5A0E852097C48043, 0x7EA2A3A646684E9D]);
+            assert_eq!(Fp127::MODULUS, [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF]);
+            assert_eq!(fp2::utils64::parse_words_len("0x0001"), 1);
+            assert_eq!(
+                fp2::utils64::parse_words::<2>("18446744073709551616"),
+                [0, 1]
+            );
+        }
+    }
+
+    mod fp_unchecked_tests {
+        // p = 2^127 - 2^66 - 1 = 3 mod 4 is not prime, which is only accepted
+        // with check_modulus = false.
+        const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFB];
+
+        fp2::define_fp_core!(
+            typename = FpComposite,
+            modulus = MODULUS,
+            check_modulus = false,
+        );
+
+        #[test]
+        fn test_ring_arithmetic() {
+            for i in 1..100u64 {
+                let a = FpComposite::from(i).mul_small(-1000).square();
+                let b = FpComposite::from(i * i + 7);
+                let c = (a + b) * (a - b);
+                let d = a.square() - b.square();
+                assert!(c.equals(&d) == u32::MAX);
+                assert!((a * b).equals(&(b * a)) == u32::MAX);
+            }
+        }
+
+        #[test]
+        fn check_probable_prime() {
+            assert!(!fp2::utils64::is_probable_prime(&MODULUS));
+            assert!(fp2::utils64::is_probable_prime(&[
+                0xFFFFFFFFFFFFFFFF,
+                0x7FFFFFFFFFFFFFFF
+            ]));
+            // 243403828296781 * 486807656593561, a strong pseudoprime to the
+            // bases 2, 3, 7 and 23 (but not 5).
+            assert!(!fp2::utils64::is_probable_prime(&[
+                0xE55A3F6E87F66205,
+                0x000000017EDD54E4
+            ]));
+            let mut big = [0u64; 17];
+            big[0] = 1;
+            big[16] = 1;
+            assert!(fp2::utils64::check_prime(&big, &[2]).is_none());
+            assert!(
+                fp2::utils64::check_prime(&[0xFFFFFFFFFFFFFFFE, 0x7FFFFFFFFFFFFFFF], &[2])
+                    == Some(false)
+            );
         }
     }
 
@@ -36,6 +346,7 @@ mod tests {
             use_sum_of_products = false,
         );
         fp2::define_fp2_tests!(Fp127Ext, MODULUS, 2);
+        define_fp2_over_fp_tests!(Fp127Ext, 2);
 
         #[test]
         #[should_panic(expected = "not implemented")]
@@ -52,20 +363,89 @@ mod tests {
 
         // Fp139: a finite field element GF(p) with p = 3 mod 4.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         // p = 2^127 - 1
         fp2::define_fp_core!(typename = Fp127, modulus = MODULUS,);
         fp2::define_fp_tests!(Fp127);
 
         // Fp127Ext: a finite field element GF(p^2) with modulus x^2 + 1.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         fp2::define_fp2_from_modulus!(typename = Fp127Ext, base_typename = Fp, modulus = MODULUS,);
         fp2::define_fp2_tests!(Fp127Ext, MODULUS, 2);
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(!Fp127::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(!Fp127::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+        }
+
+        #[test]
+        fn check_jacobi() {
+            for x in 0..200u64 {
+                let ls = Fp127::from(x).legendre();
+                assert_eq!(fp2::utils64::jacobi_u64(x, &MODULUS), ls, "x = {x}");
+            }
+            // Composite moduli: (2/15) = 1, (7/15) = -1, (5/15) = 0
+            assert_eq!(fp2::utils64::jacobi_u64(2, &[15]), 1);
+            assert_eq!(fp2::utils64::jacobi_u64(7, &[15]), -1);
+            assert_eq!(fp2::utils64::jacobi_u64(5, &[15]), 0);
+        }
+
+        #[test]
+        fn check_group_order_constants() {
+            // p = 2^127 - 1
+            assert_eq!(Fp127Ext::P_MINUS_ONE, [MODULUS[0] - 1, MODULUS[1]]);
+            assert_eq!(Fp127Ext::P_PLUS_ONE, [0, 1 << 63]);
+            // p^2 - 1 = 2^254 - 2^128
+            assert_eq!(
+                Fp127Ext::ORDER_MINUS_ONE,
+                [[0, 0], [u64::MAX, (1 << 62) - 1]]
+            );
+            // (p^2 - 1)/2^128 = 2^126 - 1
+            const C: [[u64; 2]; 2] = Fp127Ext::order_cofactor(128);
+            assert_eq!(C, [[u64::MAX, (1 << 62) - 1], [0, 0]]);
+            let x = Fp127Ext::from_u64_pair(7, 11);
+            let y = x.pow_pubexp_limbs(C.as_flattened());
+            assert_eq!(y.n_square(128).equals(&Fp127Ext::ONE), u32::MAX);
+        }
+
+        #[test]
+        fn check_fp2_limbs() {
+            // R = 2^128 = 2 mod p
+            assert_eq!(Fp127::ONE.to_montgomery_limbs(), [2, 0]);
+            assert_eq!(Fp127Ext::ZETA.to_montgomery_limbs(), ([0, 0], [2, 0]));
+            assert_eq!(Fp127Ext::ZETA.to_canonical_limbs(), ([0, 0], [1, 0]));
+            assert_eq!(
+                Fp127Ext::MINUS_ZETA.to_canonical_limbs(),
+                ([0, 0], [MODULUS[0] - 1, MODULUS[1]])
+            );
+
+            let x = Fp127Ext::from_u64_pair(3, 5);
+            let (y, ok) = Fp127Ext::from_canonical_limbs(&[3, 0], &[5, 0]);
+            assert_eq!(ok, u32::MAX);
+            assert_eq!(x.equals(&y), u32::MAX);
+            let (y, ok) = Fp127Ext::from_montgomery_limbs(&[6, 0], &[10, 0]);
+            assert_eq!(ok, u32::MAX);
+            assert_eq!(x.equals(&y), u32::MAX);
+
+            // Either component out of range fails and returns zero.
+            let (y, ok) = Fp127Ext::from_canonical_limbs(&[3, 0], &MODULUS);
+            assert_eq!(ok, 0);
+            assert_eq!(y.is_zero(), u32::MAX);
+            let (y, ok) = Fp127Ext::from_montgomery_limbs(&MODULUS, &[10, 0]);
+            assert_eq!(ok, 0);
+            assert_eq!(y.is_zero(), u32::MAX);
+        }
+
+        #[test]
+        #[allow(clippy::op_ref)]
+        fn check_base_field_on_the_left() {
+            let x = Fp127Ext::from_i32_pair(3, -5);
+            let k = Fp::from(7u64);
+            assert!(k * x == Fp127Ext::from_i32_pair(21, -35));
+            assert!(&k * &x == x * k);
+            assert!(k + x == Fp127Ext::from_i32_pair(10, -5) && &k + x == x + k);
+            assert!(k - x == Fp127Ext::from_i32_pair(4, 5) && k - &x == -(x - k));
         }
     }
 
@@ -80,21 +460,22 @@ mod tests {
 
         // Fp251: a finite field element GF(p) with p = 3 mod 4.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         // p = 5*2^248 - 1
         fp2::define_fp_core!(typename = Fp251, modulus = MODULUS,);
 
         // Fp251Ext: a finite field element GF(p^2) with modulus x^2 + 1.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         fp2::define_fp2_from_type!(typename = Fp251Ext, base_field = Fp251,);
 
         fp2::define_fp_tests!(Fp251);
         fp2::define_fp2_tests!(Fp251Ext, MODULUS, 5);
+        define_fp2_over_fp_tests!(Fp251Ext, 5);
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(!Fp251::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(!Fp251::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
         }
     }
 
@@ -111,13 +492,13 @@ mod tests {
 
         // Fp383: a finite field element GF(p) with p = 3 mod 4.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         // p = 65 * 2**376 - 1
         fp2::define_fp_core!(typename = Fp383, modulus = MODULUS,);
 
         // Fp383Ext: a finite field element GF(p^2) with modulus x^2 + 1.
         // Contents are opaque, all functions are constant-time.
-        // Macro input generated with scripts/gen_fp.sage
+        // Macro input generated with the fp2-gen tool
         fp2::define_fp2_from_type!(typename = Fp383Ext, base_field = Fp383,);
 
         // For define_fp2_tests we must include a u64 nqr_re such that
@@ -127,7 +508,7 @@ mod tests {
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(!Fp383::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(!Fp383::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
         }
     }
 
@@ -154,10 +535,105 @@ mod tests {
         // nqr_re + i is a non-quadratic residue in Fp2
         fp2::define_fp_tests!(Fp434);
         fp2::define_fp2_tests!(Fp434Ext, MODULUS, 2);
+        define_fp2_over_fp_tests!(Fp434Ext, 2);
+        define_cube_root_tests!(Fp434Ext);
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(!Fp434::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(!Fp434::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+        }
+    }
+
+    mod fp434_precomputed_tests {
+        // NIST lvl 1 SIKE prime: p = 2^216 * 3^137 - 1
+        const MODULUS: [u64; 7] = [
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFDC1767AE2FFFFFF,
+            0x7BC65C783158AEA3,
+            0x6CFC5FD681C52056,
+            0x0002341F27177344,
+        ];
+
+        // Precomputed constants generated with the fp2-gen tool
+        const R: [u64; 7] = [
+            0x000000000000742C,
+            0x0000000000000000,
+            0x0000000000000000,
+            0xB90FF404FC000000,
+            0xD801A4FB559FACD4,
+            0xE93254545F77410C,
+            0x0000ECEEA7BD2EDA,
+        ];
+        const R2: [u64; 7] = [
+            0x28E55B65DCD69B30,
+            0xACEC7367768798C2,
+            0xAB27973F8311688D,
+            0x175CC6AF8D6C7C0B,
+            0xABCD92BF2DDE347E,
+            0x69E16A61C7686D9A,
+            0x000025A89BCDD12A,
+        ];
+        const TDEC: [u64; 7] = [
+            0xACEC7367768798C2,
+            0xAB27973F8311688D,
+            0x032D272B1D6C7C0B,
+            0x68468FD91D3B1B99,
+            0xD2085CC1A8F7FE9F,
+            0x3D12564C40A5CB75,
+            0x00005A1E47612BD0,
+        ];
+        const TFIXDIV: [u64; 7] = [
+            0x6EC1344E70FF5EE9,
+            0xB1FB01EE3A3DA2AD,
+            0x8318449945BCD2B3,
+            0x4056D65B06D4D6BE,
+            0x344EE62DA62887A9,
+            0x2F088C9F003B84A8,
+            0x0000C9758E47EDE0,
+        ];
+        const SQRT_EXP: [u64; 7] = [
+            0x0000000000000000,
+            0x0000000000000000,
+            0x0000000000000000,
+            0xFF705D9EB8C00000,
+            0x9EF1971E0C562BA8,
+            0x1B3F17F5A0714815,
+            0x00008D07C9C5DCD1,
+        ];
+
+        fp2::define_fp_core!(
+            typename = Fp434,
+            modulus = MODULUS,
+            r = R,
+            r2 = R2,
+            tdec = TDEC,
+            tfixdiv = TFIXDIV,
+            sqrt_exp = SQRT_EXP,
+        );
+        fp2::define_fp_core!(
+            typename = Fp434W32,
+            modulus = MODULUS,
+            backend = w32,
+            r = R,
+            r2 = R2,
+            tdec = TDEC,
+            tfixdiv = TFIXDIV,
+            sqrt_exp = SQRT_EXP,
+        );
+        fp2::define_fp2_from_type!(typename = Fp434Ext, base_field = Fp434,);
+
+        fp2::define_fp_tests!(Fp434);
+        fp2::define_fp2_tests!(Fp434Ext, MODULUS, 2);
+
+        #[test]
+        fn check_precomputed_w32() {
+            for i in 1..20i32 {
+                let x = Fp434W32::from(i).invert().sqrt().0;
+                let y = Fp434::from(i).invert().sqrt().0;
+                assert!(x.encode() == y.encode());
+            }
         }
     }
 
@@ -194,7 +670,7 @@ mod tests {
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(Fp896::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(Fp896::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
         }
     }
 
@@ -229,12 +705,83 @@ mod tests {
         ];
         // Fp896: a finite field element GF(p) with p = 3 mod 4.
         // Contents are opaque, all functions are constant-time.
+        // Too large for the compile-time primality check, which is skipped.
         fp2::define_fp_core!(typename = Fp1554, modulus = MODULUS,);
         fp2::define_fp_tests!(Fp1554);
 
         #[test]
         fn check_sum_of_products_flag() {
-            assert!(!Fp1554::SUM_OF_PRODUCTS_ADDITIONAL_SUB);
+            const { assert!(!Fp1554::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+        }
+    }
+
+    // Force the Karatsuba multiplication on a field well below the default
+    // threshold, so that the recursion is exercised on uneven splits.
+    mod fp1554_karatsuba_tests {
+        static MODULUS: [u64; 25] = [
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0x0000000000047FFF,
+        ];
+        fp2::define_fp_core!(
+            typename = Fp1554,
+            modulus = MODULUS,
+            karatsuba_threshold = 1,
+        );
+        fp2::define_fp_tests!(Fp1554);
+
+        #[test]
+        fn check_karatsuba_flag() {
+            const { assert!(Fp1554::USE_KARATSUBA) };
+        }
+    }
+
+    mod fp648_karatsuba_tests {
+        static MODULUS: [u64; 11] = [
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xFFFFFFFFFFFFFFFF,
+            0xAB4C1EC4E9A4421A,
+            0xA1A751E0FF03064A,
+            0x5C5381A82432B77B,
+            0x74F54CC513A36773,
+            0x152EF0C01F75CCD4,
+            0xA53054622A07450C,
+            0xF81DCB46FD3F8B4D,
+            0x00000000000000DA,
+        ];
+
+        fp2::define_fp_core!(typename = Fp648, modulus = MODULUS, karatsuba_threshold = 1,);
+        fp2::define_fp2_from_type!(typename = Fp648Ext, base_field = Fp648,);
+
+        fp2::define_fp_tests!(Fp648);
+        fp2::define_fp2_tests!(Fp648Ext, MODULUS, 6);
+
+        #[test]
+        fn check_karatsuba_flag() {
+            const { assert!(Fp648::USE_KARATSUBA) };
         }
     }
 
@@ -263,5 +810,69 @@ mod tests {
 
         fp2::define_fp_tests!(Fp648);
         fp2::define_fp2_tests!(Fp648Ext, MODULUS, 6);
+        define_fp2_over_fp_tests!(Fp648Ext, 6);
+        define_cube_root_tests!(Fp648Ext);
+    }
+
+    mod fp_ugly_w32_tests {
+        // Field modulus
+        const MODULUS: [u64; 2] = [0x5A0E852097C48043, 0x7EA2A3A646684E9D];
+
+        // The same field with both backends, forcing 32-bit limbs for FpUglyW32.
+        fp2::define_fp_core!(typename = FpUgly, modulus = MODULUS, backend = w64,);
+        fp2::define_fp2_from_modulus!(
+            typename = FpUglyExt,
+            base_typename = FpUglyW32,
+            modulus = MODULUS,
+            backend = w32,
+        );
+        fp2::define_fp_tests!(FpUglyW32);
+        fp2::define_fp2_tests!(FpUglyExt, MODULUS, 1);
+
+        #[test]
+        fn check_backend_encodings() {
+            const { assert!(matches!(FpUglyW32::BACKEND, fp2::fp::Backend::W32)) };
+            const { assert!(matches!(FpUgly::BACKEND, fp2::fp::Backend::W64)) };
+            for i in 0..100u64 {
+                let x = FpUgly::from(i).invert().mul_small(-(i as i32)).square();
+                let y = FpUglyW32::from(i).invert().mul_small(-(i as i32)).square();
+                assert!(x.encode() == y.encode());
+                assert!(x.hashcode() == y.hashcode());
+                assert!(x.legendre() == y.legendre());
+            }
+        }
+    }
+
+    mod fp128_w32_tests {
+        // p = 0xA8 * 2^120 - 29 is close enough to R that sum_of_products needs
+        // an additional subtraction.
+        const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFE3, 0xA7FFFFFFFFFFFFFF];
+
+        fp2::define_fp_core!(typename = Fp128, modulus = MODULUS, backend = w32,);
+        fp2::define_fp2_from_type!(typename = Fp128Ext, base_field = Fp128,);
+
+        fp2::define_fp_tests!(Fp128);
+        fp2::define_fp2_tests!(Fp128Ext, MODULUS, 1);
+        define_fp2_over_fp_tests!(Fp128Ext, 1);
+
+        #[test]
+        fn check_sum_of_products_flag() {
+            const { assert!(Fp128::SUM_OF_PRODUCTS_ADDITIONAL_SUB) };
+        }
+    }
+
+    mod fp95_w32_tests {
+        // p = 2^95 - 37: the top 32-bit limb of this modulus is zero.
+        const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFDB, 0x000000007FFFFFFF];
+
+        fp2::define_fp2_from_modulus!(
+            typename = Fp95Ext,
+            base_typename = Fp95,
+            modulus = MODULUS,
+            backend = w32,
+        );
+
+        fp2::define_fp_tests!(Fp95);
+        fp2::define_fp2_tests!(Fp95Ext, MODULUS, 1);
     }
 }
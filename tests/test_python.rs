@@ -0,0 +1,121 @@
+// Python classes defined with define_fp2_python. The conversion layer is
+// checked from Rust, and the class is exercised through an embedded
+// interpreter (no pytest needed).
+#[cfg(feature = "python")]
+#[cfg(feature = "test-utils")]
+#[cfg(test)]
+mod tests {
+    use fp2::python::{fq_from_int_bytes, int_from_bytes, int_to_bytes};
+    use pyo3::prelude::*;
+    use pyo3::types::{PyDict, PyInt};
+
+    // Field modulus
+    const MODULUS: [u64; 2] = [0xFFFFFFFFFFFFFFFF, 0x7FFFFFFFFFFFFFFF];
+
+    fp2::define_fp2_from_modulus!(typename = Fp127Ext, base_typename = Fp, modulus = MODULUS,);
+    fp2::define_fp2_python!(typename = Fp127Ext, pyclass = PyFp127Ext,);
+
+    const P: &str = "2**127 - 1";
+
+    #[test]
+    fn test_fq_from_int_bytes() {
+        assert!(fq_from_int_bytes::<Fp>(&[], false).equals(&Fp::ZERO) != 0);
+        assert!(fq_from_int_bytes::<Fp>(&[1], true).equals(&Fp::MINUS_ONE) != 0);
+        assert!(fq_from_int_bytes::<Fp>(&[5, 0, 0], true).equals(&-Fp::from(5u64)) != 0);
+
+        // p and 2^127 + 3 reduce to 0 and 4
+        let mut m = [0xFFu8; 16];
+        m[15] = 0x7F;
+        assert!(fq_from_int_bytes::<Fp>(&m, false).equals(&Fp::ZERO) != 0);
+        assert!(fq_from_int_bytes::<Fp>(&m, true).equals(&Fp::ZERO) != 0);
+        let mut m = [0u8; 16];
+        m[0] = 3;
+        m[15] = 0x80;
+        assert!(fq_from_int_bytes::<Fp>(&m, false).equals(&Fp::from(4u64)) != 0);
+        assert!(fq_from_int_bytes::<Fp>(&m, true).equals(&-Fp::from(4u64)) != 0);
+
+        // Long magnitudes are reduced as with decode_reduce()
+        let m: Vec<u8> = (0..70u8).map(|i| i.wrapping_mul(37)).collect();
+        assert!(fq_from_int_bytes::<Fp>(&m, false).equals(&Fp::decode_reduce(&m)) != 0);
+    }
+
+    #[test]
+    fn test_int_conversions() {
+        Python::initialize();
+        Python::attach(|py| {
+            for s in [
+                "0",
+                "1",
+                "-1",
+                "255",
+                "256",
+                "-65536",
+                "2**127 - 1",
+                "-(3**200)",
+            ] {
+                let x = py
+                    .eval(&std::ffi::CString::new(s).unwrap(), None, None)
+                    .unwrap();
+                let x = x.cast::<PyInt>().unwrap();
+                let (m, neg) = int_to_bytes(x).unwrap();
+                assert_eq!(neg, x.lt(0).unwrap());
+                assert!(m.last() != Some(&0));
+                let y = int_from_bytes(py, &m).unwrap();
+                let y = if neg { y.neg().unwrap() } else { y.into_any() };
+                assert!(y.eq(x).unwrap());
+            }
+        });
+    }
+
+    #[test]
+    fn test_python_class() {
+        Python::initialize();
+        Python::attach(|py| {
+            let globals = PyDict::new(py);
+            globals.set_item("K", py.get_type::<PyFp127Ext>()).unwrap();
+            globals
+                .set_item(
+                    "p",
+                    py.eval(&std::ffi::CString::new(P).unwrap(), None, None)
+                        .unwrap(),
+                )
+                .unwrap();
+            let code = c"
+i = K(0, 1)
+assert i * i == -1 and i**2 == K(-1) and i**4 == 1 and i**-1 == -i
+assert K(p + 3, -2) == K(3, p - 2) and K(p) == 0 and not K(p)
+a = K(12345, 678)
+b = 3 * a - 1
+assert (b - 1) / 3 == a - K(2) / 3 and 1 / a == a.invert() and a / a == 1
+assert a.x0 == 12345 and a.x1 == 678 and (-a).x0 == p - 12345
+assert K.decode(a.encode()) == a and len(a.encode()) == 32
+assert hash(a) == hash(K(12345, 678)) and a != b
+r = (a * a).sqrt()
+assert r == a or r == -a
+assert a.legendre() == 1 and K(0).legendre() == 0
+n = next(K(k, 1) for k in range(2, 100) if K(k, 1).legendre() == -1)
+assert n.sqrt() is None
+g = (n**((p * p - 1) >> 10))
+assert g**512 == -1
+assert g.solve_dlp_2e(g**77, 10) == 77 and g.solve_dlp_2e(g, 9) is None
+for bad in ['K(0).invert()', 'a / 0', 'K(0)**-1']:
+    try:
+        eval(bad)
+        raise AssertionError(bad)
+    except ZeroDivisionError:
+        pass
+try:
+    K.decode(b'\\xff' * 32)
+    raise AssertionError
+except ValueError:
+    pass
+try:
+    a + 1.5
+    raise AssertionError
+except TypeError:
+    pass
+";
+            py.run(code, Some(&globals), None).unwrap();
+        });
+    }
+}